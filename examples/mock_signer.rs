@@ -0,0 +1,28 @@
+// examples/mock_signer.rs
+//
+// Serves the mock remote signing service (see src/mock_signer.rs) for manual
+// testing:
+//
+//   MOCK_SIGNER_KEYS="remote-alice=//Alice,remote-bob=//Bob" cargo run --example mock_signer
+//   DEV_MODE=true REMOTE_SIGNER_URL=http://127.0.0.1:3002 cargo run
+//
+// Never use it with real keys.
+
+#[path = "../src/mock_signer.rs"]
+mod mock_signer;
+
+use mock_signer::{parse_keys, router};
+
+/// Address the mock signer listens on
+const LISTEN_ADDR: &str = "127.0.0.1:3002";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let spec = std::env::var("MOCK_SIGNER_KEYS").unwrap_or_else(|_| "remote-alice=//Alice".into());
+    let app = router(parse_keys(&spec)?);
+
+    let listener = tokio::net::TcpListener::bind(LISTEN_ADDR).await?;
+    println!("🔏 Mock signer listening on http://{}", LISTEN_ADDR);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
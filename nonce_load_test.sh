@@ -0,0 +1,82 @@
+#!/bin/bash
+
+# Nonce manager load test for Chain A Backend
+# Signs with the dev accounts, so start the server with DEV_MODE=true
+#
+# Sends the same number of concurrent submissions twice: first all from one
+# account, then spread over six. Nonces are locked per account, so the second
+# run should reach several times the throughput of the first; with a single
+# global lock both runs take about as long. Every account's nonces must come
+# out unique in both runs.
+#
+#   REQUESTS=60 ./nonce_load_test.sh
+
+BASE_URL="http://127.0.0.1:3001"
+REQUESTS="${REQUESTS:-60}"
+ACCOUNTS=("//Alice" "//Bob" "//Charlie" "//Dave" "//Eve" "//Ferdie")
+OUT_DIR="$(mktemp -d)"
+trap 'rm -rf "${OUT_DIR}"' EXIT
+
+# Sends REQUESTS submissions concurrently, round-robin over the given signers,
+# and prints the throughput. Responses are stored as <run>-<i>.json
+run_load() {
+  local run="$1"
+  shift
+  local signers=("$@")
+  local start end
+
+  start=$(date +%s.%N)
+  for ((i = 0; i < REQUESTS; i++)); do
+    local signer="${signers[$((i % ${#signers[@]}))]}"
+    curl -s -X POST "${BASE_URL}/do-something" \
+      -H "Content-Type: application/json" \
+      -d "{\"value\": ${i}, \"signer\": \"${signer}\", \"wait_for\": \"submitted\"}" \
+      > "${OUT_DIR}/${run}-${i}.json" &
+  done
+  wait
+  end=$(date +%s.%N)
+
+  awk -v n="${REQUESTS}" -v s="${start}" -v e="${end}" -v accounts="${#signers[@]}" \
+    'BEGIN { printf "%d requests from %d account(s) in %.2fs: %.1f req/s\n", n, accounts, e - s, n / (e - s) }'
+}
+
+# Reports failed submissions and nonces handed out twice to the same signer
+check_nonces() {
+  local run="$1"
+  local accounts="$2"
+  local responses=("${OUT_DIR}/${run}"-*.json)
+
+  local failed
+  failed=$(jq -s '[.[] | select(.success != true)] | length' "${responses[@]}")
+  echo "Failed submissions: ${failed}"
+
+  # The signer isn't echoed back, so pair each response with its request index
+  local duplicates=0
+  for signer_index in $(seq 0 $((accounts - 1))); do
+    local nonces=()
+    for ((i = signer_index; i < REQUESTS; i += accounts)); do
+      nonces+=("$(jq -r '.attempts[0].nonce // empty' "${OUT_DIR}/${run}-${i}.json")")
+    done
+    local unique
+    unique=$(printf '%s\n' "${nonces[@]}" | grep -v '^$' | sort -u | wc -l)
+    local total
+    total=$(printf '%s\n' "${nonces[@]}" | grep -vc '^$')
+    duplicates=$((duplicates + total - unique))
+  done
+  echo "Duplicate nonces: ${duplicates}"
+}
+
+echo "=== Nonce Manager Load Test ==="
+echo
+
+echo "1. ${REQUESTS} submissions from one account:"
+run_load single "${ACCOUNTS[0]}"
+check_nonces single 1
+echo
+
+echo "2. ${REQUESTS} submissions spread over ${#ACCOUNTS[@]} accounts:"
+run_load spread "${ACCOUNTS[@]}"
+check_nonces spread "${#ACCOUNTS[@]}"
+echo
+
+echo "=== Test Complete ==="
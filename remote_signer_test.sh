@@ -0,0 +1,31 @@
+#!/bin/bash
+
+# Remote signer test for Chain A Backend
+# Start the mock signer and point the server at it first:
+#   cargo run --example mock_signer
+#   DEV_MODE=true REMOTE_SIGNER_URL=http://127.0.0.1:3002 cargo run
+
+BASE_URL="http://127.0.0.1:3001"
+SIGNER_URL="http://127.0.0.1:3002"
+
+echo "=== Remote Signer Test ==="
+echo
+
+echo "1. Keys offered by the mock signer:"
+curl -s -X GET "${SIGNER_URL}/keys"
+echo
+echo
+
+echo "2. Managed signers (remote-alice should have backend \"remote\"):"
+curl -s -X GET "${BASE_URL}/keys"
+echo
+echo
+
+echo "3. Transaction signed by the remote signer:"
+curl -s -X POST "${BASE_URL}/do-something" \
+  -H "Content-Type: application/json" \
+  -d '{"value": 7, "signer": "remote-alice", "wait_for": "in_block"}'
+echo
+echo
+
+echo "=== Test Complete ==="
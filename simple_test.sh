@@ -1,6 +1,7 @@
 #!/bin/bash
 
 # Simple API test for Chain A Backend
+# Signs with //Alice, so start the server with DEV_MODE=true
 
 echo "=== Chain A Backend API Test ==="
 echo
@@ -0,0 +1,383 @@
+// src/batch.rs
+//
+// Batch submission with pipelined consecutive nonces
+//
+// Submitting hundreds of transactions one request at a time means one round
+// trip and one nonce lookup per transaction. A batch instead reserves a
+// contiguous nonce range in one step, signs every transaction up front,
+// pushes them all into the pool back to back, and then follows every
+// transaction's progress in parallel.
+//
+// Because the transactions depend on each other through their nonces, a
+// failure in the middle of a batch needs care:
+// - Signing fails        -> nothing was submitted, the whole range is handed back
+// - Submission rejected  -> later items are not submitted, their nonces are handed back
+// - Dropped from pool    -> later items would be stuck, so the gap is filled with a System.remark
+// - Stale or usurped     -> another transaction took the nonce, nothing to do
+// - Dispatch error       -> the nonce was used by the failed call, nothing to do
+//
+// Nonces are only handed back while no other request reserved a nonce after
+// the batch (see `NonceManager::release_nonces`); otherwise the gap is left
+// to the periodic gap check. A nonce that a /replace or /cancel request took
+// over belongs to the replacement and is left alone.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use subxt::tx::DynamicPayload;
+use tokio::task::JoinSet;
+
+use crate::chain::chain_a;
+use crate::handlers::{watch_progress, AppState, TransactionResponse, TxFailure, TxStage};
+use crate::receipts::CallInfo;
+use crate::retry::FailureKind;
+use crate::signer::TxSigner;
+use crate::transaction::{
+    create_signed_transaction_with_nonce, ManagedTransaction, SigningStrategy, TxParams,
+};
+
+/// A single call in a batch request
+#[derive(Debug, Deserialize)]
+pub struct BatchCall {
+    /// The pallet name as it appears in metadata (e.g. "Template")
+    pub pallet: String,
+    /// The call name as it appears in metadata (e.g. "do_something")
+    pub call: String,
+    /// The call arguments, in the same format as POST /tx/{pallet}/{call}
+    #[serde(default)]
+    pub args: serde_json::Value,
+}
+
+/// Outcome of one transaction in a batch
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchItemResult {
+    /// Position of the item in the batch
+    pub index: usize,
+    /// The nonce reserved for this item
+    pub nonce: u64,
+    /// Whether the transaction was accepted into the pool
+    pub submitted: bool,
+    /// The transaction result, in the same format as single submissions
+    pub result: TransactionResponse,
+    /// Hash of the System.remark that took over this item's nonce, if any
+    pub gap_filled_by: Option<String>,
+}
+
+/// Response payload for the /tx/batch endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchResponse {
+    /// Whether every transaction in the batch succeeded
+    pub success: bool,
+    /// The first nonce of the reserved range (null if no range was reserved)
+    pub first_nonce: Option<u64>,
+    /// Number of transactions accepted into the pool
+    pub submitted: usize,
+    /// Number of transactions that reached the requested stage successfully
+    pub succeeded: usize,
+    /// Number of transactions that failed or were never submitted
+    pub failed: usize,
+    /// Per-item results in batch order
+    pub items: Vec<BatchItemResult>,
+    /// Error message if the batch failed as a whole
+    pub error: Option<String>,
+}
+
+impl BatchResponse {
+    /// Builds a failed response for a batch that never reached submission
+    pub fn failure(error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            first_nonce: None,
+            submitted: 0,
+            succeeded: 0,
+            failed: 0,
+            items: Vec::new(),
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Signs, submits and tracks a batch of calls from one signer
+///
+/// # Arguments
+/// * `state` - Shared application state
+/// * `signer` - The signer of every transaction in the batch
+/// * `calls` - The calls to submit in nonce order, with their receipt descriptions
+/// * `params` - Mortality and tip applied to every transaction
+/// * `wait_for` - The lifecycle stage to wait for on each transaction
+///
+/// # Returns
+/// A response with one result per call; failures are reported per item
+pub async fn submit_batch(
+    state: &AppState,
+    signer: Arc<dyn TxSigner>,
+    calls: Vec<(DynamicPayload, CallInfo)>,
+    params: TxParams,
+    wait_for: TxStage,
+) -> BatchResponse {
+    let account_id = signer.account_id();
+    let count = calls.len();
+
+    // Reserve the whole range at once so no other request can take a nonce in between
+    // The batch holds the lease until every item is settled
+    let lease = match state
+        .nonce_manager
+        .reserve_nonces(&account_id, count as u64)
+        .await
+    {
+        Ok(lease) => lease,
+        Err(e) => {
+            log::error!("❌ Failed to reserve {} nonces: {:?}", count, e);
+            return BatchResponse::failure(format!("Failed to get nonce: {:?}", e));
+        }
+    };
+    let first_nonce = lease.nonce();
+    state.nonce_manager.remember_signer(&signer).await;
+    log::info!(
+        "📦 Batch of {} reserved nonces {}..{}",
+        count,
+        first_nonce,
+        first_nonce + count as u64
+    );
+
+    // Sign everything before submitting anything
+    let mut signed = Vec::with_capacity(count);
+    for (index, (call, call_info)) in calls.iter().enumerate() {
+        let nonce = first_nonce + index as u64;
+        // A fallback nonce would break the contiguous range, so signing is strict
+        match create_signed_transaction_with_nonce(
+            &state.client,
+            &state.rpc,
+            call,
+            signer.as_ref(),
+            nonce,
+            &params,
+            SigningStrategy::Strict,
+        )
+        .await
+        {
+            Ok(signed_tx) => {
+                state.metrics.record_signing(signed_tx.path);
+                let tx = ManagedTransaction {
+                    extrinsic: signed_tx.extrinsic,
+                    account_id: account_id.clone(),
+                    nonce,
+                    tip: params.tip,
+                    reserved: true,
+                    signing_path: signed_tx.path,
+                    lease: None,
+                };
+                state.receipts.record_signed(&tx, call_info);
+                signed.push(tx);
+            }
+            Err(e) => {
+                // Nothing is in the pool yet, so the whole range can be handed back
+                state
+                    .nonce_manager
+                    .release_nonces(&account_id, first_nonce, count as u64)
+                    .await;
+
+                log::error!("❌ Failed to sign batch item {}: {:?}", index, e);
+                let mut response =
+                    BatchResponse::failure(format!("Failed to sign item {}: {:?}", index, e));
+                response.failed = count;
+                return response;
+            }
+        }
+    }
+
+    // Push every transaction into the pool in nonce order without waiting for blocks
+    let mut items = Vec::with_capacity(count);
+    let mut in_flight = Vec::with_capacity(count);
+    let mut rejected_at = None;
+    for (index, tx) in signed.into_iter().enumerate() {
+        let nonce = first_nonce + index as u64;
+
+        if let Some(rejected) = rejected_at {
+            items.push(BatchItemResult {
+                index,
+                nonce,
+                submitted: false,
+                result: TransactionResponse::failure(format!(
+                    "Not submitted: item {} was rejected",
+                    rejected
+                )),
+                gap_filled_by: None,
+            });
+            continue;
+        }
+
+        let hash = tx.extrinsic.hash();
+        match tx.extrinsic.submit_and_watch().await {
+            Ok(progress) => {
+                state.receipts.mark_submitted(hash);
+                in_flight.push((index, nonce, hash, progress));
+            }
+            Err(e) => {
+                // Anything submitted after this would wait forever behind the gap,
+                // so stop here and hand back the unused nonces. Bytes that may
+                // have reached the node keep their nonce reserved.
+                let unused = match FailureKind::from_submit_error(&e) {
+                    FailureKind::Transient => nonce + 1,
+                    _ => nonce,
+                };
+                state
+                    .nonce_manager
+                    .release_nonces(&account_id, unused, first_nonce + count as u64 - unused)
+                    .await;
+
+                log::error!("❌ Batch item {} rejected: {:?}", index, e);
+                let result = TransactionResponse::failure(format!("Failed to submit: {:?}", e));
+                state.receipts.mark_failed(hash, &result);
+                rejected_at = Some(index);
+                items.push(BatchItemResult {
+                    index,
+                    nonce,
+                    submitted: false,
+                    result,
+                    gap_filled_by: None,
+                });
+            }
+        }
+    }
+
+    let submitted = in_flight.len();
+    let last_in_flight = in_flight.last().map(|(index, _, _, _)| *index);
+
+    // Follow every submitted transaction in parallel
+    let mut tasks = JoinSet::new();
+    for (index, nonce, hash, progress) in in_flight {
+        let state = state.clone();
+        let signer = signer.clone();
+        let account_id = account_id.clone();
+
+        tasks.spawn(async move {
+            let mut gap_filled_by = None;
+            let result = match watch_progress(&state, progress, wait_for, None).await {
+                Ok(response) => response,
+                Err(TxFailure::NotIncluded(kind, response)) => {
+                    if let Some(replacement) = state.receipts.replaced_by(hash) {
+                        // A /replace or /cancel request owns this nonce now
+                        log::info!(
+                            "🔀 Batch item {} was replaced by {}, leaving nonce {} to it",
+                            index,
+                            replacement,
+                            nonce
+                        );
+                    } else if matches!(kind, FailureKind::Stale | FailureKind::Usurped) {
+                        // Another transaction took the nonce, so nothing waits on it
+                        log::warn!("⚠️ Batch item {} lost nonce {} ({:?})", index, nonce, kind);
+                    } else if Some(index) == last_in_flight {
+                        // Nothing of ours comes after this nonce, so it can be reused
+                        // unless another request reserved a later one meanwhile
+                        state.nonce_manager.release_nonce(&account_id, nonce).await;
+                    } else {
+                        // Later items are waiting on this nonce: take it over with a no-op
+                        match fill_nonce_gap(&state, signer, nonce, &params).await {
+                            Ok(hash) => gap_filled_by = Some(hash),
+                            Err(e) => log::error!("❌ Failed to fill nonce gap {}: {}", nonce, e),
+                        }
+                    }
+                    response
+                }
+                Err(TxFailure::DispatchFailed(response)) | Err(TxFailure::Unknown(response)) => {
+                    response
+                }
+            };
+
+            BatchItemResult {
+                index,
+                nonce,
+                submitted: true,
+                result,
+                gap_filled_by,
+            }
+        });
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(item) => items.push(item),
+            Err(e) => log::error!("❌ Batch tracking task failed: {:?}", e),
+        }
+    }
+    items.sort_by_key(|item| item.index);
+
+    // Every item is settled; nonces still missing are up to the gap check now
+    drop(lease);
+
+    let succeeded = items.iter().filter(|item| item.result.success).count();
+    let failed = count - succeeded;
+    log::info!(
+        "📦 Batch finished: {} submitted, {} succeeded, {} failed",
+        submitted,
+        succeeded,
+        failed
+    );
+
+    BatchResponse {
+        success: failed == 0,
+        first_nonce: Some(first_nonce),
+        submitted,
+        succeeded,
+        failed,
+        items,
+        error: None,
+    }
+}
+
+/// Submits a System.remark at `nonce` so that transactions behind it can proceed
+///
+/// The remark is only submitted, not waited for; its receipt follows it to
+/// finalization in the background.
+///
+/// # Returns
+/// * `Ok(String)` - The hash of the filler transaction
+/// * `Err(String)` - If the filler could not be signed or submitted
+pub async fn fill_nonce_gap(
+    state: &AppState,
+    signer: Arc<dyn TxSigner>,
+    nonce: u64,
+    params: &TxParams,
+) -> Result<String, String> {
+    let call = chain_a::tx().system().remark(Vec::new());
+    let account_id = signer.account_id();
+
+    // The filler is only useful at exactly this nonce
+    let signed = create_signed_transaction_with_nonce(
+        &state.client,
+        &state.rpc,
+        &call,
+        signer.as_ref(),
+        nonce,
+        params,
+        SigningStrategy::Strict,
+    )
+    .await
+    .map_err(|e| format!("Failed to create filler: {:?}", e))?;
+    state.metrics.record_signing(signed.path);
+    let tx = ManagedTransaction {
+        extrinsic: signed.extrinsic,
+        account_id,
+        nonce,
+        tip: params.tip,
+        reserved: true,
+        signing_path: signed.path,
+        lease: None,
+    };
+    state.receipts.record_signed(
+        &tx,
+        &CallInfo::new("System", "remark", serde_json::json!({ "remark": "0x" })),
+    );
+
+    let hash = tx.extrinsic.hash();
+    let progress = tx
+        .extrinsic
+        .submit_and_watch()
+        .await
+        .map_err(|e| format!("Failed to submit filler: {:?}", e))?;
+    state.receipts.mark_submitted(hash);
+    let _ = watch_progress(state, progress, TxStage::Submitted, None).await;
+
+    log::warn!("🩹 Filled nonce gap {} with remark {:?}", nonce, hash);
+    Ok(format!("{:?}", hash))
+}
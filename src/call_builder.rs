@@ -0,0 +1,520 @@
+// src/call_builder.rs
+//
+// Builds dynamic extrinsic calls from JSON arguments
+//
+// The generated `chain_a` API only covers calls we hand-write handlers for.
+// This module lets the backend submit ANY pallet call by looking up the call's
+// argument types in the runtime metadata and converting plain JSON into
+// `scale_value::Value`s that subxt can encode against those types.
+
+use scale_info::{form::PortableForm, Field, PortableRegistry, TypeDef, TypeDefPrimitive};
+use serde_json::Value as JsonValue;
+use std::str::FromStr;
+use subxt::{
+    dynamic::Value,
+    ext::scale_value::{Composite, ValueDef},
+    tx::DynamicPayload,
+    utils::AccountId32,
+    Metadata,
+};
+
+/// Builds a dynamic call payload for `pallet.call` from JSON arguments
+///
+/// The arguments can be given either as a JSON object keyed by the call's
+/// argument names, or as a JSON array in declaration order. Calls without
+/// arguments accept `null`, `{}` or `[]`.
+///
+/// Each argument is converted using the type information from the runtime
+/// metadata, which allows friendly JSON inputs:
+/// - Numbers as JSON numbers or decimal strings (for values above 2^53),
+///   checked against the width of the integer type
+/// - Account IDs as SS58 addresses or 0x-prefixed hex
+/// - Byte vectors as 0x-prefixed hex or plain UTF-8 strings
+/// - Enums as `"Variant"` or `{ "Variant": <fields> }`
+/// - Options as `null` or the inner value
+///
+/// # Arguments
+/// * `metadata` - The runtime metadata used to resolve argument types
+/// * `pallet` - The pallet name as it appears in metadata (e.g. "Balances")
+/// * `call` - The call name as it appears in metadata (e.g. "transfer_keep_alive")
+/// * `args` - The JSON arguments for the call
+///
+/// # Returns
+/// * `Ok(DynamicPayload)` - A call that can be signed like any static payload
+/// * `Err(String)` - If the call does not exist or the arguments don't match;
+///   argument errors name the offending field (e.g. `dest.Id`)
+///
+/// # Example Usage
+/// ```rust
+/// let args = serde_json::json!({ "dest": "5FHneW46...", "value": "1000000000000" });
+/// let call = build_dynamic_call(&client.metadata(), "Balances", "transfer_keep_alive", &args)?;
+/// ```
+pub fn build_dynamic_call(
+    metadata: &Metadata,
+    pallet: &str,
+    call: &str,
+    args: &JsonValue,
+) -> Result<DynamicPayload, String> {
+    let pallet_metadata = metadata
+        .pallet_by_name(pallet)
+        .ok_or_else(|| format!("Unknown pallet '{}'", pallet))?;
+    let call_variant = pallet_metadata
+        .call_variant_by_name(call)
+        .ok_or_else(|| format!("Unknown call '{}' in pallet '{}'", call, pallet))?;
+
+    let fields = fields_to_composite(metadata.types(), &call_variant.fields, args, "")
+        .map_err(|e| format!("Invalid arguments for {}.{}: {}", pallet, call, e))?;
+
+    Ok(subxt::dynamic::tx(pallet, call, fields))
+}
+
+/// Converts JSON into a composite matching a list of metadata fields
+///
+/// Objects are matched by field name, arrays by position. A single field
+/// may also be given directly without being wrapped in an array/object.
+/// `path` locates `json` in the request for error messages (empty at the
+/// top level).
+fn fields_to_composite(
+    types: &PortableRegistry,
+    fields: &[Field<PortableForm>],
+    json: &JsonValue,
+    path: &str,
+) -> Result<Composite<()>, String> {
+    let named = fields.iter().all(|f| f.name.is_some()) && !fields.is_empty();
+    // Where a field's value sits in the request when given by name or position
+    let field_path = |i: usize, f: &Field<PortableForm>| match f.name.as_deref() {
+        Some(name) if named => child_path(path, name),
+        _ => item_path(path, i),
+    };
+
+    let values: Vec<Value> = match json {
+        JsonValue::Null if fields.is_empty() => Vec::new(),
+        JsonValue::Object(map) if fields.is_empty() && map.is_empty() => Vec::new(),
+        JsonValue::Object(map) if named => {
+            if let Some(unknown) = map
+                .keys()
+                .find(|k| !fields.iter().any(|f| f.name.as_deref() == Some(k.as_str())))
+            {
+                return Err(at(path, format!("unexpected field '{}'", unknown)));
+            }
+            fields
+                .iter()
+                .enumerate()
+                .map(|(i, f)| {
+                    let name = f.name.as_deref().unwrap_or_default();
+                    let value = map
+                        .get(name)
+                        .ok_or_else(|| at(path, format!("missing field '{}'", name)))?;
+                    json_to_value(types, f.ty.id, value, &field_path(i, f))
+                })
+                .collect::<Result<_, String>>()?
+        }
+        JsonValue::Array(items) if items.len() == fields.len() => fields
+            .iter()
+            .zip(items)
+            .enumerate()
+            .map(|(i, (f, item))| json_to_value(types, f.ty.id, item, &field_path(i, f)))
+            .collect::<Result<_, String>>()?,
+        // A bare value for a single field sits where the field would
+        other if fields.len() == 1 => {
+            let field_path = match fields[0].name.as_deref() {
+                Some(name) => child_path(path, name),
+                None => path.to_string(),
+            };
+            vec![json_to_value(types, fields[0].ty.id, other, &field_path)?]
+        }
+        _ => {
+            return Err(at(
+                path,
+                format!(
+                    "expected {} field(s) as an object or array, got {}",
+                    fields.len(),
+                    json
+                ),
+            ))
+        }
+    };
+
+    if named {
+        let names = fields.iter().map(|f| f.name.clone().unwrap_or_default());
+        Ok(Composite::named(names.zip(values)))
+    } else {
+        Ok(Composite::unnamed(values))
+    }
+}
+
+/// Converts a JSON value into a `Value` shaped like the metadata type `type_id`
+///
+/// Errors name `path`, the value's location in the request (e.g.
+/// `dest.Id` or `calls[2].value`).
+fn json_to_value(
+    types: &PortableRegistry,
+    type_id: u32,
+    json: &JsonValue,
+    path: &str,
+) -> Result<Value, String> {
+    let ty = types
+        .resolve(type_id)
+        .ok_or_else(|| at(path, format!("type {} not found in metadata", type_id)))?;
+
+    match &ty.type_def {
+        TypeDef::Composite(composite) => {
+            let fields = fields_to_composite(types, &composite.fields, json, path)?;
+            Ok(Value::without_context(ValueDef::Composite(fields)))
+        }
+        TypeDef::Variant(variant) => {
+            let is_option = ty.path.segments.last().map(String::as_str) == Some("Option");
+
+            // `null` means `None` for Options
+            if json.is_null() && is_option {
+                return Ok(Value::unnamed_variant("None", []));
+            }
+
+            let (name, inner, inner_path) = match json {
+                JsonValue::String(name) if variant.variants.iter().any(|v| &v.name == name) => {
+                    (name.as_str(), &JsonValue::Null, path.to_string())
+                }
+                JsonValue::Object(map) if map.len() == 1 => {
+                    let (name, inner) = map.iter().next().expect("map has one entry");
+                    if variant.variants.iter().any(|v| &v.name == name) {
+                        (name.as_str(), inner, child_path(path, name))
+                    } else if is_option {
+                        ("Some", json, path.to_string())
+                    } else {
+                        return Err(at(path, format!("unknown variant '{}'", name)));
+                    }
+                }
+                // A bare inner value for an Option means `Some(value)`
+                _ if is_option => ("Some", json, path.to_string()),
+                // A bare address for a MultiAddress means `Id(address)`
+                JsonValue::String(_) if variant.variants.iter().any(|v| v.name == "Id") => {
+                    ("Id", json, path.to_string())
+                }
+                _ => return Err(at(path, format!("expected an enum variant, got {}", json))),
+            };
+
+            let selected = variant
+                .variants
+                .iter()
+                .find(|v| v.name == name)
+                .ok_or_else(|| at(path, format!("unknown variant '{}'", name)))?;
+            let fields = fields_to_composite(types, &selected.fields, inner, &inner_path)?;
+            Ok(Value::variant(name, fields))
+        }
+        TypeDef::Sequence(sequence) => {
+            if is_u8(types, sequence.type_param.id) {
+                if let JsonValue::String(s) = json {
+                    let bytes = match s.strip_prefix("0x") {
+                        Some(hex) => decode_hex(hex).map_err(|e| at(path, e))?,
+                        None => s.as_bytes().to_vec(),
+                    };
+                    return Ok(Value::from_bytes(bytes));
+                }
+            }
+            let items = json
+                .as_array()
+                .ok_or_else(|| at(path, format!("expected an array, got {}", json)))?;
+            let values = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    json_to_value(types, sequence.type_param.id, item, &item_path(path, i))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Value::unnamed_composite(values))
+        }
+        TypeDef::Array(array) => {
+            if is_u8(types, array.type_param.id) {
+                if let JsonValue::String(s) = json {
+                    let bytes = match s.strip_prefix("0x") {
+                        Some(hex) => decode_hex(hex).map_err(|e| at(path, e))?,
+                        // A 32 byte array given as a string is most likely an SS58 address
+                        None if array.len == 32 => AccountId32::from_str(s)
+                            .map_err(|e| {
+                                at(path, format!("invalid SS58 address '{}': {:?}", s, e))
+                            })?
+                            .0
+                            .to_vec(),
+                        None => {
+                            return Err(at(path, format!("expected 0x-prefixed hex, got '{}'", s)))
+                        }
+                    };
+                    if bytes.len() != array.len as usize {
+                        return Err(at(
+                            path,
+                            format!("expected {} bytes, got {}", array.len, bytes.len()),
+                        ));
+                    }
+                    return Ok(Value::from_bytes(bytes));
+                }
+            }
+            let items = json
+                .as_array()
+                .ok_or_else(|| at(path, format!("expected an array, got {}", json)))?;
+            if items.len() != array.len as usize {
+                return Err(at(
+                    path,
+                    format!("expected {} items, got {}", array.len, items.len()),
+                ));
+            }
+            let values = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    json_to_value(types, array.type_param.id, item, &item_path(path, i))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Value::unnamed_composite(values))
+        }
+        TypeDef::Tuple(tuple) => {
+            if tuple.fields.is_empty() {
+                return Ok(Value::unnamed_composite([]));
+            }
+            let items = json
+                .as_array()
+                .filter(|items| items.len() == tuple.fields.len())
+                .ok_or_else(|| {
+                    at(
+                        path,
+                        format!("expected a {}-item array, got {}", tuple.fields.len(), json),
+                    )
+                })?;
+            let values = tuple
+                .fields
+                .iter()
+                .zip(items)
+                .enumerate()
+                .map(|(i, (field, item))| json_to_value(types, field.id, item, &item_path(path, i)))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Value::unnamed_composite(values))
+        }
+        TypeDef::Primitive(primitive) => {
+            json_to_primitive(primitive, json).map_err(|e| at(path, e))
+        }
+        TypeDef::Compact(compact) => json_to_value(types, compact.type_param.id, json, path),
+        TypeDef::BitSequence(_) => Err(at(path, "bit sequences are not supported".to_string())),
+    }
+}
+
+/// Converts a JSON value into a primitive `Value`
+///
+/// Integers are checked against the range of the primitive, so a value that
+/// doesn't fit is rejected here instead of failing to encode later.
+fn json_to_primitive(primitive: &TypeDefPrimitive, json: &JsonValue) -> Result<Value, String> {
+    match primitive {
+        TypeDefPrimitive::Bool => json
+            .as_bool()
+            .map(Value::bool)
+            .ok_or_else(|| format!("expected a boolean, got {}", json)),
+        TypeDefPrimitive::Char => {
+            let mut chars = json.as_str().map(str::chars);
+            match chars.as_mut().map(|chars| (chars.next(), chars.next())) {
+                Some((Some(c), None)) => Ok(Value::char(c)),
+                _ => Err(format!("expected a single character, got {}", json)),
+            }
+        }
+        TypeDefPrimitive::Str => json
+            .as_str()
+            .map(Value::string)
+            .ok_or_else(|| format!("expected a string, got {}", json)),
+        TypeDefPrimitive::U8
+        | TypeDefPrimitive::U16
+        | TypeDefPrimitive::U32
+        | TypeDefPrimitive::U64
+        | TypeDefPrimitive::U128 => {
+            let (max, name) = match primitive {
+                TypeDefPrimitive::U8 => (u128::from(u8::MAX), "u8"),
+                TypeDefPrimitive::U16 => (u128::from(u16::MAX), "u16"),
+                TypeDefPrimitive::U32 => (u128::from(u32::MAX), "u32"),
+                TypeDefPrimitive::U64 => (u128::from(u64::MAX), "u64"),
+                _ => (u128::MAX, "u128"),
+            };
+            let number = match json {
+                JsonValue::Number(n) => n.as_u64().map(u128::from),
+                JsonValue::String(s) => s.parse::<u128>().ok(),
+                _ => None,
+            };
+            match number {
+                Some(n) if n <= max => Ok(Value::u128(n)),
+                Some(n) => Err(format!("{} is out of range for {}", n, name)),
+                None => Err(format!("expected an unsigned integer, got {}", json)),
+            }
+        }
+        TypeDefPrimitive::I8
+        | TypeDefPrimitive::I16
+        | TypeDefPrimitive::I32
+        | TypeDefPrimitive::I64
+        | TypeDefPrimitive::I128 => {
+            let (min, max, name) = match primitive {
+                TypeDefPrimitive::I8 => (i128::from(i8::MIN), i128::from(i8::MAX), "i8"),
+                TypeDefPrimitive::I16 => (i128::from(i16::MIN), i128::from(i16::MAX), "i16"),
+                TypeDefPrimitive::I32 => (i128::from(i32::MIN), i128::from(i32::MAX), "i32"),
+                TypeDefPrimitive::I64 => (i128::from(i64::MIN), i128::from(i64::MAX), "i64"),
+                _ => (i128::MIN, i128::MAX, "i128"),
+            };
+            let number = match json {
+                JsonValue::Number(n) => n.as_i64().map(i128::from),
+                JsonValue::String(s) => s.parse::<i128>().ok(),
+                _ => None,
+            };
+            match number {
+                Some(n) if (min..=max).contains(&n) => Ok(Value::i128(n)),
+                Some(n) => Err(format!("{} is out of range for {}", n, name)),
+                None => Err(format!("expected an integer, got {}", json)),
+            }
+        }
+        TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => {
+            Err("256-bit integers are not supported".to_string())
+        }
+    }
+}
+
+/// Extends a field path by a field or variant name, e.g. `dest` to `dest.Id`
+fn child_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+/// Extends a field path by a position, e.g. `calls` to `calls[2]`
+fn item_path(path: &str, index: usize) -> String {
+    format!("{}[{}]", path, index)
+}
+
+/// Prefixes an error with the path of the value it is about
+fn at(path: &str, error: String) -> String {
+    if path.is_empty() {
+        error
+    } else {
+        format!("{}: {}", path, error)
+    }
+}
+
+/// Returns true if `type_id` resolves to the `u8` primitive
+fn is_u8(types: &PortableRegistry, type_id: u32) -> bool {
+    matches!(
+        types.resolve(type_id).map(|ty| &ty.type_def),
+        Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+    )
+}
+
+/// Decodes a hex string (without the 0x prefix) into bytes
+fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    hex::decode(hex).map_err(|e| format!("invalid hex: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::chain_a;
+    use serde_json::json;
+    use subxt::{ext::codec::Decode, tx::Payload};
+
+    fn metadata() -> Metadata {
+        Metadata::decode(&mut &include_bytes!("metadata.scale")[..]).expect("valid metadata")
+    }
+
+    /// The call data `build_dynamic_call` produces for the JSON arguments
+    fn call_data(pallet: &str, call: &str, args: JsonValue) -> Result<Vec<u8>, String> {
+        let metadata = metadata();
+        let payload = build_dynamic_call(&metadata, pallet, call, &args)?;
+        Ok(payload.encode_call_data(&metadata).expect("encodable call"))
+    }
+
+    #[test]
+    fn json_arguments_encode_like_the_static_call() {
+        let metadata = metadata();
+        let expected = chain_a::tx()
+            .template()
+            .do_something(42)
+            .encode_call_data(&metadata)
+            .unwrap();
+        for args in [
+            json!({ "something": 42 }),
+            json!([42]),
+            json!(42),
+            json!("42"),
+        ] {
+            assert_eq!(
+                call_data("Template", "do_something", args),
+                Ok(expected.clone())
+            );
+        }
+
+        let dest =
+            AccountId32::from_str("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty").unwrap();
+        let expected = chain_a::tx()
+            .balances()
+            .transfer_keep_alive(dest.clone().into(), 1_000_000_000_000)
+            .encode_call_data(&metadata)
+            .unwrap();
+        for dest in [json!(dest.to_string()), json!({ "Id": dest.to_string() })] {
+            let args = json!({ "dest": dest, "value": "1000000000000" });
+            assert_eq!(
+                call_data("Balances", "transfer_keep_alive", args),
+                Ok(expected.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn integers_are_checked_against_their_width() {
+        assert_eq!(
+            call_data("Template", "do_something", json!({ "something": 4_294_967_296u64 })),
+            Err("Invalid arguments for Template.do_something: something: 4294967296 is out of range for u32".to_string())
+        );
+        assert!(call_data("Template", "do_something", json!({ "something": u32::MAX })).is_ok());
+
+        assert!(json_to_primitive(&TypeDefPrimitive::U8, &json!(255)).is_ok());
+        assert!(json_to_primitive(&TypeDefPrimitive::U8, &json!(256)).is_err());
+        assert!(json_to_primitive(&TypeDefPrimitive::U8, &json!(-1)).is_err());
+        assert!(json_to_primitive(&TypeDefPrimitive::I8, &json!(-128)).is_ok());
+        assert!(json_to_primitive(&TypeDefPrimitive::I8, &json!("128")).is_err());
+        assert!(json_to_primitive(&TypeDefPrimitive::U128, &json!(u128::MAX.to_string())).is_ok());
+    }
+
+    #[test]
+    fn characters_are_exactly_one_char() {
+        let mut registry = scale_info::Registry::new();
+        let id = registry.register_type(&scale_info::meta_type::<char>()).id;
+        let types: PortableRegistry = registry.into();
+
+        assert!(json_to_value(&types, id, &json!("a"), "letter").is_ok());
+        assert!(json_to_value(&types, id, &json!("é"), "letter").is_ok());
+        assert_eq!(
+            json_to_value(&types, id, &json!("ab"), "letters[1]"),
+            Err("letters[1]: expected a single character, got \"ab\"".to_string())
+        );
+        assert_eq!(
+            json_to_value(&types, id, &json!(""), "letter"),
+            Err("letter: expected a single character, got \"\"".to_string())
+        );
+        assert!(json_to_value(&types, id, &json!(97), "letter").is_err());
+    }
+
+    #[test]
+    fn errors_name_the_field_path() {
+        let args = json!({ "dest": { "Id": "0x1234" }, "value": 1 });
+        assert_eq!(
+            call_data("Balances", "transfer_keep_alive", args),
+            Err("Invalid arguments for Balances.transfer_keep_alive: dest.Id: expected 32 bytes, got 2".to_string())
+        );
+
+        let args =
+            json!({ "dest": "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty", "value": -1 });
+        assert_eq!(
+            call_data("Balances", "transfer_keep_alive", args),
+            Err("Invalid arguments for Balances.transfer_keep_alive: value: expected an unsigned integer, got -1".to_string())
+        );
+
+        let args = json!({ "something": 1, "extra": 2 });
+        assert_eq!(
+            call_data("Template", "do_something", args),
+            Err(
+                "Invalid arguments for Template.do_something: unexpected field 'extra'".to_string()
+            )
+        );
+    }
+}
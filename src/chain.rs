@@ -0,0 +1,77 @@
+// src/chain.rs
+//
+// Runtime types and the chain queries the nonce manager relies on
+//
+// `chain_a` is generated from the runtime metadata and gives typed access to
+// the runtime's calls and storage. `NonceQueries` is everything the nonce
+// manager needs to know from the node: the next nonce including the pool, and
+// the nonce at the latest finalized block. `ChainNonces` answers both over
+// the shared RPC connection; the nonce manager's tests substitute their own.
+
+use async_trait::async_trait;
+use subxt::{backend::legacy::LegacyRpcMethods, utils::AccountId32, OnlineClient, SubstrateConfig};
+
+// Include the generated runtime types from the blockchain's metadata
+// This macro generates Rust types and APIs based on the actual runtime
+#[subxt::subxt(runtime_metadata_path = "src/metadata.scale")]
+pub mod chain_a {}
+
+/// The node's view of an account's nonce
+#[async_trait]
+pub trait NonceQueries: Send + Sync {
+    /// The account's next nonce, counting its transactions in the node's pool
+    async fn pool_nonce(
+        &self,
+        account_id: &AccountId32,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// The account's nonce in `System.Account` at the latest finalized block
+    async fn finalized_nonce(
+        &self,
+        account_id: &AccountId32,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Answers nonce queries from the connected node
+pub struct ChainNonces {
+    /// Blockchain client for storage queries
+    client: OnlineClient<SubstrateConfig>,
+    /// Raw RPC methods, for the pool-aware `system_accountNextIndex`
+    rpc: LegacyRpcMethods<SubstrateConfig>,
+}
+
+impl ChainNonces {
+    /// Creates the queries over an existing connection
+    pub fn new(
+        client: OnlineClient<SubstrateConfig>,
+        rpc: LegacyRpcMethods<SubstrateConfig>,
+    ) -> Self {
+        Self { client, rpc }
+    }
+}
+
+#[async_trait]
+impl NonceQueries for ChainNonces {
+    async fn pool_nonce(
+        &self,
+        account_id: &AccountId32,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.rpc.system_account_next_index(account_id).await?)
+    }
+
+    /// Accounts that don't exist on chain yet start at nonce 0
+    async fn finalized_nonce(
+        &self,
+        account_id: &AccountId32,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        // at_latest() returns the latest finalized block
+        let account = self
+            .client
+            .storage()
+            .at_latest()
+            .await?
+            .fetch(&chain_a::storage().system().account(account_id.clone()))
+            .await?;
+        Ok(account.map_or(0, |info| u64::from(info.nonce)))
+    }
+}
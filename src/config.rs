@@ -0,0 +1,215 @@
+// src/config.rs
+//
+// Server configuration
+//
+// Settings are read once at startup from environment variables, falling back
+// to defaults that suit a local development node. Handlers get the parsed
+// configuration through AppState.
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use crate::gaps::GapAction;
+use crate::nonce_manager::NonceSource;
+use crate::retry::RetryPolicy;
+use crate::transaction::{SigningStrategy, TxParams};
+
+/// Runtime configuration for the backend
+///
+/// `Debug` leaves out the signer settings, which may hold secret URIs.
+#[derive(Clone)]
+pub struct Config {
+    /// Default number of blocks a transaction stays valid for (`TX_MORTALITY_PERIOD`)
+    ///
+    /// The period is anchored at the latest finalized block when signing.
+    /// `0` makes transactions immortal.
+    pub default_mortality_period: u64,
+    /// Default tip added to every transaction, in the chain's smallest unit (`TX_DEFAULT_TIP`)
+    pub default_tip: u128,
+    /// Maximum number of transactions accepted in one batch request (`BATCH_MAX_SIZE`)
+    pub max_batch_size: usize,
+    /// How long idempotency keys and their responses are remembered (`IDEMPOTENCY_WINDOW_SECS`)
+    pub idempotency_window: Duration,
+    /// Largest request or response body the idempotency layer buffers, in bytes
+    /// (`IDEMPOTENCY_MAX_BODY_BYTES`)
+    pub idempotency_max_body: usize,
+    /// Directory of the embedded transaction receipt database (`RECEIPTS_DB_PATH`)
+    pub receipts_db_path: String,
+    /// How long a prepared signing ticket stays valid (`TICKET_TTL_SECS`)
+    pub ticket_ttl: Duration,
+    /// Most open signing tickets one account may hold (`TICKET_MAX_PER_ACCOUNT`)
+    ///
+    /// Every ticket holds a nonce reservation, so unbounded tickets would let
+    /// a client stall an account's nonces until they expire.
+    pub max_tickets_per_account: usize,
+    /// Path of the encrypted keystore with the managed signers (`KEYSTORE_PATH`)
+    ///
+    /// The password is read from `KEYSTORE_PASSWORD` at startup and is not
+    /// part of the configuration, so it never ends up in logs.
+    pub keystore_path: String,
+    /// Signer used when a request doesn't name one (`DEFAULT_SIGNER`)
+    ///
+    /// Required outside dev mode (see `validate`); dev mode defaults to
+    /// `//Alice`.
+    pub default_signer: Option<String>,
+    /// Accept raw secret URIs as signers in request bodies (`DEV_MODE`)
+    ///
+    /// Meant for local development nodes only; otherwise requests must name
+    /// a signer from the keystore.
+    pub dev_mode: bool,
+    /// Signer that end users' accounts are derived from (`USER_MASTER_SIGNER`)
+    ///
+    /// A keystore alias (or a secret URI in dev mode). Without it, user
+    /// accounts are disabled.
+    pub user_master_signer: Option<String>,
+    /// Path of the signer authorization policy (`POLICY_PATH`)
+    ///
+    /// Startup fails if there is no file at this path, unless in dev mode.
+    /// `none` allows every request.
+    pub policy_path: String,
+    /// Base URL of a remote signing service whose keys become signers (`REMOTE_SIGNER_URL`)
+    pub remote_signer_url: Option<String>,
+    /// What to do when signing with the reserved nonce fails (`SIGNING_STRATEGY`)
+    ///
+    /// `strict` fails the request, `fallback` signs with the node's next nonce
+    /// after a nonce error and moves the nonce cache past it if it is ahead.
+    pub signing_strategy: SigningStrategy,
+    /// Where the nonce manager reads an account's next nonce from (`NONCE_SOURCE`)
+    ///
+    /// `pool` asks the node's `system_accountNextIndex`, which counts the
+    /// account's transactions waiting in the pool; `finalized` reads
+    /// `System.Account` at the latest finalized block.
+    pub nonce_source: NonceSource,
+    /// How long a reserved nonce may stay out of the pool before it counts as
+    /// a gap (`NONCE_GAP_TIMEOUT_SECS`)
+    pub nonce_gap_timeout: Duration,
+    /// What to do about nonce gaps (`NONCE_GAP_ACTION`)
+    ///
+    /// `fill` submits a `System.remark` at the missing nonce, `alert` only
+    /// logs and counts the gap.
+    pub nonce_gap_action: GapAction,
+    /// Tip of the `System.remark` that fills a nonce gap (`NONCE_GAP_TIP`)
+    ///
+    /// Defaults to `TX_DEFAULT_TIP`. The remark only displaces a transaction
+    /// still in the pool at the missing nonce if it pays a higher tip.
+    pub nonce_gap_tip: u128,
+    /// Retry policy for failed submissions
+    /// (`RETRY_MAX_ATTEMPTS`, `RETRY_INITIAL_BACKOFF_MS`, `RETRY_MAX_BACKOFF_MS`)
+    pub retry: RetryPolicy,
+}
+
+impl Config {
+    /// Loads the configuration from environment variables
+    ///
+    /// Unset variables use their defaults; variables that fail to parse are
+    /// logged and also fall back to their defaults.
+    pub fn from_env() -> Self {
+        let default_tip = env_or("TX_DEFAULT_TIP", 0);
+        let dev_mode = env_or("DEV_MODE", false);
+        Self {
+            default_mortality_period: env_or("TX_MORTALITY_PERIOD", 64),
+            default_tip,
+            max_batch_size: env_or("BATCH_MAX_SIZE", 500),
+            idempotency_window: Duration::from_secs(env_or("IDEMPOTENCY_WINDOW_SECS", 86_400)),
+            idempotency_max_body: env_or("IDEMPOTENCY_MAX_BODY_BYTES", 2 * 1024 * 1024),
+            receipts_db_path: env_or("RECEIPTS_DB_PATH", "data/receipts".to_string()),
+            ticket_ttl: Duration::from_secs(env_or("TICKET_TTL_SECS", 300)),
+            max_tickets_per_account: env_or("TICKET_MAX_PER_ACCOUNT", 16),
+            keystore_path: env_or("KEYSTORE_PATH", "keystore.json".to_string()),
+            default_signer: std::env::var("DEFAULT_SIGNER")
+                .ok()
+                .or_else(|| dev_mode.then(|| "//Alice".to_string())),
+            dev_mode,
+            user_master_signer: std::env::var("USER_MASTER_SIGNER").ok(),
+            policy_path: env_or("POLICY_PATH", "policy.json".to_string()),
+            remote_signer_url: std::env::var("REMOTE_SIGNER_URL").ok(),
+            signing_strategy: env_or("SIGNING_STRATEGY", SigningStrategy::Fallback),
+            nonce_source: env_or("NONCE_SOURCE", NonceSource::Pool),
+            nonce_gap_timeout: Duration::from_secs(env_or("NONCE_GAP_TIMEOUT_SECS", 60)),
+            nonce_gap_action: env_or("NONCE_GAP_ACTION", GapAction::Fill),
+            nonce_gap_tip: env_or("NONCE_GAP_TIP", default_tip),
+            retry: RetryPolicy {
+                max_attempts: env_or("RETRY_MAX_ATTEMPTS", 3u32).max(1),
+                initial_backoff: Duration::from_millis(env_or("RETRY_INITIAL_BACKOFF_MS", 500)),
+                max_backoff: Duration::from_millis(env_or("RETRY_MAX_BACKOFF_MS", 8_000)),
+            },
+        }
+    }
+
+    /// Checks the settings that have no default outside dev mode
+    ///
+    /// Kept apart from `from_env` so the keystore CLI works without them.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The configuration is complete
+    /// * `Err(String)` - Which setting is missing
+    pub fn validate(&self) -> Result<(), String> {
+        if self.default_signer.is_none() {
+            return Err("DEFAULT_SIGNER must be set outside dev mode".to_string());
+        }
+        Ok(())
+    }
+
+    /// Resolves the extrinsic parameters for one transaction
+    ///
+    /// Per-request overrides win over the configured defaults. A mortality
+    /// period of `0` (from either source) means immortal.
+    ///
+    /// # Arguments
+    /// * `mortality` - Optional per-request mortality period in blocks
+    /// * `tip` - Optional per-request tip
+    pub fn tx_params(&self, mortality: Option<u64>, tip: Option<u128>) -> TxParams {
+        let period = mortality.unwrap_or(self.default_mortality_period);
+        TxParams {
+            mortality_period: (period > 0).then_some(period),
+            tip: tip.unwrap_or(self.default_tip),
+        }
+    }
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The signers may be secret URIs in dev mode, so only say whether they are set
+        let redacted = |signer: &Option<String>| signer.as_ref().map(|_| "<redacted>");
+        f.debug_struct("Config")
+            .field("default_mortality_period", &self.default_mortality_period)
+            .field("default_tip", &self.default_tip)
+            .field("max_batch_size", &self.max_batch_size)
+            .field("idempotency_window", &self.idempotency_window)
+            .field("idempotency_max_body", &self.idempotency_max_body)
+            .field("receipts_db_path", &self.receipts_db_path)
+            .field("ticket_ttl", &self.ticket_ttl)
+            .field("max_tickets_per_account", &self.max_tickets_per_account)
+            .field("keystore_path", &self.keystore_path)
+            .field("default_signer", &redacted(&self.default_signer))
+            .field("dev_mode", &self.dev_mode)
+            .field("user_master_signer", &redacted(&self.user_master_signer))
+            .field("policy_path", &self.policy_path)
+            .field("remote_signer_url", &self.remote_signer_url)
+            .field("signing_strategy", &self.signing_strategy)
+            .field("nonce_source", &self.nonce_source)
+            .field("nonce_gap_timeout", &self.nonce_gap_timeout)
+            .field("nonce_gap_action", &self.nonce_gap_action)
+            .field("nonce_gap_tip", &self.nonce_gap_tip)
+            .field("retry", &self.retry)
+            .finish()
+    }
+}
+
+/// Reads and parses an environment variable, falling back to `default`
+fn env_or<T>(name: &str, default: T) -> T
+where
+    T: FromStr + std::fmt::Debug,
+{
+    match std::env::var(name) {
+        Ok(raw) => raw.parse().unwrap_or_else(|_| {
+            log::warn!(
+                "⚠️ Invalid value '{}' for {}, using default {:?}",
+                raw,
+                name,
+                default
+            );
+            default
+        }),
+        Err(_) => default,
+    }
+}
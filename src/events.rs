@@ -0,0 +1,291 @@
+// src/events.rs
+//
+// Decoding of extrinsic events into structured JSON
+//
+// subxt decodes event fields into `scale_value::Value`s tagged with their
+// metadata type IDs. This module turns those values into the same friendly
+// JSON shapes that the call builder accepts as input, so clients can read
+// events without knowing anything about SCALE:
+// - Account IDs as SS58 addresses
+// - Byte arrays as 0x-prefixed hex
+// - Enums as `"Variant"` or `{ "Variant": <fields> }`, Options as `null` or the inner value
+// - Integers as JSON numbers, or decimal strings when they don't fit in a u64
+
+use scale_info::{PortableRegistry, TypeDef, TypeDefPrimitive};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value as JsonValue};
+use subxt::{
+    blocks::ExtrinsicEvents,
+    ext::scale_value::{BitSequence, Composite, Primitive, Value, ValueDef},
+    utils::AccountId32,
+    Metadata, SubstrateConfig,
+};
+
+/// A single event emitted by an extrinsic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventInfo {
+    /// Position of the event among all events in the block
+    pub index: u32,
+    /// Name of the pallet that emitted the event (e.g. "Template")
+    pub pallet: String,
+    /// Name of the event (e.g. "SomethingStored")
+    pub variant: String,
+    /// The event's fields decoded to JSON
+    pub fields: JsonValue,
+}
+
+/// Decodes every event emitted by one extrinsic
+///
+/// # Arguments
+/// * `events` - The events of the extrinsic, as returned by `TxInBlock::fetch_events`
+/// * `metadata` - The runtime metadata used to recognise well-known types
+///
+/// # Returns
+/// * `Ok(Vec<EventInfo>)` - The events in the order they were emitted
+/// * `Err(String)` - If an event could not be decoded
+pub fn decode_extrinsic_events(
+    events: &ExtrinsicEvents<SubstrateConfig>,
+    metadata: &Metadata,
+) -> Result<Vec<EventInfo>, String> {
+    events
+        .iter()
+        .map(|event| {
+            let event = event.map_err(|e| format!("Failed to decode event: {:?}", e))?;
+            let fields = event
+                .field_values()
+                .map_err(|e| format!("Failed to decode event fields: {:?}", e))?;
+
+            Ok(EventInfo {
+                index: event.index(),
+                pallet: event.pallet_name().to_string(),
+                variant: event.variant_name().to_string(),
+                fields: fields_to_json(&fields, metadata),
+            })
+        })
+        .collect()
+}
+
+/// Converts decoded fields (of a call or an event) into JSON
+///
+/// Used for any fields decoded against the runtime metadata, so that call
+/// arguments and event fields are rendered the same way.
+pub fn fields_to_json(fields: &Composite<u32>, metadata: &Metadata) -> JsonValue {
+    composite_to_json(metadata.types(), fields)
+}
+
+/// Converts a decoded value into JSON
+///
+/// The value's context is its metadata type ID, which is used to render
+/// account IDs as SS58 addresses and byte sequences as hex, whatever their
+/// length.
+fn value_to_json(types: &PortableRegistry, value: &Value<u32>) -> JsonValue {
+    if is_account_id(types, value.context) {
+        if let Some(bytes) = collect_bytes(types, value) {
+            if let Ok(raw) = <[u8; 32]>::try_from(bytes.as_slice()) {
+                return JsonValue::String(AccountId32(raw).to_string());
+            }
+        }
+    }
+
+    // Checked before a composite is unwrapped or rendered element by element,
+    // so that `[]` and `[1]` become "0x" and "0x01" rather than an array and
+    // a number
+    if is_byte_sequence(types, value.context) {
+        if let ValueDef::Composite(Composite::Unnamed(values)) = &value.value {
+            let bytes: Option<Vec<u8>> = values.iter().map(|v| as_u8(types, v)).collect();
+            if let Some(bytes) = bytes {
+                return JsonValue::String(format!("0x{}", hex::encode(bytes)));
+            }
+        }
+    }
+
+    match &value.value {
+        ValueDef::Composite(composite) => composite_to_json(types, composite),
+        ValueDef::Variant(variant) => {
+            let is_option = types
+                .resolve(value.context)
+                .and_then(|ty| ty.path.segments.last())
+                .map(String::as_str)
+                == Some("Option");
+
+            match (variant.name.as_str(), variant.values.len()) {
+                ("None", 0) if is_option => JsonValue::Null,
+                ("Some", _) if is_option => composite_to_json(types, &variant.values),
+                (name, 0) => JsonValue::String(name.to_string()),
+                (name, _) => json!({ name: composite_to_json(types, &variant.values) }),
+            }
+        }
+        ValueDef::BitSequence(bits) => bits_to_json(bits),
+        ValueDef::Primitive(primitive) => primitive_to_json(primitive),
+    }
+}
+
+/// Converts a composite into a JSON object (named fields) or array (unnamed fields)
+///
+/// Single-field tuple structs are unwrapped, and byte sequences become hex.
+fn composite_to_json(types: &PortableRegistry, composite: &Composite<u32>) -> JsonValue {
+    match composite {
+        Composite::Named(fields) => {
+            let map: Map<String, JsonValue> = fields
+                .iter()
+                .map(|(name, value)| (name.clone(), value_to_json(types, value)))
+                .collect();
+            JsonValue::Object(map)
+        }
+        Composite::Unnamed(values) => {
+            if let [single] = values.as_slice() {
+                return value_to_json(types, single);
+            }
+            let bytes: Option<Vec<u8>> = values.iter().map(|v| as_u8(types, v)).collect();
+            match bytes {
+                Some(bytes) if !bytes.is_empty() => {
+                    JsonValue::String(format!("0x{}", hex::encode(bytes)))
+                }
+                _ => JsonValue::Array(values.iter().map(|v| value_to_json(types, v)).collect()),
+            }
+        }
+    }
+}
+
+/// Converts a primitive into JSON
+fn primitive_to_json(primitive: &Primitive) -> JsonValue {
+    match primitive {
+        Primitive::Bool(b) => JsonValue::Bool(*b),
+        Primitive::Char(c) => JsonValue::String(c.to_string()),
+        Primitive::String(s) => JsonValue::String(s.clone()),
+        Primitive::U128(n) => match u64::try_from(*n) {
+            Ok(n) => json!(n),
+            Err(_) => JsonValue::String(n.to_string()),
+        },
+        Primitive::I128(n) => match i64::try_from(*n) {
+            Ok(n) => json!(n),
+            Err(_) => JsonValue::String(n.to_string()),
+        },
+        Primitive::U256(bytes) | Primitive::I256(bytes) => {
+            JsonValue::String(format!("0x{}", hex::encode(bytes)))
+        }
+    }
+}
+
+/// Converts a bit sequence into an array of booleans
+fn bits_to_json(bits: &BitSequence) -> JsonValue {
+    JsonValue::Array(bits.iter().map(JsonValue::Bool).collect())
+}
+
+/// Returns true if `type_id` is an `AccountId32`
+fn is_account_id(types: &PortableRegistry, type_id: u32) -> bool {
+    types
+        .resolve(type_id)
+        .and_then(|ty| ty.path.segments.last())
+        .map(String::as_str)
+        == Some("AccountId32")
+}
+
+/// Returns true if `type_id` is a sequence or array of `u8`
+fn is_byte_sequence(types: &PortableRegistry, type_id: u32) -> bool {
+    let element = match types.resolve(type_id).map(|ty| &ty.type_def) {
+        Some(TypeDef::Sequence(sequence)) => sequence.type_param.id,
+        Some(TypeDef::Array(array)) => array.type_param.id,
+        _ => return false,
+    };
+    matches!(
+        types.resolve(element).map(|ty| &ty.type_def),
+        Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+    )
+}
+
+/// Flattens a value made only of `u8`s (possibly nested in wrappers) into bytes
+fn collect_bytes(types: &PortableRegistry, value: &Value<u32>) -> Option<Vec<u8>> {
+    match &value.value {
+        ValueDef::Composite(Composite::Unnamed(values)) => match values.as_slice() {
+            [single] => collect_bytes(types, single),
+            values => values.iter().map(|v| as_u8(types, v)).collect(),
+        },
+        _ => None,
+    }
+}
+
+/// Returns the value as a `u8` if its metadata type is the `u8` primitive
+fn as_u8(types: &PortableRegistry, value: &Value<u32>) -> Option<u8> {
+    let is_u8 = matches!(
+        types.resolve(value.context).map(|ty| &ty.type_def),
+        Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+    );
+    match value.value {
+        ValueDef::Primitive(Primitive::U128(n)) if is_u8 => u8::try_from(n).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scale_info::{meta_type, Registry};
+
+    /// Type IDs of the types the tests decode values of
+    struct Types {
+        registry: PortableRegistry,
+        u8: u32,
+        bytes: u32,
+        byte_array: u32,
+    }
+
+    fn types() -> Types {
+        let mut registry = Registry::new();
+        let u8 = registry.register_type(&meta_type::<u8>()).id;
+        let bytes = registry.register_type(&meta_type::<Vec<u8>>()).id;
+        let byte_array = registry.register_type(&meta_type::<[u8; 1]>()).id;
+        Types {
+            registry: registry.into(),
+            u8,
+            bytes,
+            byte_array,
+        }
+    }
+
+    /// A decoded value of type `type_id` made of the given bytes
+    fn byte_value(types: &Types, type_id: u32, bytes: &[u8]) -> Value<u32> {
+        let values = bytes
+            .iter()
+            .map(|b| Value::u128(*b as u128).map_context(|_| types.u8));
+        Value {
+            value: ValueDef::Composite(Composite::Unnamed(values.collect())),
+            context: type_id,
+        }
+    }
+
+    #[test]
+    fn byte_sequences_of_any_length_become_hex() {
+        let types = types();
+        for (bytes, hex) in [
+            (&[][..], "0x"),
+            (&[1][..], "0x01"),
+            (&[1, 2, 255][..], "0x0102ff"),
+        ] {
+            let value = byte_value(&types, types.bytes, bytes);
+            assert_eq!(value_to_json(&types.registry, &value), json!(hex));
+        }
+        let value = byte_value(&types, types.byte_array, &[7]);
+        assert_eq!(value_to_json(&types.registry, &value), json!("0x07"));
+    }
+
+    #[test]
+    fn byte_fields_keep_their_shape_among_other_fields() {
+        let types = types();
+        let fields = Composite::Named(vec![
+            ("data".to_string(), byte_value(&types, types.bytes, &[42])),
+            ("empty".to_string(), byte_value(&types, types.bytes, &[])),
+            (
+                "count".to_string(),
+                Value::u128(5).map_context(|_| types.u8),
+            ),
+        ]);
+        assert_eq!(
+            composite_to_json(&types.registry, &fields),
+            json!({ "data": "0x2a", "empty": "0x", "count": 5 })
+        );
+        // A lone u8 that isn't a byte sequence stays a number
+        let single = Composite::Unnamed(vec![Value::u128(5).map_context(|_| types.u8)]);
+        assert_eq!(composite_to_json(&types.registry, &single), json!(5));
+    }
+}
@@ -0,0 +1,158 @@
+// src/gaps.rs
+//
+// Nonce gap detection and filling
+//
+// A reserved nonce that never reaches the pool, e.g. because the request was
+// cancelled between reserving the nonce and submitting the transaction,
+// blocks every later transaction of the account: they wait in the pool's
+// future queue until something is submitted at the missing nonce.
+//
+// The nonce manager tracks reserved nonces until the pool's next nonce moves
+// past them; a transaction waiting in the pool is never missing, whatever
+// `NONCE_SOURCE` says. This module periodically asks it for missing nonces that nobody is
+// going to submit anymore (see `NonceLease`) and that stayed missing longer
+// than `NONCE_GAP_TIMEOUT_SECS`, and deals with them according to
+// `NONCE_GAP_ACTION`:
+// - `fill` (the default) submits a `System.remark` at the missing nonce,
+//   signed by the account's signer, the same way batches fill their gaps
+// - `alert` only logs an error and counts the gap in GET /metrics
+//
+// The remark pays `NONCE_GAP_TIP`. A transaction that is still in the pool at
+// the missing nonce (e.g. one whose lower-priority predecessor was dropped)
+// is only displaced if the remark's tip is higher; otherwise the fill is
+// rejected and the gap alerted.
+//
+// Accounts this service holds no signer for (e.g. nonces prepared for an
+// external signer) are always alerted.
+
+use std::str::FromStr;
+
+use crate::batch::fill_nonce_gap;
+use crate::handlers::AppState;
+use crate::nonce_manager::NonceGap;
+
+/// Most gaps of one account that are dealt with in a single check
+///
+/// Filling a gap lets the transactions behind it into the ready queue, which
+/// can reveal the next gap right away.
+const MAX_GAPS_PER_ACCOUNT: usize = 16;
+
+/// What to do about a nonce gap
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GapAction {
+    /// Submit a `System.remark` at the missing nonce
+    #[default]
+    Fill,
+    /// Only log an error and count the gap
+    Alert,
+}
+
+impl FromStr for GapAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fill" => Ok(Self::Fill),
+            "alert" => Ok(Self::Alert),
+            other => Err(format!("Unknown nonce gap action '{}'", other)),
+        }
+    }
+}
+
+/// Finds the nonce gaps of every cached account and fills or reports them
+///
+/// Meant to be called periodically from a background task.
+pub async fn check_nonce_gaps(state: &AppState) {
+    for gap in state.nonce_manager.find_gaps().await {
+        handle_account_gaps(state, gap).await;
+    }
+}
+
+/// Deals with an account's first gap and any gap it reveals behind it
+async fn handle_account_gaps(state: &AppState, mut gap: NonceGap) {
+    for _ in 0..MAX_GAPS_PER_ACCOUNT {
+        if !fill_or_alert(state, &gap).await {
+            return;
+        }
+        gap = match state.nonce_manager.find_gap(&gap.account_id).await {
+            // Only move on once the pool has moved past the filled nonce
+            Ok(Some(next)) if next.nonce > gap.nonce => next,
+            Ok(_) => return,
+            Err(e) => {
+                log::error!(
+                    "🕳️ Nonce gap check failed for account {:?}: {:?}",
+                    gap.account_id,
+                    e
+                );
+                return;
+            }
+        };
+    }
+}
+
+/// Fills a gap if configured and possible, alerts otherwise
+///
+/// # Returns
+/// `true` if a filler was submitted at the missing nonce
+async fn fill_or_alert(state: &AppState, gap: &NonceGap) -> bool {
+    let signer = match (state.config.nonce_gap_action, &gap.signer) {
+        (GapAction::Fill, Some(signer)) => signer.clone(),
+        (GapAction::Fill, None) => {
+            alert(
+                state,
+                gap,
+                "no signer of this service is known for the account",
+            );
+            return false;
+        }
+        (GapAction::Alert, _) => {
+            alert(state, gap, "NONCE_GAP_ACTION is alert");
+            return false;
+        }
+    };
+
+    let params = state
+        .config
+        .tx_params(None, Some(state.config.nonce_gap_tip));
+    match fill_nonce_gap(state, signer, gap.nonce, &params).await {
+        Ok(hash) => {
+            state.metrics.record_nonce_gap(GapAction::Fill);
+            log::warn!(
+                "🩹 Account {:?}: filled missing nonce {} with remark {}, releasing {} waiting transaction(s)",
+                gap.account_id,
+                gap.nonce,
+                hash,
+                gap.blocked - 1
+            );
+            true
+        }
+        Err(e) => {
+            alert(state, gap, &e);
+            false
+        }
+    }
+}
+
+/// Reports a gap that stays open
+fn alert(state: &AppState, gap: &NonceGap, reason: &str) {
+    state.metrics.record_nonce_gap(GapAction::Alert);
+    log::error!(
+        "🚨 Account {:?} is missing nonce {}, {} later reservation(s) are stuck behind it: {}",
+        gap.account_id,
+        gap.nonce,
+        gap.blocked - 1,
+        reason
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gap_actions_parse_case_insensitively() {
+        assert_eq!("fill".parse(), Ok(GapAction::Fill));
+        assert_eq!(" Alert ".parse(), Ok(GapAction::Alert));
+        assert!("ignore".parse::<GapAction>().is_err());
+    }
+}
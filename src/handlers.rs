@@ -9,24 +9,55 @@
 // - Response formatting and error handling
 // - Nonce management for transactions
 
-use axum::{extract::State, http::StatusCode, response::Json};
-use log;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 use subxt::{
-    ext::sp_core::{sr25519::Pair, Pair as PairTrait},
-    tx::PairSigner,
-    utils::AccountId32,
+    backend::legacy::{rpc_methods::DryRunResult, LegacyRpcMethods},
+    blocks::ExtrinsicEvents,
+    tx::{TxProgress, TxStatus, ValidationResult},
+    utils::{AccountId32, UncheckedExtrinsic, H256},
     OnlineClient, SubstrateConfig,
 };
 
-use crate::nonce_manager::NonceManager;
-use crate::transaction::create_signed_transaction_with_nonce;
+use crate::batch::{submit_batch, BatchCall, BatchResponse};
+use crate::call_builder::build_dynamic_call;
+use crate::config::Config;
+use crate::events::{decode_extrinsic_events, EventInfo};
+use crate::idempotency::IdempotencyStore;
+use crate::jobs::{track_job, Job, JobAccepted, JobStatus, JobStore};
+use crate::keystore::{KeyInfo, Keystore};
+use crate::keytools::{
+    generate_key, inspect_key, validate_prefix, AddressInfo, AddressQuery, GenerateKeyRequest,
+    InspectKeyRequest, KeyDetails,
+};
+use crate::metrics::Metrics;
+use crate::nonce_manager::{NonceManager, NonceReport};
+use crate::policy::{ApiClient, Policy};
+use crate::receipts::{CallInfo, Receipt, ReceiptStore};
+use crate::relay::{decode_signed_extrinsic, SubmitRawRequest};
+use crate::replace::{
+    replace_transaction, CancelRequest, ReplaceError, ReplaceRequest, ReplaceResponse,
+};
+use crate::retry::{submit_with_retry, FailureKind, Resigner, SubmissionAttempt};
+use crate::session::{rotate_session_keys, RotateKeysRequest, RotateKeysResponse};
+use crate::signer::{LocalSigner, TxSigner};
+use crate::tickets::{
+    CompleteRequest, PrepareRequest, PrepareResponse, PreparedTransaction, TicketStore,
+};
+use crate::transaction::{
+    create_signed_transaction_with_nonce, signing_params_with_nonce, ManagedTransaction, RawCall,
+    SigningPath, TxParams,
+};
+use crate::users::{CreateUserRequest, UserAccount, UserAccounts, USER_SIGNER_PREFIX};
+
+pub use crate::chain::chain_a;
 
-// Include the generated runtime types from the blockchain's metadata
-// This macro generates Rust types and APIs based on the actual runtime
-#[subxt::subxt(runtime_metadata_path = "src/metadata.scale")]
-pub mod chain_a {}
+use chain_a::runtime_types::frame_support::dispatch::DispatchClass;
 
 /// Request payload for the do_something endpoint
 ///
@@ -36,17 +67,242 @@ pub mod chain_a {}
 pub struct DoSomethingRequest {
     /// The value to store on the blockchain (required)
     pub value: u32,
-    /// Optional signer account seed (defaults to "//Alice" if not provided)
-    /// Example seeds: "//Alice", "//Bob", "//Charlie", or custom private keys
+    /// Optional signer alias from the keystore (defaults to `DEFAULT_SIGNER`)
+    /// In dev mode, raw secret URIs such as "//Alice" or "//Bob" are accepted too
+    pub signer: Option<String>,
+    /// Lifecycle stage to wait for before responding (defaults to "finalized")
+    #[serde(default)]
+    pub wait_for: TxStage,
+    /// Optional mortality period in blocks, 0 for immortal (defaults to server config)
+    pub mortality: Option<u64>,
+    /// Optional tip in the chain's smallest unit (defaults to server config)
+    pub tip: Option<u128>,
+}
+
+/// Request payload for the generic /tx/{pallet}/{call} endpoint
+///
+/// The pallet and call names come from the URL path; this body carries
+/// the call arguments and the signer.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmitCallRequest {
+    /// Call arguments, either as an object keyed by argument name
+    /// or as an array in declaration order (defaults to no arguments)
+    #[serde(default)]
+    pub args: serde_json::Value,
+    /// Optional signer alias from the keystore (defaults to `DEFAULT_SIGNER`)
+    pub signer: Option<String>,
+    /// Lifecycle stage to wait for before responding (defaults to "finalized")
+    #[serde(default)]
+    pub wait_for: TxStage,
+    /// Optional mortality period in blocks, 0 for immortal (defaults to server config)
+    pub mortality: Option<u64>,
+    /// Optional tip in the chain's smallest unit (defaults to server config)
+    pub tip: Option<u128>,
+}
+
+/// Request payload for the /tx/batch endpoint
+///
+/// Each entry of `values` becomes a Template.do_something transaction and
+/// each entry of `calls` an arbitrary call; `values` come first in the batch.
+/// The remaining options apply to every transaction in the batch.
+#[derive(Debug, Deserialize)]
+pub struct BatchRequest {
+    /// Values to store with Template.do_something, one transaction each
+    #[serde(default)]
+    pub values: Vec<u32>,
+    /// Arbitrary pallet calls, one transaction each
+    #[serde(default)]
+    pub calls: Vec<BatchCall>,
+    /// Optional signer alias from the keystore (defaults to `DEFAULT_SIGNER`)
+    pub signer: Option<String>,
+    /// Lifecycle stage to wait for on each transaction (defaults to "finalized")
+    #[serde(default)]
+    pub wait_for: TxStage,
+    /// Optional mortality period in blocks, 0 for immortal (defaults to server config)
+    pub mortality: Option<u64>,
+    /// Optional tip in the chain's smallest unit (defaults to server config)
+    pub tip: Option<u128>,
+}
+
+/// Request payload for endpoints that inspect a call without submitting it
+///
+/// Used by /tx/dry-run and /tx/estimate-fee. The call is described the same way as for
+/// /tx/{pallet}/{call}, but the pallet and call names are part of the body.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CallRequest {
+    /// Pallet name as it appears in metadata (e.g. "Balances")
+    pub pallet: String,
+    /// Call name as it appears in metadata (e.g. "transfer_keep_alive")
+    pub call: String,
+    /// Call arguments, either as an object keyed by argument name
+    /// or as an array in declaration order (defaults to no arguments)
+    #[serde(default)]
+    pub args: serde_json::Value,
+    /// Optional signer alias from the keystore (defaults to `DEFAULT_SIGNER`)
     pub signer: Option<String>,
+    /// Optional mortality period in blocks, 0 for immortal (defaults to server config)
+    pub mortality: Option<u64>,
+    /// Optional tip in the chain's smallest unit (defaults to server config)
+    pub tip: Option<u128>,
+}
+
+/// Response payload for the /tx/dry-run endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DryRunResponse {
+    /// Whether the transaction would be accepted and dispatch successfully
+    /// (a dry run that could not be performed doesn't count as a failure)
+    pub success: bool,
+    /// The hash the transaction would have if submitted (hex string)
+    pub transaction_hash: Option<String>,
+    /// The nonce the transaction was signed with (not reserved)
+    pub nonce: Option<u64>,
+    /// Result of the TaggedTransactionQueue_validate_transaction runtime API
+    pub validity: Option<ValidityInfo>,
+    /// Result of the node's system_dryRun RPC
+    pub dry_run: Option<DryRunOutcome>,
+    /// Error message if the call could not be built or validated
+    pub error: Option<String>,
+}
+
+impl DryRunResponse {
+    /// Builds a failed response carrying only an error message
+    fn failure(error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            transaction_hash: None,
+            nonce: None,
+            validity: None,
+            dry_run: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Transaction pool validity as reported by the runtime
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidityInfo {
+    /// Whether the transaction pool would accept the transaction
+    pub valid: bool,
+    /// Priority of the transaction in the pool (valid transactions only)
+    pub priority: Option<u64>,
+    /// Number of blocks the validity is guaranteed for (valid transactions only)
+    pub longevity: Option<u64>,
+    /// Whether the transaction would be gossiped to other nodes (valid transactions only)
+    pub propagate: Option<bool>,
+    /// Why the transaction is invalid or could not be validated
+    pub reason: Option<String>,
+}
+
+/// Outcome of executing the transaction with the node's dry-run RPC
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DryRunOutcome {
+    /// What happened when the extrinsic was applied
+    pub outcome: DryRunStatus,
+    /// The decoded dispatch error, if the call failed
+    pub dispatch_error: Option<String>,
+    /// Error message if the dry run could not be performed
+    pub error: Option<String>,
+}
+
+/// Result categories of the system_dryRun RPC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DryRunStatus {
+    /// The extrinsic was applied and the call dispatched successfully
+    Success,
+    /// The extrinsic was applied but the call returned an error
+    DispatchError,
+    /// The extrinsic could not be applied at all
+    TransactionValidityError,
+    /// The node refused or failed to dry run (e.g. unsafe RPCs are disabled)
+    Unavailable,
+}
+
+/// Response payload for the /tx/estimate-fee endpoint
+///
+/// Balances are returned as decimal strings because they can exceed the
+/// integer precision of JSON numbers in JavaScript clients.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FeeEstimateResponse {
+    /// Whether the estimate was computed
+    pub success: bool,
+    /// The fee the transaction would pay, excluding any tip
+    pub partial_fee: Option<String>,
+    /// The weight of the call
+    pub weight: Option<WeightInfo>,
+    /// The dispatch class of the call ("normal", "operational" or "mandatory")
+    pub dispatch_class: Option<String>,
+    /// How the partial fee is composed (null for calls that pay no fees)
+    pub inclusion_fee: Option<InclusionFeeInfo>,
+    /// Error message if the estimate could not be computed
+    pub error: Option<String>,
+}
+
+impl FeeEstimateResponse {
+    /// Builds a failed response carrying only an error message
+    fn failure(error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            partial_fee: None,
+            weight: None,
+            dispatch_class: None,
+            inclusion_fee: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Two-dimensional weight of a call
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WeightInfo {
+    /// Computational time used, in picoseconds
+    pub ref_time: u64,
+    /// Size of the storage proof, in bytes
+    pub proof_size: u64,
+}
+
+/// Breakdown of the inclusion fee as reported by TransactionPaymentApi
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InclusionFeeInfo {
+    /// Minimum fee every transaction pays
+    pub base_fee: String,
+    /// Fee for the encoded length of the transaction
+    pub len_fee: String,
+    /// Weight fee multiplied by the current fee multiplier
+    pub adjusted_weight_fee: String,
+}
+
+/// Stage of the transaction lifecycle a submission can wait for
+///
+/// Used both as the `wait_for` request field and as the `stage` reported
+/// in responses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxStage {
+    /// Accepted into the node's transaction pool; only the hash is known
+    Submitted,
+    /// Included in a best block, which may still be reorganized away
+    InBlock,
+    /// Included in a finalized block
+    #[default]
+    Finalized,
+}
+
+/// Query options shared by all transaction submission endpoints
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SubmitOptions {
+    /// When true (`?async=true`), return `202 Accepted` with a job ID
+    /// instead of waiting for finalization
+    #[serde(default, rename = "async")]
+    pub run_async: bool,
 }
 
-/// Response payload for the do_something endpoint
+/// Response payload for all transaction submission endpoints
 ///
 /// This structure provides comprehensive information about the transaction
 /// result, including success status, blockchain hashes, and error details.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DoSomethingResponse {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionResponse {
     /// Whether the transaction was successfully submitted and finalized
     pub success: bool,
     /// The transaction hash if successfully submitted (hex string)
@@ -55,91 +311,1801 @@ pub struct DoSomethingResponse {
     pub block_hash: Option<String>,
     /// Detailed block header information where the transaction was included
     pub block_header: Option<BlockHeaderInfo>,
+    /// Position of the transaction among the block's extrinsics
+    pub extrinsic_index: Option<u32>,
+    /// Events emitted by the transaction, decoded to JSON
+    pub events: Option<Vec<EventInfo>>,
+    /// The lifecycle stage the transaction reached (null if never submitted)
+    pub stage: Option<TxStage>,
     /// Error message if the transaction failed at any stage
     pub error: Option<String>,
+    /// Every submission attempt, including retries (see `retry`)
+    #[serde(default)]
+    pub attempts: Vec<SubmissionAttempt>,
+}
+
+impl TransactionResponse {
+    /// Builds a failed response carrying only an error message
+    pub fn failure(error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            transaction_hash: None,
+            block_hash: None,
+            block_header: None,
+            extrinsic_index: None,
+            events: None,
+            stage: None,
+            error: Some(error.into()),
+            attempts: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockHeaderInfo {
+    pub parent_hash: String,
+    pub state_root: String,
+    pub extrinsics_root: String,
+    pub block_number: u32,
+    pub digest: String,
+}
+
+/// Response payload for the get_storage endpoint
+///
+/// This structure contains the current value stored on the blockchain
+/// along with the block hash where the value was queried from.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetStorageResponse {
+    /// The current value stored on the blockchain (None if not set)
+    pub value: Option<u32>,
+    /// The block hash where this value was queried from
+    pub block_hash: String,
+}
+
+/// Application state shared across all HTTP handlers
+///
+/// This struct contains the core dependencies that handlers need to
+/// interact with the blockchain and manage transaction state:
+/// - Blockchain client for queries and transaction submission
+/// - Nonce manager for preventing transaction conflicts
+/// - Job store for asynchronous submissions
+/// - Legacy RPC methods sharing the client's connection
+/// - Server configuration (transaction defaults etc.)
+/// - Idempotency key store for submission endpoints
+/// - Receipt database for transaction lookups
+///
+/// The Clone trait allows this state to be efficiently shared across
+/// multiple concurrent HTTP requests without expensive copying.
+#[derive(Clone)]
+pub struct AppState {
+    /// The subxt client for blockchain communication
+    pub client: OnlineClient<SubstrateConfig>,
+    /// Production-grade nonce manager for transaction sequencing
+    pub nonce_manager: NonceManager,
+    /// Background transaction jobs created by async submissions
+    pub jobs: JobStore,
+    /// Raw JSON-RPC methods for node features subxt doesn't wrap (e.g. dry runs)
+    pub rpc: LegacyRpcMethods<SubstrateConfig>,
+    /// Server configuration loaded at startup
+    pub config: Arc<Config>,
+    /// Idempotency keys seen on submission endpoints and their responses
+    pub idempotency: IdempotencyStore,
+    /// Persistent receipts of every signed transaction
+    pub receipts: ReceiptStore,
+    /// Transactions prepared for external signers, waiting for their signature
+    pub tickets: TicketStore,
+    /// Operational counters exposed on /metrics
+    pub metrics: Metrics,
+    /// Managed signers, referred to by alias in requests
+    pub keystore: Keystore,
+    /// Which signer may submit which calls, for which API clients
+    pub policy: Policy,
+    /// End users' accounts, derived from the master signer; `None` without
+    /// `USER_MASTER_SIGNER`
+    pub users: Option<UserAccounts>,
+    /// The chain's SS58 address prefix, from `system_properties`
+    pub ss58_prefix: u16,
+}
+
+/// Health check endpoint for service monitoring
+///
+/// This endpoint provides a simple way for load balancers, monitoring
+/// systems, or clients to verify that the API service is running and
+/// responsive. It returns basic service information without requiring
+/// any blockchain interaction.
+///
+/// # Returns
+/// JSON object with service status and identification information
+///
+/// # Example Response
+/// ```json
+/// {
+///   "status": "healthy",
+///   "service": "Chain A Backend API"
+/// }
+/// ```
+pub async fn health_check() -> Json<HashMap<String, String>> {
+    let mut response = HashMap::new();
+    response.insert("status".to_string(), "healthy".to_string());
+    response.insert("service".to_string(), "Chain A Backend API".to_string());
+    Json(response)
+}
+
+/// Metrics endpoint in the Prometheus text exposition format
+///
+/// # Example Response
+/// ```text
+/// # TYPE tx_signed_total counter
+/// tx_signed_total{path="explicit"} 42
+/// tx_signed_total{path="fallback"} 1
+/// ```
+pub async fn metrics_handler(State(state): State<AppState>) -> Response {
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4",
+        )],
+        state.metrics.render(),
+    )
+        .into_response()
+}
+
+/// Lists the managed signers from the keystore
+///
+/// Only public information is returned; requests refer to a signer by its alias.
+///
+/// # Example Response
+/// ```json
+/// [
+///   { "alias": "treasury", "public_key": "0xd435...", "address": "5GrwvaEF...", "backend": "local" }
+/// ]
+/// ```
+pub async fn list_keys_handler(State(state): State<AppState>) -> Json<Vec<KeyInfo>> {
+    Json(state.keystore.list())
+}
+
+/// Looks up one managed signer by alias
+///
+/// # Returns
+/// The signer's public key and address, or 404 if the alias is unknown
+pub async fn get_key_handler(
+    State(state): State<AppState>,
+    Path(alias): Path<String>,
+) -> Result<Json<KeyInfo>, StatusCode> {
+    state
+        .keystore
+        .list()
+        .into_iter()
+        .find(|info| info.alias == alias)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Creates (derives) the account of an end user
+///
+/// User accounts are derived from the master signer along
+/// `//users//{user_id}`, so creating one stores nothing; it returns the same
+/// account every time. Submit as the user with `"signer": "user:{user_id}"`.
+///
+/// # Request Format
+/// POST /users
+/// ```json
+/// { "user_id": "42" }
+/// ```
+///
+/// # Example Response
+/// ```json
+/// {
+///   "user_id": "42",
+///   "address": "5Dfis6XL...",
+///   "public_key": "0x46eb...",
+///   "scheme": "sr25519",
+///   "derivation_path": "//users//42"
+/// }
+/// ```
+///
+/// # Returns
+/// 201 with the user's account, or 400 if the user ID is invalid or user
+/// accounts are disabled
+pub async fn create_user_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateUserRequest>,
+) -> Result<(StatusCode, Json<UserAccount>), StatusCode> {
+    match user_accounts(&state).and_then(|users| users.account(&payload.user_id)) {
+        Ok(account) => {
+            log::info!(
+                "👥 User '{}' has account {}",
+                account.user_id,
+                account.address
+            );
+            Ok((StatusCode::CREATED, Json(account)))
+        }
+        Err(e) => {
+            log::warn!("⚠️ Cannot derive account of user: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+/// Looks up the derived account of an end user
+///
+/// # Returns
+/// The user's account (same format as POST /users), or 400 if the user ID is
+/// invalid or user accounts are disabled
+pub async fn get_user_handler(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> Result<Json<UserAccount>, StatusCode> {
+    user_accounts(&state)
+        .and_then(|users| users.account(&user_id))
+        .map(Json)
+        .map_err(|e| {
+            log::warn!("⚠️ Cannot derive account of user: {}", e);
+            StatusCode::BAD_REQUEST
+        })
+}
+
+/// Generates a new key pair (dev mode only)
+///
+/// The response carries the secret URI, so this is only meant for test keys
+/// on development chains.
+///
+/// # Request Format
+/// POST /util/keys/generate
+/// ```json
+/// { "scheme": "ed25519", "prefix": 0 }  // both optional: sr25519 and the chain's prefix by default
+/// ```
+///
+/// # Example Response
+/// ```json
+/// {
+///   "secret_uri": "ed25519:bottom drive obey lake ...",
+///   "scheme": "ed25519",
+///   "public_key": "0x88dc...",
+///   "account_id": "0x88dc...",
+///   "address": "5FA9nQDV...",
+///   "prefix": 42
+/// }
+/// ```
+///
+/// # Returns
+/// The new key, 403 outside dev mode, or 400 for an invalid prefix
+pub async fn generate_key_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<GenerateKeyRequest>,
+) -> Result<Json<KeyDetails>, StatusCode> {
+    if !state.config.dev_mode {
+        log::warn!("⚠️ Rejected key generation outside dev mode");
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let prefix = request_prefix(&state, payload.prefix)?;
+    let key = generate_key(payload.scheme, prefix).map_err(|e| {
+        log::error!("❌ Failed to generate a key: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    log::info!("🔑 Generated {:?} key {}", key.scheme, key.address);
+    Ok(Json(key))
+}
+
+/// Derives the public key, account and address of a secret URI (dev mode only)
+///
+/// Raw secrets are only accepted in dev mode, like everywhere else. The secret
+/// URI is neither logged nor returned.
+///
+/// # Request Format
+/// POST /util/keys/inspect
+/// ```json
+/// { "suri": "ecdsa://Alice", "prefix": 0 }  // prefix optional
+/// ```
+///
+/// # Returns
+/// The key's public details (same format as /util/keys/generate, without
+/// `secret_uri`), 403 outside dev mode, or 400 for an invalid secret URI or prefix
+pub async fn inspect_key_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<InspectKeyRequest>,
+) -> Result<Json<KeyDetails>, StatusCode> {
+    if !state.config.dev_mode {
+        log::warn!("⚠️ Rejected secret URI inspection outside dev mode");
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let prefix = request_prefix(&state, payload.prefix)?;
+    inspect_key(&payload.suri, prefix).map(Json).map_err(|e| {
+        log::warn!("⚠️ Cannot inspect key: {}", e);
+        StatusCode::BAD_REQUEST
+    })
+}
+
+/// Validates an address and converts it to another SS58 prefix
+///
+/// The address can be given as SS58 (any prefix) or as 0x-prefixed hex.
+///
+/// # Request Format
+/// GET /util/address/5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY?prefix=0
+///
+/// # Example Response
+/// ```json
+/// {
+///   "valid": true,
+///   "input": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+///   "input_prefix": 42,
+///   "account_id": "0xd43593c7...",
+///   "address": "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5",
+///   "prefix": 0,
+///   "error": null
+/// }
+/// ```
+///
+/// # Returns
+/// The address details (`valid: false` with an `error` for invalid input),
+/// or 400 for an invalid prefix
+pub async fn address_handler(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(query): Query<AddressQuery>,
+) -> Result<Json<AddressInfo>, StatusCode> {
+    let prefix = request_prefix(&state, query.prefix)?;
+    Ok(Json(match parse_account_id(&address) {
+        Ok(account_id) => AddressInfo::valid(&address, &account_id, prefix),
+        Err(e) => AddressInfo::invalid(&address, prefix, e),
+    }))
+}
+
+/// Resolves the SS58 prefix a request asks for, defaulting to the chain's
+fn request_prefix(state: &AppState, prefix: Option<u16>) -> Result<u16, StatusCode> {
+    validate_prefix(prefix.unwrap_or(state.ss58_prefix)).map_err(|e| {
+        log::warn!("⚠️ {}", e);
+        StatusCode::BAD_REQUEST
+    })
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BlockHeaderInfo {
-    pub parent_hash: String,
-    pub state_root: String,
-    pub extrinsics_root: String,
-    pub block_number: u32,
-    pub digest: String,
+/// Handles the /do-something endpoint for submitting transactions to the blockchain
+///
+/// This is the main transaction endpoint that:
+/// 1. Validates the request payload and signer
+/// 2. Gets the next available nonce for the account
+/// 3. Creates and signs a blockchain transaction
+/// 4. Submits the transaction and waits for the requested stage
+/// 5. Returns the transaction result with hashes and status
+///
+/// The endpoint uses production-grade nonce management to prevent conflicts
+/// when multiple transactions are submitted concurrently for the same account.
+///
+/// With `?async=true` the endpoint returns `202 Accepted` with a job ID right
+/// after signing, and the transaction is tracked in the background (see /jobs/{id}).
+///
+/// # Request Format
+/// POST /do-something
+/// ```json
+/// {
+///   "value": 42,
+///   "signer": "treasury",    // optional keystore alias, defaults to DEFAULT_SIGNER
+///   "wait_for": "in_block",  // optional: submitted | in_block | finalized (default)
+///   "mortality": 32,         // optional, blocks until expiry (0 = immortal)
+///   "tip": 1000              // optional, raises priority in the pool
+/// }
+/// ```
+///
+/// # Response Format
+/// ```json
+/// {
+///   "success": true,
+///   "transaction_hash": "0x...",
+///   "block_hash": "0x...",
+///   "extrinsic_index": 2,
+///   "events": [
+///     { "index": 3, "pallet": "Template", "variant": "SomethingStored",
+///       "fields": { "something": 42, "who": "5GrwvaEF..." } },
+///     ...
+///   ],
+///   "stage": "in_block",
+///   "error": null
+/// }
+/// ```
+///
+/// # Arguments
+/// * `state` - Shared application state (client + nonce manager)
+/// * `payload` - JSON request body with value and optional signer
+///
+/// # Returns
+/// JSON response with transaction result or error details
+pub async fn do_something_handler(
+    State(state): State<AppState>,
+    Query(options): Query<SubmitOptions>,
+    ApiClient(client): ApiClient,
+    Json(payload): Json<DoSomethingRequest>,
+) -> Result<Response, StatusCode> {
+    // 📥 LOG THE INCOMING REQUEST
+    log::info!("📥 INCOMING REQUEST:");
+    log::info!("   Value: {}", payload.value);
+
+    let (signer_name, signer) = match parse_signer(&state, payload.signer) {
+        Ok(resolved) => resolved,
+        Err((status, e)) => {
+            return Ok((status, Json(TransactionResponse::failure(e))).into_response())
+        }
+    };
+
+    // Create the blockchain extrinsic call
+    // This represents the actual function call that will be executed on-chain
+    let call = chain_a::tx().template().do_something(payload.value);
+    let call_info = CallInfo::new(
+        "Template",
+        "do_something",
+        serde_json::json!({ "something": payload.value }),
+    );
+    if let Err(e) = state
+        .policy
+        .authorize(&signer_name, client.as_deref(), &call_info)
+    {
+        return Ok(policy_denied(e));
+    }
+    let params = state.config.tx_params(payload.mortality, payload.tip);
+
+    Ok(submit_with_managed_nonce(
+        &state,
+        signer,
+        &call,
+        call_info,
+        params,
+        payload.wait_for,
+        &options,
+    )
+    .await)
+}
+
+/// Handles the /tx/{pallet}/{call} endpoint for submitting any pallet call
+///
+/// Unlike /do-something, this endpoint is not tied to a generated call.
+/// The call is looked up in the runtime metadata by name and the JSON
+/// arguments are encoded against its argument types (see `call_builder`).
+/// Signing, nonce management and finalization tracking are identical to
+/// /do-something.
+///
+/// # Request Format
+/// POST /tx/Balances/transfer_keep_alive
+/// ```json
+/// {
+///   "args": { "dest": "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty", "value": "1000000000000" },
+///   "signer": "treasury"  // optional keystore alias, defaults to DEFAULT_SIGNER
+/// }
+/// ```
+///
+/// # Response Format
+/// Same as /do-something
+///
+/// # Arguments
+/// * `state` - Shared application state (client + nonce manager)
+/// * `pallet` - Pallet name from the path, as it appears in metadata
+/// * `call` - Call name from the path, as it appears in metadata
+/// * `payload` - JSON request body with call arguments and optional signer
+///
+/// # Returns
+/// JSON response with transaction result or error details
+pub async fn submit_call_handler(
+    State(state): State<AppState>,
+    Path((pallet, call)): Path<(String, String)>,
+    Query(options): Query<SubmitOptions>,
+    ApiClient(client): ApiClient,
+    Json(payload): Json<SubmitCallRequest>,
+) -> Result<Response, StatusCode> {
+    log::info!("📥 INCOMING CALL REQUEST: {}.{}", pallet, call);
+    log::info!("   Args: {}", payload.args);
+
+    let (signer_name, signer) = match parse_signer(&state, payload.signer) {
+        Ok(resolved) => resolved,
+        Err((status, e)) => {
+            return Ok((status, Json(TransactionResponse::failure(e))).into_response())
+        }
+    };
+
+    // Encode the JSON arguments against the call's types in the runtime metadata
+    let dynamic_call =
+        match build_dynamic_call(&state.client.metadata(), &pallet, &call, &payload.args) {
+            Ok(dynamic_call) => dynamic_call,
+            Err(e) => {
+                log::error!("❌ Failed to build call {}.{}: {}", pallet, call, e);
+                return Ok(Json(TransactionResponse::failure(e)).into_response());
+            }
+        };
+
+    let call_info = CallInfo::new(pallet, call, payload.args);
+    if let Err(e) = state
+        .policy
+        .authorize(&signer_name, client.as_deref(), &call_info)
+    {
+        return Ok(policy_denied(e));
+    }
+
+    let params = state.config.tx_params(payload.mortality, payload.tip);
+
+    Ok(submit_with_managed_nonce(
+        &state,
+        signer,
+        &dynamic_call,
+        call_info,
+        params,
+        payload.wait_for,
+        &options,
+    )
+    .await)
+}
+
+/// Handles the /tx/dry-run endpoint for checking a call before submitting it
+///
+/// This endpoint builds and signs the call exactly like the submission
+/// endpoints do, then asks the node what would happen without submitting:
+/// 1. TaggedTransactionQueue_validate_transaction - would the pool accept it?
+/// 2. system_dryRun - would the call dispatch successfully?
+///
+/// The nonce is only peeked at, never reserved, so dry runs don't disturb
+/// the nonce sequence of real submissions.
+///
+/// # Request Format
+/// POST /tx/dry-run
+/// ```json
+/// {
+///   "pallet": "Template",
+///   "call": "cause_error",
+///   "args": {},
+///   "signer": "treasury"  // optional keystore alias, defaults to DEFAULT_SIGNER
+/// }
+/// ```
+///
+/// # Response Format
+/// ```json
+/// {
+///   "success": false,
+///   "transaction_hash": "0x...",
+///   "nonce": 7,
+///   "validity": { "valid": true, "priority": 0, "longevity": 18446744073709551615, "propagate": true, "reason": null },
+///   "dry_run": { "outcome": "dispatch_error", "dispatch_error": "Module error: Template::NoneValue", "error": null },
+///   "error": null
+/// }
+/// ```
+///
+/// # Returns
+/// JSON response with validity and dry-run results; 403 if the signer's
+/// policy doesn't allow the call
+pub async fn dry_run_handler(
+    State(state): State<AppState>,
+    ApiClient(client): ApiClient,
+    Json(payload): Json<CallRequest>,
+) -> Response {
+    log::info!("🧪 DRY RUN REQUEST: {}.{}", payload.pallet, payload.call);
+
+    let (signer_name, signer) = match parse_signer(&state, payload.signer) {
+        Ok(resolved) => resolved,
+        Err((status, e)) => return (status, Json(DryRunResponse::failure(e))).into_response(),
+    };
+
+    let call_info = CallInfo::new(
+        payload.pallet.clone(),
+        payload.call.clone(),
+        payload.args.clone(),
+    );
+    if let Err(e) = state
+        .policy
+        .authorize(&signer_name, client.as_deref(), &call_info)
+    {
+        return (StatusCode::FORBIDDEN, Json(DryRunResponse::failure(e))).into_response();
+    }
+
+    let metadata = state.client.metadata();
+    let call = match build_dynamic_call(&metadata, &payload.pallet, &payload.call, &payload.args) {
+        Ok(call) => call,
+        Err(e) => return Json(DryRunResponse::failure(e)).into_response(),
+    };
+
+    // Peek at the nonce a real submission would use, without reserving it
+    let account_id = signer.account_id();
+    let nonce = match state.nonce_manager.peek_next_nonce(&account_id).await {
+        Ok(nonce) => nonce,
+        Err(e) => {
+            return Json(DryRunResponse::failure(format!(
+                "Failed to get nonce: {:?}",
+                e
+            )))
+            .into_response()
+        }
+    };
+
+    let params = state.config.tx_params(payload.mortality, payload.tip);
+    // Sign the way a real submission would, fallback included
+    let (signed_tx, nonce) = match create_signed_transaction_with_nonce(
+        &state.client,
+        &state.rpc,
+        &call,
+        signer.as_ref(),
+        nonce,
+        &params,
+        state.config.signing_strategy,
+    )
+    .await
+    {
+        Ok(signed) => (signed.extrinsic, signed.nonce),
+        Err(e) => {
+            return Json(DryRunResponse::failure(format!(
+                "Failed to create transaction: {:?}",
+                e
+            )))
+            .into_response()
+        }
+    };
+
+    // Ask the runtime whether the transaction pool would accept this extrinsic
+    let validity = match signed_tx.validate().await {
+        Ok(ValidationResult::Valid(valid)) => ValidityInfo {
+            valid: true,
+            priority: Some(valid.priority),
+            longevity: Some(valid.longevity),
+            propagate: Some(valid.propagate),
+            reason: None,
+        },
+        Ok(ValidationResult::Invalid(invalid)) => ValidityInfo {
+            valid: false,
+            priority: None,
+            longevity: None,
+            propagate: None,
+            reason: Some(format!("Invalid: {:?}", invalid)),
+        },
+        Ok(ValidationResult::Unknown(unknown)) => ValidityInfo {
+            valid: false,
+            priority: None,
+            longevity: None,
+            propagate: None,
+            reason: Some(format!("Unknown: {:?}", unknown)),
+        },
+        Err(e) => {
+            log::error!("❌ Failed to validate transaction: {:?}", e);
+            return Json(DryRunResponse::failure(format!(
+                "Failed to validate transaction: {:?}",
+                e
+            )))
+            .into_response();
+        }
+    };
+
+    // Apply the extrinsic on top of the best block without importing it
+    let dry_run = match state.rpc.dry_run(signed_tx.encoded(), None).await {
+        Ok(bytes) => match bytes.into_dry_run_result(&metadata) {
+            Ok(DryRunResult::Success) => DryRunOutcome {
+                outcome: DryRunStatus::Success,
+                dispatch_error: None,
+                error: None,
+            },
+            Ok(DryRunResult::DispatchError(e)) => DryRunOutcome {
+                outcome: DryRunStatus::DispatchError,
+                dispatch_error: Some(e.to_string()),
+                error: None,
+            },
+            Ok(DryRunResult::TransactionValidityError) => DryRunOutcome {
+                outcome: DryRunStatus::TransactionValidityError,
+                dispatch_error: None,
+                error: None,
+            },
+            Err(e) => DryRunOutcome {
+                outcome: DryRunStatus::Unavailable,
+                dispatch_error: None,
+                error: Some(format!("Failed to decode dry run result: {:?}", e)),
+            },
+        },
+        Err(e) => {
+            // system_dryRun is an unsafe RPC and may be disabled on the node
+            log::warn!("⚠️ Dry run RPC unavailable: {:?}", e);
+            DryRunOutcome {
+                outcome: DryRunStatus::Unavailable,
+                dispatch_error: None,
+                error: Some(format!("Dry run failed: {:?}", e)),
+            }
+        }
+    };
+
+    let success = validity.valid
+        && matches!(
+            dry_run.outcome,
+            DryRunStatus::Success | DryRunStatus::Unavailable
+        );
+
+    Json(DryRunResponse {
+        success,
+        transaction_hash: Some(format!("{:?}", signed_tx.hash())),
+        nonce: Some(nonce),
+        validity: Some(validity),
+        dry_run: Some(dry_run),
+        error: None,
+    })
+    .into_response()
+}
+
+/// Handles the /tx/estimate-fee endpoint for showing costs before submission
+///
+/// This endpoint signs the call the way a real submission would and asks the
+/// runtime's TransactionPaymentApi for:
+/// - `query_info` - partial fee, weight and dispatch class
+/// - `query_fee_details` - base fee, length fee and adjusted weight fee
+///
+/// The nonce is compact-encoded into the extrinsic, so it changes the length
+/// and with it the length fee. Like dry runs, the estimate uses the nonce a
+/// real submission would get, peeked at without reserving it. Nothing is
+/// submitted.
+///
+/// # Request Format
+/// POST /tx/estimate-fee
+/// ```json
+/// {
+///   "pallet": "Balances",
+///   "call": "transfer_keep_alive",
+///   "args": { "dest": "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty", "value": "1000000000000" },
+///   "signer": "treasury"  // optional keystore alias, defaults to DEFAULT_SIGNER
+/// }
+/// ```
+///
+/// # Response Format
+/// ```json
+/// {
+///   "success": true,
+///   "partial_fee": "153000000",
+///   "weight": { "ref_time": 143322000, "proof_size": 3593 },
+///   "dispatch_class": "normal",
+///   "inclusion_fee": { "base_fee": "1000000", "len_fee": "139000000", "adjusted_weight_fee": "13000000" },
+///   "error": null
+/// }
+/// ```
+///
+/// # Returns
+/// JSON response with the fee estimate or error details; 403 if the signer's
+/// policy doesn't allow the call
+pub async fn estimate_fee_handler(
+    State(state): State<AppState>,
+    ApiClient(client): ApiClient,
+    Json(payload): Json<CallRequest>,
+) -> Response {
+    log::info!(
+        "💰 FEE ESTIMATE REQUEST: {}.{}",
+        payload.pallet,
+        payload.call
+    );
+
+    let (signer_name, signer) = match parse_signer(&state, payload.signer) {
+        Ok(resolved) => resolved,
+        Err((status, e)) => return (status, Json(FeeEstimateResponse::failure(e))).into_response(),
+    };
+
+    let call_info = CallInfo::new(
+        payload.pallet.clone(),
+        payload.call.clone(),
+        payload.args.clone(),
+    );
+    if let Err(e) = state
+        .policy
+        .authorize(&signer_name, client.as_deref(), &call_info)
+    {
+        return (StatusCode::FORBIDDEN, Json(FeeEstimateResponse::failure(e))).into_response();
+    }
+
+    let call = match build_dynamic_call(
+        &state.client.metadata(),
+        &payload.pallet,
+        &payload.call,
+        &payload.args,
+    ) {
+        Ok(call) => call,
+        Err(e) => return Json(FeeEstimateResponse::failure(e)).into_response(),
+    };
+
+    // The nonce's encoded length counts towards the length fee, so peek at the
+    // one a real submission would use, without reserving it
+    let nonce = match state
+        .nonce_manager
+        .peek_next_nonce(&signer.account_id())
+        .await
+    {
+        Ok(nonce) => nonce,
+        Err(e) => {
+            return Json(FeeEstimateResponse::failure(format!(
+                "Failed to get nonce: {:?}",
+                e
+            )))
+            .into_response()
+        }
+    };
+
+    let params = state.config.tx_params(payload.mortality, payload.tip);
+    // Sign the way a real submission would, fallback included
+    let signed_tx = match create_signed_transaction_with_nonce(
+        &state.client,
+        &state.rpc,
+        &call,
+        signer.as_ref(),
+        nonce,
+        &params,
+        state.config.signing_strategy,
+    )
+    .await
+    {
+        Ok(signed) => signed.extrinsic,
+        Err(e) => {
+            return Json(FeeEstimateResponse::failure(format!(
+                "Failed to create transaction: {:?}",
+                e
+            )))
+            .into_response()
+        }
+    };
+
+    let encoded = signed_tx.into_encoded();
+    let len = encoded.len() as u32;
+
+    let runtime_api = match state.client.runtime_api().at_latest().await {
+        Ok(runtime_api) => runtime_api,
+        Err(e) => {
+            log::error!("❌ Failed to get latest block: {:?}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let info_call = chain_a::apis()
+        .transaction_payment_api()
+        .query_info(UncheckedExtrinsic::new(encoded.clone()), len);
+    let info = match runtime_api.call(info_call).await {
+        Ok(info) => info,
+        Err(e) => {
+            log::error!("❌ TransactionPaymentApi.query_info failed: {:?}", e);
+            return Json(FeeEstimateResponse::failure(format!(
+                "Failed to query fee info: {:?}",
+                e
+            )))
+            .into_response();
+        }
+    };
+
+    let details_call = chain_a::apis()
+        .transaction_payment_api()
+        .query_fee_details(UncheckedExtrinsic::new(encoded), len);
+    let details = match runtime_api.call(details_call).await {
+        Ok(details) => details,
+        Err(e) => {
+            log::error!("❌ TransactionPaymentApi.query_fee_details failed: {:?}", e);
+            return Json(FeeEstimateResponse::failure(format!(
+                "Failed to query fee details: {:?}",
+                e
+            )))
+            .into_response();
+        }
+    };
+
+    let dispatch_class = match info.class {
+        DispatchClass::Normal => "normal",
+        DispatchClass::Operational => "operational",
+        DispatchClass::Mandatory => "mandatory",
+    };
+
+    Json(FeeEstimateResponse {
+        success: true,
+        partial_fee: Some(info.partial_fee.to_string()),
+        weight: Some(WeightInfo {
+            ref_time: info.weight.ref_time,
+            proof_size: info.weight.proof_size,
+        }),
+        dispatch_class: Some(dispatch_class.to_string()),
+        inclusion_fee: details.inclusion_fee.map(|fee| InclusionFeeInfo {
+            base_fee: fee.base_fee.to_string(),
+            len_fee: fee.len_fee.to_string(),
+            adjusted_weight_fee: fee.adjusted_weight_fee.to_string(),
+        }),
+        error: None,
+    })
+    .into_response()
+}
+
+/// Resolves the signer named in a request
+///
+/// The signer is looked up by alias in the keystore, falling back to the
+/// configured default signer when the request doesn't name one. Anything
+/// that isn't an alias is only accepted as a raw secret URI in dev mode.
+/// Secret URIs are never logged.
+///
+/// `"user:{user_id}"` resolves to the end user's derived account (see `users`).
+///
+/// Returns the signer together with the name the policy knows it by: its
+/// alias (or `user:{user_id}`), or its SS58 address for a raw secret URI.
+/// On failure, returns the status and error message that should be sent
+/// back to the client: `400 Bad Request` for a `user:` signer that can't be
+/// derived (as on the /users endpoints), the endpoint's usual `200` failure
+/// response otherwise.
+fn parse_signer(
+    state: &AppState,
+    signer: Option<String>,
+) -> Result<(String, Arc<dyn TxSigner>), (StatusCode, String)> {
+    let signer = signer
+        .or_else(|| state.config.default_signer.clone())
+        .ok_or((
+            StatusCode::OK,
+            "No signer given and no DEFAULT_SIGNER configured".to_string(),
+        ))?;
+
+    // "user:{id}" names an end user's account derived from the master signer
+    if let Some(user_id) = signer.strip_prefix(USER_SIGNER_PREFIX) {
+        let derived = user_accounts(state)
+            .and_then(|users| users.signer(user_id))
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        log::info!(
+            "🔑 Signing as user '{}' ({})",
+            user_id,
+            derived.account_id()
+        );
+        return Ok((signer, derived));
+    }
+
+    if let Some(managed) = state.keystore.get(&signer) {
+        log::info!(
+            "🔑 Signing as '{}' ({:?}, {:?})",
+            signer,
+            managed.scheme(),
+            managed.backend()
+        );
+        return Ok((signer, managed));
+    }
+
+    if !state.config.dev_mode {
+        log::warn!("⚠️ Rejected signer that is not a keystore alias");
+        return Err((
+            StatusCode::OK,
+            "Unknown signer: use a keystore alias (raw seeds are only accepted in dev mode)"
+                .to_string(),
+        ));
+    }
+
+    // Dev mode: a secret URI ("//Alice", a mnemonic, a hex seed, optionally
+    // with derivation paths) becomes a key pair; "ed25519://Alice" or
+    // "ecdsa://Alice" select another scheme than sr25519
+    match LocalSigner::from_suri(&signer) {
+        Ok(local) => Ok((local.account_id().to_string(), Arc::new(local))),
+        Err(_) => {
+            log::error!("❌ Invalid signer seed provided");
+            Err((StatusCode::OK, "Invalid signer".to_string()))
+        }
+    }
+}
+
+/// The deriver of end users' accounts, or why there is none
+fn user_accounts(state: &AppState) -> Result<&UserAccounts, String> {
+    state
+        .users
+        .as_ref()
+        .ok_or_else(|| "User accounts are disabled: USER_MASTER_SIGNER is not set".to_string())
+}
+
+/// Turns a policy denial into a `403 Forbidden` response
+fn policy_denied(reason: String) -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(TransactionResponse::failure(reason)),
+    )
+        .into_response()
+}
+
+/// Signs and submits a call using the managed nonce for the signer
+///
+/// This is the shared write path behind every submission endpoint:
+/// 1. Reserves the next nonce and signs the call with it
+/// 2. In sync mode, submits it and waits for the `wait_for` stage, retrying
+///    failed submissions according to the configured retry policy
+/// 3. In async mode, hands it to a background job and returns `202 Accepted`
+///
+/// # Arguments
+/// * `state` - Shared application state (client + nonce manager + jobs)
+/// * `signer` - The key pair that signs the transaction
+/// * `call` - Any static or dynamic call payload
+/// * `call_info` - Description of the call for the transaction's receipt
+/// * `params` - Mortality and tip for the transaction
+/// * `wait_for` - The lifecycle stage to wait for in sync mode
+/// * `options` - Query options selecting sync or async mode
+///
+/// # Returns
+/// The HTTP response; failures are reported with `success: false`
+async fn submit_with_managed_nonce<Call>(
+    state: &AppState,
+    signer: Arc<dyn TxSigner>,
+    call: &Call,
+    call_info: CallInfo,
+    params: TxParams,
+    wait_for: TxStage,
+    options: &SubmitOptions,
+) -> Response
+where
+    Call: subxt::tx::Payload,
+{
+    // Keep the encoded call around so a retry can sign it again with a new nonce
+    let call_data = match call.encode_call_data(&state.client.metadata()) {
+        Ok(call_data) => call_data,
+        Err(e) => {
+            log::error!("❌ Failed to encode call: {:?}", e);
+            let response = TransactionResponse::failure(format!("Failed to encode call: {:?}", e));
+            return Json(response).into_response();
+        }
+    };
+
+    let tx = match sign_with_managed_nonce(state, &signer, call, &params).await {
+        Ok(tx) => tx,
+        Err(e) => return Json(TransactionResponse::failure(e)).into_response(),
+    };
+    state.receipts.record_signed(&tx, &call_info);
+
+    let resigner = Resigner {
+        signer,
+        call_data,
+        params,
+        call_info,
+    };
+
+    submit_signed(state, tx, Some(resigner), wait_for, options).await
+}
+
+/// Submits a signed transaction in sync or async mode
+///
+/// In async mode the transaction is handed to a background job and
+/// `202 Accepted` is returned; in sync mode the response is returned once the
+/// transaction reaches `wait_for`. Either way, failures are retried according
+/// to the configured retry policy.
+///
+/// # Arguments
+/// * `state` - Shared application state
+/// * `tx` - The signed transaction, with its receipt already recorded
+/// * `resigner` - Used to sign the call again after nonce problems (`None` for client-signed transactions)
+/// * `wait_for` - The lifecycle stage to wait for in sync mode
+/// * `options` - Query options selecting sync or async mode
+async fn submit_signed(
+    state: &AppState,
+    tx: ManagedTransaction,
+    resigner: Option<Resigner>,
+    wait_for: TxStage,
+    options: &SubmitOptions,
+) -> Response {
+    if options.run_async {
+        let transaction_hash = format!("{:?}", tx.extrinsic.hash());
+        let job_id = state.jobs.create(transaction_hash.clone()).await;
+        log::info!(
+            "📋 Created job {} for transaction {}",
+            job_id,
+            transaction_hash
+        );
+
+        // The background task owns the transaction from here on
+        tokio::spawn(track_job(state.clone(), job_id.clone(), tx, resigner));
+
+        let accepted = JobAccepted {
+            status_url: format!("/jobs/{}", job_id),
+            job_id,
+            transaction_hash,
+        };
+        return (StatusCode::ACCEPTED, Json(accepted)).into_response();
+    }
+
+    Json(submit_with_retry(state, tx, resigner.as_ref(), wait_for, None).await).into_response()
+}
+
+/// Reserves the next nonce for the signer and signs the call with it
+///
+/// If signing fails, the nonce is handed back to the nonce manager so the
+/// next transaction can reuse it.
+///
+/// # Returns
+/// * `Ok(ManagedTransaction)` - The signed transaction plus its account and nonce
+/// * `Err(String)` - Error message for the client
+pub async fn sign_with_managed_nonce<Call>(
+    state: &AppState,
+    signer: &Arc<dyn TxSigner>,
+    call: &Call,
+    params: &TxParams,
+) -> Result<ManagedTransaction, String>
+where
+    Call: subxt::tx::Payload,
+{
+    // The signer's account is what the nonce manager tracks
+    let account_id = signer.account_id();
+
+    // Get the next nonce for this account using our production nonce manager
+    // This prevents nonce conflicts when multiple transactions are submitted concurrently
+    let lease = match state.nonce_manager.get_next_nonce(&account_id).await {
+        Ok(lease) => lease,
+        Err(e) => {
+            log::error!(
+                "❌ Failed to get nonce for account {:?}: {:?}",
+                account_id,
+                e
+            );
+            return Err(format!("Failed to get nonce: {:?}", e));
+        }
+    };
+    let nonce = lease.nonce();
+    // Lets the gap check fill this nonce if it never reaches the pool
+    state.nonce_manager.remember_signer(signer).await;
+
+    // Create the signed transaction with explicit nonce control
+    // This ensures our nonce manager maintains proper transaction sequencing
+    match create_signed_transaction_with_nonce(
+        &state.client,
+        &state.rpc,
+        call,
+        signer.as_ref(),
+        nonce,
+        params,
+        state.config.signing_strategy,
+    )
+    .await
+    {
+        Ok(signed) => {
+            state.metrics.record_signing(signed.path);
+            if signed.path == SigningPath::Fallback {
+                // The reservation went unused; follow the nonce that was
+                state
+                    .nonce_manager
+                    .adopt_fallback_nonce(&account_id, nonce, signed.nonce)
+                    .await;
+            }
+            Ok(ManagedTransaction {
+                extrinsic: signed.extrinsic,
+                account_id,
+                nonce: signed.nonce,
+                tip: params.tip,
+                reserved: true,
+                signing_path: signed.path,
+                lease: Some(lease),
+            })
+        }
+        Err(e) => {
+            // Hand the nonce back since we failed to create the transaction,
+            // unless a later nonce was reserved in the meantime
+            state.nonce_manager.release_nonce(&account_id, nonce).await;
+
+            log::error!("❌ Failed to create signed transaction: {:?}", e);
+            Err(format!("Failed to create transaction: {:?}", e))
+        }
+    }
+}
+
+/// How a submitted transaction failed, which decides what happened to its nonce
+#[derive(Debug)]
+pub enum TxFailure {
+    /// Rejected, dropped or lost before reaching a block; the nonce was not used
+    /// by this transaction, and the kind says whether it was used by another one
+    NotIncluded(FailureKind, TransactionResponse),
+    /// Included in a block, but the call failed during execution
+    DispatchFailed(TransactionResponse),
+    /// The status stream ended early; the transaction may still land
+    Unknown(TransactionResponse),
+}
+
+/// Follows a submitted transaction until it reaches the requested stage
+///
+/// Callers decide what to do with the nonce of a failed transaction based on
+/// the kind of failure; this function never touches the nonce manager.
+///
+/// Progress is mirrored into the transaction's receipt, and into the job
+/// `job_id` if one is given. If the caller only
+/// waits for submission or a best block, the rest of the stream is followed
+/// in the background so that the receipt still records finalization.
+///
+/// # Arguments
+/// * `state` - Shared application state, used to describe the inclusion block
+/// * `progress` - The progress stream returned by `submit_and_watch`
+/// * `wait_for` - The lifecycle stage to wait for
+/// * `job_id` - The async job to mirror progress into, if any
+///
+/// # Returns
+/// * `Ok(TransactionResponse)` - The transaction reached `wait_for` and succeeded
+/// * `Err(TxFailure)` - The failure kind, carrying the response to report
+pub async fn watch_progress(
+    state: &AppState,
+    mut progress: TxProgress<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    wait_for: TxStage,
+    job_id: Option<&str>,
+) -> Result<TransactionResponse, TxFailure> {
+    let result = follow_progress(state, &mut progress, wait_for, job_id).await;
+
+    if wait_for != TxStage::Finalized && result.is_ok() {
+        let state = state.clone();
+        tokio::spawn(async move {
+            // The outcome is only recorded in the receipt
+            let _ = follow_progress(&state, &mut progress, TxStage::Finalized, None).await;
+        });
+    }
+
+    result
+}
+
+/// Reads the progress stream until the transaction reaches `wait_for` or fails
+async fn follow_progress(
+    state: &AppState,
+    progress: &mut TxProgress<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    wait_for: TxStage,
+    job_id: Option<&str>,
+) -> Result<TransactionResponse, TxFailure> {
+    let hash = progress.extrinsic_hash();
+    let tx_hash = format!("{:?}", hash);
+
+    // The node accepted the transaction into its pool; that's all some callers need
+    if wait_for == TxStage::Submitted {
+        let response = TransactionResponse {
+            success: true,
+            transaction_hash: Some(tx_hash),
+            block_hash: None,
+            block_header: None,
+            extrinsic_index: None,
+            events: None,
+            stage: Some(TxStage::Submitted),
+            error: None,
+            attempts: Vec::new(),
+        };
+        log_response(&response);
+        return Ok(response);
+    }
+
+    // Follow the transaction through the pool and into blocks
+    // InBestBlock  -> included in a block that may still be reorganized away
+    // InFinalized  -> included in a block that is permanent
+    // Each TxInBlock carries the hash of the block containing the transaction
+    let mut reached = TxStage::Submitted;
+    while let Some(status) = progress.next().await {
+        let is_invalid = matches!(status, Ok(TxStatus::Invalid { .. }));
+        let (in_block, stage) = match status {
+            Ok(TxStatus::InBestBlock(in_block)) => {
+                reached = TxStage::InBlock;
+                state.receipts.mark_in_block(hash, in_block.block_hash());
+                if let Some(job_id) = job_id {
+                    let block_hash = format!("{:?}", in_block.block_hash());
+                    state
+                        .jobs
+                        .set_status(job_id, JobStatus::InBlock, None, Some(block_hash))
+                        .await;
+                }
+                if wait_for != TxStage::InBlock {
+                    continue;
+                }
+                (in_block, TxStage::InBlock)
+            }
+            Ok(TxStatus::InFinalizedBlock(in_block)) => (in_block, TxStage::Finalized),
+            Ok(TxStatus::Error { message })
+            | Ok(TxStatus::Invalid { message })
+            | Ok(TxStatus::Dropped { message }) => {
+                // Transaction was rejected by the pool or dropped
+                // Invalid carries the reason (e.g. usurped); errors and drops can be retried
+                let kind = if is_invalid {
+                    FailureKind::from_invalid_status(&message)
+                } else {
+                    FailureKind::Transient
+                };
+                log::error!("❌ Transaction rejected ({:?}): {}", kind, message);
+                let mut response =
+                    TransactionResponse::failure(format!("Transaction failed: {}", message));
+                response.transaction_hash = Some(tx_hash);
+                response.stage = Some(reached);
+                state.receipts.mark_failed(hash, &response);
+                return Err(TxFailure::NotIncluded(kind, response));
+            }
+            Ok(TxStatus::Validated) | Ok(TxStatus::NoLongerInBestBlock) => {
+                if let Some(job_id) = job_id {
+                    state
+                        .jobs
+                        .set_status(job_id, JobStatus::Submitted, None, None)
+                        .await;
+                }
+                continue;
+            }
+            Ok(TxStatus::Broadcasted { .. }) => {
+                if let Some(job_id) = job_id {
+                    state
+                        .jobs
+                        .set_status(job_id, JobStatus::Broadcast, None, None)
+                        .await;
+                }
+                continue;
+            }
+            Err(e) => {
+                log::error!("❌ Lost track of transaction: {:?}", e);
+                let mut response =
+                    TransactionResponse::failure(format!("Transaction failed: {:?}", e));
+                response.transaction_hash = Some(tx_hash);
+                response.stage = Some(reached);
+                state.receipts.mark_failed(hash, &response);
+                return Err(TxFailure::NotIncluded(FailureKind::Transient, response));
+            }
+        };
+
+        // Being in a block doesn't mean the call succeeded
+        // wait_for_success() looks for a System.ExtrinsicFailed event for our transaction
+        let events = match in_block.wait_for_success().await {
+            Ok(events) => events,
+            Err(e) => {
+                // Transaction was included but failed during execution
+                log::error!("❌ Transaction failed during execution: {:?}", e);
+                let mut response =
+                    TransactionResponse::failure(format!("Transaction failed: {:?}", e));
+                response.transaction_hash = Some(tx_hash);
+                response.block_hash = Some(format!("{:?}", in_block.block_hash()));
+                response.stage = Some(stage);
+                state.receipts.mark_failed(hash, &response);
+                return Err(TxFailure::DispatchFailed(response));
+            }
+        };
+
+        let response =
+            inclusion_response(&state.client, in_block.block_hash(), &events, stage).await;
+        state.receipts.mark_included(hash, &response);
+        log_response(&response);
+        return Ok(response);
+    }
+
+    // The status stream ended before the requested stage; the transaction may still land
+    log::error!("❌ Transaction status stream ended before {:?}", wait_for);
+    let mut response = TransactionResponse::failure("Transaction status stream ended unexpectedly");
+    response.transaction_hash = Some(tx_hash);
+    response.stage = Some(reached);
+    Err(TxFailure::Unknown(response))
+}
+
+/// Builds the success response for a transaction included in a block
+///
+/// Everything in the response describes the block that actually contains the
+/// transaction (the one reported by `TxInBlock`), never just the latest block.
+///
+/// # Arguments
+/// * `client` - Used to fetch the inclusion block's header
+/// * `block_hash` - Hash of the block containing the transaction
+/// * `events` - The transaction's events, as returned by `wait_for_success`
+/// * `stage` - The lifecycle stage that was reached
+pub async fn inclusion_response(
+    client: &OnlineClient<SubstrateConfig>,
+    block_hash: H256,
+    events: &ExtrinsicEvents<SubstrateConfig>,
+    stage: TxStage,
+) -> TransactionResponse {
+    // The header is informational; a failure to fetch it doesn't undo the inclusion
+    let block_header = match client.blocks().at(block_hash).await {
+        Ok(block) => Some(BlockHeaderInfo {
+            parent_hash: format!("{:?}", block.header().parent_hash),
+            state_root: format!("{:?}", block.header().state_root),
+            extrinsics_root: format!("{:?}", block.header().extrinsics_root),
+            block_number: block.number(),
+            digest: format!("{:?}", block.header().digest),
+        }),
+        Err(e) => {
+            log::warn!("⚠️ Failed to fetch block {:?}: {:?}", block_hash, e);
+            None
+        }
+    };
+
+    let decoded_events = match decode_extrinsic_events(events, &client.metadata()) {
+        Ok(decoded) => Some(decoded),
+        Err(e) => {
+            log::warn!("⚠️ {}", e);
+            None
+        }
+    };
+
+    TransactionResponse {
+        success: true,
+        transaction_hash: Some(format!("{:?}", events.extrinsic_hash())),
+        block_hash: Some(format!("{:?}", block_hash)),
+        block_header,
+        extrinsic_index: Some(events.extrinsic_index()),
+        events: decoded_events,
+        stage: Some(stage),
+        error: None,
+        attempts: Vec::new(),
+    }
+}
+
+/// Logs a successful transaction response
+fn log_response(response: &TransactionResponse) {
+    log::info!("📤 OUTGOING RESPONSE:");
+    log::info!("   Success: {}", response.success);
+    log::info!("   Stage: {:?}", response.stage);
+    log::info!("   Transaction Hash: {:?}", response.transaction_hash);
+    log::info!("   Block Hash: {:?}", response.block_hash);
+    log::info!("   Block Header: {:?}", response.block_header);
+    log::info!("   Extrinsic Index: {:?}", response.extrinsic_index);
+    if let Some(events) = &response.events {
+        for event in events {
+            log::info!(
+                "   Event: {}.{} {}",
+                event.pallet,
+                event.variant,
+                event.fields
+            );
+        }
+    }
+    log::info!("   Error: {:?}", response.error);
+}
+
+/// Handles the /tx/batch endpoint for submitting many transactions from one signer
+///
+/// The whole batch shares one contiguous nonce range: every transaction is
+/// signed up front, submitted back to back without waiting for blocks, and
+/// then tracked in parallel. See `batch::submit_batch` for how partial
+/// failures are handled.
+///
+/// # Request Format
+/// POST /tx/batch
+/// ```json
+/// {
+///   "values": [1, 2, 3],
+///   "calls": [{ "pallet": "System", "call": "remark", "args": "hello" }],
+///   "signer": "treasury",    // optional keystore alias, defaults to DEFAULT_SIGNER
+///   "wait_for": "in_block"   // optional: submitted | in_block | finalized (default)
+/// }
+/// ```
+///
+/// # Response Format
+/// ```json
+/// {
+///   "success": true,
+///   "first_nonce": 12,
+///   "submitted": 4,
+///   "succeeded": 4,
+///   "failed": 0,
+///   "items": [
+///     { "index": 0, "nonce": 12, "submitted": true, "result": { ... }, "gap_filled_by": null },
+///     ...
+///   ],
+///   "error": null
+/// }
+/// ```
+///
+/// # Returns
+/// JSON response with a result for every item in the batch, or 403 if the
+/// signer's policy denies any of the calls
+pub async fn batch_handler(
+    State(state): State<AppState>,
+    ApiClient(client): ApiClient,
+    Json(payload): Json<BatchRequest>,
+) -> Result<Response, StatusCode> {
+    let count = payload.values.len() + payload.calls.len();
+    log::info!("📥 INCOMING BATCH REQUEST: {} transaction(s)", count);
+
+    if count == 0 {
+        return Ok(Json(BatchResponse::failure("Batch is empty")).into_response());
+    }
+    if count > state.config.max_batch_size {
+        let response = BatchResponse::failure(format!(
+            "Batch of {} exceeds the maximum of {}",
+            count, state.config.max_batch_size
+        ));
+        return Ok(Json(response).into_response());
+    }
+
+    let (signer_name, signer) = match parse_signer(&state, payload.signer) {
+        Ok(resolved) => resolved,
+        Err((status, e)) => return Ok((status, Json(BatchResponse::failure(e))).into_response()),
+    };
+
+    // Build every call before reserving any nonces, so bad input costs nothing
+    let metadata = state.client.metadata();
+    let value_calls = payload.values.iter().map(|value| {
+        CallInfo::new(
+            "Template",
+            "do_something",
+            serde_json::json!({ "something": value }),
+        )
+    });
+    let other_calls = payload
+        .calls
+        .into_iter()
+        .map(|c| CallInfo::new(c.pallet, c.call, c.args));
+
+    let mut calls = Vec::with_capacity(count);
+    for (index, info) in value_calls.chain(other_calls).enumerate() {
+        // One denied item denies the whole batch
+        if let Err(e) = state
+            .policy
+            .authorize(&signer_name, client.as_deref(), &info)
+        {
+            let response = BatchResponse::failure(format!("Item {}: {}", index, e));
+            return Ok((StatusCode::FORBIDDEN, Json(response)).into_response());
+        }
+        match build_dynamic_call(&metadata, &info.pallet, &info.call, &info.args) {
+            Ok(call) => calls.push((call, info)),
+            Err(e) => {
+                log::error!("❌ Invalid batch item {}: {}", index, e);
+                let response = BatchResponse::failure(format!("Item {}: {}", index, e));
+                return Ok(Json(response).into_response());
+            }
+        }
+    }
+
+    let params = state.config.tx_params(payload.mortality, payload.tip);
+
+    Ok(Json(submit_batch(&state, signer, calls, params, payload.wait_for).await).into_response())
 }
 
-/// Response payload for the get_storage endpoint
+/// Handles the /tx/{hash}/replace endpoint for swapping a pending transaction's call
 ///
-/// This structure contains the current value stored on the blockchain
-/// along with the block hash where the value was queried from.
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GetStorageResponse {
-    /// The current value stored on the blockchain (None if not set)
-    pub value: Option<u32>,
-    /// The block hash where this value was queried from
-    pub block_hash: String,
+/// The new call is signed at the pending transaction's nonce with a higher
+/// tip, so the pool evicts the original in its favour. See
+/// `replace::replace_transaction` for how the winner is decided.
+///
+/// # Request Format
+/// POST /tx/0x1234.../replace
+/// ```json
+/// {
+///   "pallet": "Template",
+///   "call": "do_something",
+///   "args": { "something": 43 },
+///   "signer": "treasury", // optional, must match the original
+///   "tip": 1000           // optional, defaults to the original tip plus 10%
+/// }
+/// ```
+///
+/// # Returns
+/// JSON outcome with the winner; 404 if the transaction is unknown, 409 if it
+/// is no longer pending, 400 for a wrong signer or a tip that is too low, 403
+/// if the signer's policy doesn't allow the new call
+pub async fn replace_handler(
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+    ApiClient(client): ApiClient,
+    Json(payload): Json<ReplaceRequest>,
+) -> Response {
+    log::info!(
+        "📥 INCOMING REPLACE REQUEST: {} -> {}.{}",
+        hash,
+        payload.pallet,
+        payload.call
+    );
+
+    let (signer_name, signer) = match parse_signer(&state, payload.signer) {
+        Ok(resolved) => resolved,
+        Err((_, e)) => return replace_error(&hash, ReplaceError::BadRequest(e)),
+    };
+
+    let call_info = CallInfo::new(payload.pallet, payload.call, payload.args);
+    if let Err(e) = state
+        .policy
+        .authorize(&signer_name, client.as_deref(), &call_info)
+    {
+        return replace_error(&hash, ReplaceError::Forbidden(e));
+    }
+    let call = match build_dynamic_call(
+        &state.client.metadata(),
+        &call_info.pallet,
+        &call_info.call,
+        &call_info.args,
+    ) {
+        Ok(call) => call,
+        Err(e) => return replace_error(&hash, ReplaceError::BadRequest(e)),
+    };
+
+    match replace_transaction(
+        &state,
+        &hash,
+        signer,
+        &call,
+        call_info,
+        payload.tip,
+        payload.mortality,
+        payload.wait_for,
+    )
+    .await
+    {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => replace_error(&hash, e),
+    }
 }
 
-/// Application state shared across all HTTP handlers
+/// Handles the /tx/{hash}/cancel endpoint for cancelling a pending transaction
 ///
-/// This struct contains the core dependencies that handlers need to
-/// interact with the blockchain and manage transaction state:
-/// - Blockchain client for queries and transaction submission
-/// - Nonce manager for preventing transaction conflicts
+/// Works like /tx/{hash}/replace with an empty System.remark as the new
+/// call, so the nonce is used up without doing anything.
 ///
-/// The Clone trait allows this state to be efficiently shared across
-/// multiple concurrent HTTP requests without expensive copying.
-#[derive(Clone)]
-pub struct AppState {
-    /// The subxt client for blockchain communication
-    pub client: OnlineClient<SubstrateConfig>,
-    /// Production-grade nonce manager for transaction sequencing
-    pub nonce_manager: NonceManager,
+/// # Request Format
+/// POST /tx/0x1234.../cancel
+/// ```json
+/// { "signer": "treasury", "tip": 1000 }  // both optional
+/// ```
+///
+/// # Returns
+/// Same as /tx/{hash}/replace
+pub async fn cancel_handler(
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+    ApiClient(client): ApiClient,
+    Json(payload): Json<CancelRequest>,
+) -> Response {
+    log::info!("📥 INCOMING CANCEL REQUEST: {}", hash);
+
+    let (signer_name, signer) = match parse_signer(&state, payload.signer) {
+        Ok(resolved) => resolved,
+        Err((_, e)) => return replace_error(&hash, ReplaceError::BadRequest(e)),
+    };
+
+    let call = chain_a::tx().system().remark(Vec::new());
+    let call_info = CallInfo::new("System", "remark", serde_json::json!({ "remark": "0x" }));
+    if let Err(e) = state
+        .policy
+        .authorize(&signer_name, client.as_deref(), &call_info)
+    {
+        return replace_error(&hash, ReplaceError::Forbidden(e));
+    }
+
+    match replace_transaction(
+        &state,
+        &hash,
+        signer,
+        &call,
+        call_info,
+        payload.tip,
+        payload.mortality,
+        payload.wait_for,
+    )
+    .await
+    {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => replace_error(&hash, e),
+    }
 }
 
-/// Health check endpoint for service monitoring
+/// Turns a `ReplaceError` into an HTTP response
+fn replace_error(hash: &str, error: ReplaceError) -> Response {
+    let (status, message) = match error {
+        ReplaceError::NotFound => (
+            StatusCode::NOT_FOUND,
+            "No transaction with this hash was signed here".to_string(),
+        ),
+        ReplaceError::NotPending(status) => (
+            StatusCode::CONFLICT,
+            format!("Transaction is no longer pending (status: {:?})", status),
+        ),
+        ReplaceError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+        ReplaceError::Forbidden(message) => (StatusCode::FORBIDDEN, message),
+    };
+    log::warn!("⚠️ Cannot replace {}: {}", hash, message);
+    (status, Json(ReplaceResponse::failure(hash, message))).into_response()
+}
+
+/// Handles the /validators/{signer}/rotate-keys endpoint
 ///
-/// This endpoint provides a simple way for load balancers, monitoring
-/// systems, or clients to verify that the API service is running and
-/// responsive. It returns basic service information without requiring
-/// any blockchain interaction.
+/// Has the local node generate new session keys (`author_rotateKeys`),
+/// registers them with `Session.set_keys` signed by the validator's signer
+/// through the nonce-managed path, then reads them back from storage (see
+/// `session`). The path names the signer like a request body would.
+///
+/// # Request Format
+/// POST /validators/validator-1/rotate-keys
+/// ```json
+/// { "wait_for": "in_block" }  // optional: in_block | finalized (default); mortality and tip too
+/// ```
+///
+/// # Response Format
+/// ```json
+/// {
+///   "success": true,
+///   "validator": "5GNJqTPy...",
+///   "session_keys": "0x9a5c...",
+///   "transaction": { "success": true, "transaction_hash": "0x...", ... },
+///   "next_keys_confirmed": true,
+///   "queued": false,
+///   "error": null
+/// }
+/// ```
 ///
 /// # Returns
-/// JSON object with service status and identification information
+/// JSON outcome of every step; 403 if the signer's policy doesn't allow
+/// `Session.set_keys` with the new keys
+pub async fn rotate_keys_handler(
+    State(state): State<AppState>,
+    Path(signer): Path<String>,
+    ApiClient(client): ApiClient,
+    Json(payload): Json<RotateKeysRequest>,
+) -> Response {
+    log::info!("📥 INCOMING ROTATE KEYS REQUEST");
+
+    let (signer_name, signer) = match parse_signer(&state, Some(signer)) {
+        Ok(resolved) => resolved,
+        Err((status, e)) => return (status, Json(RotateKeysResponse::failure(e))).into_response(),
+    };
+
+    let params = state.config.tx_params(payload.mortality, payload.tip);
+    match rotate_session_keys(
+        &state,
+        &signer_name,
+        client.as_deref(),
+        signer,
+        params,
+        payload.wait_for,
+    )
+    .await
+    {
+        Ok(response) => Json(response).into_response(),
+        Err(denied) => (StatusCode::FORBIDDEN, Json(denied)).into_response(),
+    }
+}
+
+/// Handles the /tx/submit-raw endpoint for relaying client-signed extrinsics
 ///
-/// # Example Response
+/// The extrinsic is decoded against the runtime metadata to validate it and
+/// to learn its signer, call and nonce (see `relay`), then submitted and
+/// tracked exactly like the transactions this server signs, including
+/// receipts, `wait_for` and `?async=true`. The server never sees a seed, so
+/// the extrinsic is only ever resubmitted unchanged, never re-signed.
+///
+/// # Request Format
+/// POST /tx/submit-raw
 /// ```json
 /// {
-///   "status": "healthy",
-///   "service": "Chain A Backend API"
+///   "extrinsic": "0x4502840...",   // SCALE-encoded signed extrinsic
+///   "wait_for": "in_block"          // optional, defaults to "finalized"
 /// }
 /// ```
+///
+/// # Response Format
+/// Same as /do-something
+///
+/// # Returns
+/// JSON outcome; 403 if the signer policy doesn't allow the extrinsic's
+/// account to make its call
+pub async fn submit_raw_handler(
+    State(state): State<AppState>,
+    Query(options): Query<SubmitOptions>,
+    ApiClient(client): ApiClient,
+    Json(payload): Json<SubmitRawRequest>,
+) -> Response {
+    log::info!("📥 INCOMING RAW EXTRINSIC");
+
+    let (tx, decoded) = match decode_signed_extrinsic(&state.client, &payload.extrinsic) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            log::warn!("⚠️ Rejected raw extrinsic: {}", e);
+            return Json(TransactionResponse::failure(e)).into_response();
+        }
+    };
+    log::info!(
+        "   Signer: {}, nonce: {}, call: {}.{} {}",
+        decoded.signer,
+        decoded.nonce,
+        decoded.call.pallet,
+        decoded.call.call,
+        decoded.call.args
+    );
+
+    if let Err(e) = authorize_account(&state, &tx.account_id, client.as_deref(), &decoded.call) {
+        log::warn!("⚠️ Rejected raw extrinsic: {}", e);
+        return policy_denied(e);
+    }
 
-pub async fn health_check() -> Json<HashMap<String, String>> {
-    let mut response = HashMap::new();
-    response.insert("status".to_string(), "healthy".to_string());
-    response.insert("service".to_string(), "Chain A Backend API".to_string());
-    Json(response)
+    // Keep the managed nonces of this account clear of the client's nonce
+    state
+        .nonce_manager
+        .observe_external_nonce(&tx.account_id, tx.nonce)
+        .await;
+    state.receipts.record_signed(&tx, &decoded.call);
+
+    submit_signed(&state, tx, None, payload.wait_for, &options).await
 }
 
-/// Handles the /do-something endpoint for submitting transactions to the blockchain
+/// Handles the /tx/prepare endpoint, the first step of external signing
 ///
-/// This is the main transaction endpoint that:
-/// 1. Validates the request payload and signer
-/// 2. Gets the next available nonce for the account
-/// 3. Creates and signs a blockchain transaction
-/// 4. Submits the transaction and waits for finalization
-/// 5. Returns the transaction result with hashes and status
+/// Builds the call, reserves the account's next nonce and builds the
+/// transaction body with the same mortality and tip rules as the other
+/// submission endpoints. The response carries the exact bytes the account
+/// has to sign and a ticket for /tx/complete. The ticket holds the nonce
+/// reservation until it is completed or expires (see `tickets`).
 ///
-/// The endpoint uses production-grade nonce management to prevent conflicts
-/// when multiple transactions are submitted concurrently for the same account.
+/// Accounts of managed signers (keystore aliases and derived users) are
+/// refused with 400: their nonces belong to the nonce-managed path. Other
+/// accounts are checked against the signer policy as external accounts (403
+/// if denied), and may only hold a limited number of open tickets (429
+/// beyond that). All checks happen before a nonce is reserved.
 ///
 /// # Request Format
-/// POST /do-something
+/// POST /tx/prepare
 /// ```json
 /// {
-///   "value": 42,
-///   "signer": "//Alice"  // optional, defaults to //Alice
+///   "pallet": "Template",
+///   "call": "do_something",
+///   "args": { "something": 42 },
+///   "account": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
 /// }
 /// ```
 ///
@@ -147,227 +2113,383 @@ pub async fn health_check() -> Json<HashMap<String, String>> {
 /// ```json
 /// {
 ///   "success": true,
-///   "transaction_hash": "0x...",
-///   "block_hash": "0x...",
+///   "ticket": "0b6e...",
+///   "signer_payload": "0x...",
+///   "nonce": 12,
+///   "expires_at": 1700000300,
 ///   "error": null
 /// }
 /// ```
-///
-/// # Arguments
-/// * `state` - Shared application state (client + nonce manager)
-/// * `payload` - JSON request body with value and optional signer
-///
-/// # Returns
-/// JSON response with transaction result or error details
-pub async fn do_something_handler(
+pub async fn prepare_handler(
     State(state): State<AppState>,
-    Json(payload): Json<DoSomethingRequest>,
-) -> Result<Json<DoSomethingResponse>, StatusCode> {
-    // 📥 LOG THE INCOMING REQUEST
-    log::info!("📥 INCOMING REQUEST:");
-    log::info!("   Raw payload: {:?}", payload);
-    log::info!("   Value: {}", payload.value);
-    log::info!("   Signer: {:?}", payload.signer);
-
-    // Parse and validate the signer account
-    // Default to Alice if no signer is provided in the request
-    let signer_seed = payload.signer.unwrap_or_else(|| "//Alice".to_string());
-
-    // This is a static method that converts a seed string into a cryptographic key pair for blockchain
-    // transactions.
-
-    // Pair - The Key Pair Type
-    // What it is: A cryptographic key pair (public + private keys)
-
-    // from_string() - The Conversion Method
-    // What it does: Converts a human-readable string into actual cryptographic keys
+    ApiClient(client): ApiClient,
+    Json(payload): Json<PrepareRequest>,
+) -> Response {
+    log::info!(
+        "📥 INCOMING PREPARE REQUEST: {}.{} for {}",
+        payload.pallet,
+        payload.call,
+        payload.account
+    );
+
+    let account_id = match parse_account_id(&payload.account) {
+        Ok(account_id) => account_id,
+        Err(e) => return Json(PrepareResponse::failure(e)).into_response(),
+    };
 
-    // &signer_seed - The Input String
+    // Tickets would hold nonces in the sequence of a managed signer, whose
+    // own submissions would then pile up behind expired ones
+    if let Some(name) = managed_signer_name(&state.keystore, state.users.as_ref(), &account_id) {
+        log::warn!("⚠️ Prepare for managed signer '{}' refused", name);
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(PrepareResponse::failure(format!(
+                "Account belongs to managed signer '{}'; submit through the signing endpoints",
+                name
+            ))),
+        )
+            .into_response();
+    }
 
-    let signer = match Pair::from_string(&signer_seed, None) {
-        Ok(pair) => pair,
-        Err(_) => {
-            log::error!("❌ Invalid signer seed provided: {}", signer_seed);
-            return Ok(Json(DoSomethingResponse {
-                success: false,
-                transaction_hash: None,
-                block_hash: None,
-                block_header: None,
-                error: Some("Invalid signer".to_string()),
-            }));
+    let dynamic_call = match build_dynamic_call(
+        &state.client.metadata(),
+        &payload.pallet,
+        &payload.call,
+        &payload.args,
+    ) {
+        Ok(dynamic_call) => dynamic_call,
+        Err(e) => {
+            log::error!(
+                "❌ Failed to build call {}.{}: {}",
+                payload.pallet,
+                payload.call,
+                e
+            );
+            return Json(PrepareResponse::failure(e)).into_response();
         }
     };
 
-    // Keypair {
-    //     secret: SecretKey,  ✅ Has private key for signing
-    //     public: PublicKey,  ✅ Has public key for verification
-    // }
+    // The same rules as for managed signers, before anything is reserved
+    let call_info = CallInfo::new(payload.pallet, payload.call, payload.args);
+    if let Err(e) = authorize_account(&state, &account_id, client.as_deref(), &call_info) {
+        return (StatusCode::FORBIDDEN, Json(PrepareResponse::failure(e))).into_response();
+    }
+    if let Err(e) = state.tickets.check_capacity(&account_id).await {
+        log::warn!("⚠️ Prepare for {:?} refused: {}", account_id, e);
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(PrepareResponse::failure(e)),
+        )
+            .into_response();
+    }
+
+    let call_data =
+        match subxt::tx::Payload::encode_call_data(&dynamic_call, &state.client.metadata()) {
+            Ok(call_data) => call_data,
+            Err(e) => {
+                log::error!("❌ Failed to encode call: {:?}", e);
+                return Json(PrepareResponse::failure(format!(
+                    "Failed to encode call: {:?}",
+                    e
+                )))
+                .into_response();
+            }
+        };
 
-    // Convert the cryptographic public key to an AccountId32 for nonce management
-    let account_id = AccountId32::from(signer.public());
+    let params = state.config.tx_params(payload.mortality, payload.tip);
 
-    // Get the next nonce for this account using our production nonce manager
-    // This prevents nonce conflicts when multiple transactions are submitted concurrently
-    let nonce = match state.nonce_manager.get_next_nonce(&account_id).await {
-        Ok(nonce) => nonce,
+    let lease = match state.nonce_manager.get_next_nonce(&account_id).await {
+        Ok(lease) => lease,
         Err(e) => {
             log::error!(
                 "❌ Failed to get nonce for account {:?}: {:?}",
                 account_id,
                 e
             );
-            return Ok(Json(DoSomethingResponse {
-                success: false,
-                transaction_hash: None,
-                block_hash: None,
-                block_header: None,
-                error: Some(format!("Failed to get nonce: {:?}", e)),
-            }));
+            return Json(PrepareResponse::failure(format!(
+                "Failed to get nonce: {:?}",
+                e
+            )))
+            .into_response();
         }
     };
+    let nonce = lease.nonce();
 
-    // Create the blockchain extrinsic call
-    // This represents the actual function call that will be executed on-chain
-    let call = chain_a::tx().template().do_something(payload.value);
+    let signing_params = match signing_params_with_nonce(&state.client, nonce, &params).await {
+        Ok(signing_params) => signing_params,
+        Err(e) => {
+            state.nonce_manager.release_nonce(&account_id, nonce).await;
+            log::error!("❌ Failed to create transaction: {:?}", e);
+            return Json(PrepareResponse::failure(format!(
+                "Failed to create transaction: {:?}",
+                e
+            )))
+            .into_response();
+        }
+    };
+
+    let prepared = PreparedTransaction {
+        call: RawCall(call_data),
+        signing_params,
+        account_id: account_id.clone(),
+        call_info,
+        // The external signer has until the ticket expires
+        lease,
+    };
+    match state.tickets.issue(&state.client, prepared).await {
+        Ok(response) => Json(response).into_response(),
+        Err(e) => {
+            state.nonce_manager.release_nonce(&account_id, nonce).await;
+            log::error!("❌ {}", e);
+            Json(PrepareResponse::failure(e)).into_response()
+        }
+    }
+}
 
-    // Wrap the cryptographic pair in a PairSigner for transaction signing
-    // it implement Signer<SubstrateConfig> trait that using this wrapper thats why using it instead of directly using struct if key pair.
-    let pair_signer = PairSigner::new(signer);
+/// The signer name of a managed account: its keystore alias, or
+/// `user:{user_id}` for a derived user account
+fn managed_signer_name(
+    keystore: &Keystore,
+    users: Option<&UserAccounts>,
+    account_id: &AccountId32,
+) -> Option<String> {
+    keystore.alias_of(account_id).or_else(|| {
+        users?
+            .user_of(account_id)
+            .map(|user_id| format!("{}{}", USER_SIGNER_PREFIX, user_id))
+    })
+}
 
-    // Create the signed transaction with explicit nonce control
-    // This ensures our nonce manager maintains proper transaction sequencing
-    let signed_tx =
-        match create_signed_transaction_with_nonce(&state.client, &call, &pair_signer, nonce).await
-        {
-            Ok(tx) => tx,
-            Err(e) => {
-                // Reset nonce since we failed to create transaction
-                // This allows the same nonce to be reused for the next attempt
-                state.nonce_manager.reset_nonce(&account_id, nonce).await;
-
-                log::error!("❌ Failed to create signed transaction: {:?}", e);
-                return Ok(Json(DoSomethingResponse {
-                    success: false,
-                    transaction_hash: None,
-                    block_hash: None,
-                    block_header: None,
-                    error: Some(format!("Failed to create transaction: {:?}", e)),
-                }));
-            }
-        };
+/// Checks a call signed outside this service against the signer policy
+///
+/// An account that belongs to a managed signer is checked under that
+/// signer's name, any other account as an external one (see
+/// `Policy::authorize_external`).
+fn authorize_account(
+    state: &AppState,
+    account_id: &AccountId32,
+    client: Option<&str>,
+    call: &CallInfo,
+) -> Result<(), String> {
+    match managed_signer_name(&state.keystore, state.users.as_ref(), account_id) {
+        Some(name) => state.policy.authorize(&name, client, call),
+        None => state.policy.authorize_external(account_id, client, call),
+    }
+}
 
-    // Submit the transaction to the blockchain and monitor its progress
-    // What This Does:
-    // Submits the signed transaction to the blockchain mempool
-    // Starts monitoring the transaction's progress through the blockchain
-    // Returns immediately after submission (doesn't wait for finalization)
-    // Success case:
-    // Ok(progress) -> TxProgress<SubstrateConfig, OnlineClient<SubstrateConfig>>
-    // What TxProgress Contains:
-    // TxProgress {
-    //     transaction_hash: H256,           // Unique transaction identifier
-    //     block_subscription: Stream,       // Real-time block updates
-    //     client: OnlineClient,            // For additional queries
-    //     // ... internal monitoring state
-    // }
-    match signed_tx.submit_and_watch().await {
-        Ok(progress) => {
-            // Wait for the transaction to be included in a finalized block
-            // This ensures the transaction is permanently recorded on the blockchain
-            // What wait_for_finalized_success() Does:
-            // Monitors blockchain for the transaction to be included in a block
-            // Waits for finalization (block becomes permanent, not just proposed)
-            // Validates success (transaction didn't fail during execution)
-            // Collects events emitted by the transaction
-            // Success case:
-            // Ok(events) -> TxInBlock<SubstrateConfig, OnlineClient<SubstrateConfig>>
-            // TxInBlock {
-            //     block_hash: H256,                // Hash of the block containing transaction
-            //     extrinsic_hash: H256,           // Transaction hash
-            //     extrinsic_index: u32,           // Position in block
-            //     events: Events,                 // All events emitted by this transaction
-            //     // ... other metadata
-            // }
-
-            match progress.wait_for_finalized_success().await {
-                Ok(events) => {
-                    // Extract the transaction hash from the finalized events
-                    let tx_hash = format!("{:?}", events.extrinsic_hash());
-
-                    // Get the hash of the block containing our transaction
-                    let block = match state.client.blocks().at_latest().await {
-                        Ok(block) => block,
-                        Err(_) => {
-                            return Ok(Json(DoSomethingResponse {
-                                success: false,
-                                transaction_hash: None,
-                                block_hash: None,
-                                block_header: None,
-                                error: Some("Failed to fetch latest block".to_string()),
-                            }))
-                        }
-                    };
-
-                    let block_hash = format!("{:?}", block.hash());
-
-                    let block_header = BlockHeaderInfo {
-                        parent_hash: format!("{:?}", block.header().parent_hash),
-                        state_root: format!("{:?}", block.header().state_root),
-                        extrinsics_root: format!("{:?}", block.header().extrinsics_root),
-                        block_number: block.number(),
-                        digest: format!("{:?}", block.header().digest),
-                    };
-
-                    let response = DoSomethingResponse {
-                        success: true, // or false based on result
-                        transaction_hash: Some(tx_hash.clone()),
-                        block_hash: Some(block_hash.clone()),
-                        block_header: Some(block_header),
-                        error: None,
-                    };
-                    log::info!("📤 OUTGOING RESPONSE:");
-                    log::info!("   Success: {}", response.success);
-                    log::info!("   Transaction Hash: {:?}", response.transaction_hash);
-                    log::info!("   Block Hash: {:?}", response.block_hash);
-                    log::info!("   Block Header: {:?}", response.block_header);
-                    log::info!("   Error: {:?}", response.error);
-
-                    Ok(Json(response))
-                }
+/// Handles the /tx/complete endpoint, the second step of external signing
+///
+/// Attaches the signature to the transaction prepared under the ticket and
+/// submits it like any other transaction, including receipts, `wait_for`
+/// and `?async=true`. The signature is verified first; a wrong signature
+/// leaves the ticket usable. Like relayed extrinsics, completed transactions
+/// are never re-signed, only resubmitted unchanged.
+///
+/// # Request Format
+/// POST /tx/complete
+/// ```json
+/// {
+///   "ticket": "0b6e...",
+///   "signature": "0x...",     // signature over signer_payload
+///   "scheme": "sr25519",      // optional: "sr25519" (default), "ed25519" or "ecdsa"
+///   "wait_for": "in_block"    // optional, defaults to "finalized"
+/// }
+/// ```
+///
+/// # Response Format
+/// Same as /do-something
+pub async fn complete_handler(
+    State(state): State<AppState>,
+    Query(options): Query<SubmitOptions>,
+    Json(payload): Json<CompleteRequest>,
+) -> Response {
+    log::info!("📥 INCOMING COMPLETE REQUEST: ticket {}", payload.ticket);
+
+    let (tx, call_info) = match state
+        .tickets
+        .complete(
+            &state.client,
+            &state.nonce_manager,
+            &payload.ticket,
+            &payload.signature,
+            payload.scheme,
+        )
+        .await
+    {
+        Ok(completed) => completed,
+        Err(e) => {
+            log::warn!("⚠️ Cannot complete ticket {}: {}", payload.ticket, e);
+            return Json(TransactionResponse::failure(e)).into_response();
+        }
+    };
+    state.receipts.record_signed(&tx, &call_info);
 
-                Err(e) => {
-                    // Transaction was submitted but failed during execution
-                    // Reset nonce so it can be reused for retry attempts
-                    state.nonce_manager.reset_nonce(&account_id, nonce).await;
-
-                    log::error!("❌ Transaction failed during finalization: {:?}", e);
-                    Ok(Json(DoSomethingResponse {
-                        success: false,
-                        transaction_hash: None,
-                        block_hash: None,
-                        block_header: None,
-                        error: Some(format!("Transaction failed: {:?}", e)),
-                    }))
-                }
-            }
+    submit_signed(&state, tx, None, payload.wait_for, &options).await
+}
+
+/// Query parameters for the /accounts/{id}/txs endpoint
+#[derive(Debug, Deserialize)]
+pub struct AccountTxsQuery {
+    /// Maximum number of receipts to return (defaults to 50, at most 500)
+    pub limit: Option<usize>,
+}
+
+/// Handles the /tx/{hash} endpoint for looking up a transaction receipt
+///
+/// Every transaction signed by this server has a receipt with its signer,
+/// nonce, call, lifecycle timestamps, inclusion block, events and error.
+///
+/// # Response Format
+/// ```json
+/// {
+///   "transaction_hash": "0x...",
+///   "signer": "5GrwvaEF...",
+///   "nonce": 12,
+///   "pallet": "Template",
+///   "call": "do_something",
+///   "args": { "something": 42 },
+///   "status": "finalized",
+///   "block_hash": "0x...",
+///   "block_number": 1234,
+///   "extrinsic_index": 2,
+///   "events": [ ... ],
+///   "error": null,
+///   "timestamps": { "signed_at": 1700000000, "submitted_at": 1700000000, "in_block_at": 1700000006, "finalized_at": 1700000018, "failed_at": null }
+/// }
+/// ```
+///
+/// # Returns
+/// The receipt, or 404 if no transaction with this hash was signed here
+pub async fn get_receipt_handler(
+    State(state): State<AppState>,
+    Path(hash): Path<String>,
+) -> Result<Json<Receipt>, StatusCode> {
+    match state.receipts.get(&hash) {
+        Some(receipt) => Ok(Json(receipt)),
+        None => {
+            log::warn!("⚠️ Receipt not found: {}", hash);
+            Err(StatusCode::NOT_FOUND)
+        }
+    }
+}
+
+/// Handles the /accounts/{id}/txs endpoint for listing an account's transactions
+///
+/// The account can be given as an SS58 address or 0x-prefixed hex. Receipts
+/// are returned newest nonce first.
+///
+/// # Returns
+/// JSON array of receipts, or 400 if the account ID is invalid
+pub async fn get_account_txs_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<AccountTxsQuery>,
+) -> Result<Json<Vec<Receipt>>, StatusCode> {
+    let account_id = match parse_account_id(&id) {
+        Ok(account_id) => account_id,
+        Err(e) => {
+            log::warn!("⚠️ {}", e);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+
+    let limit = query.limit.unwrap_or(50).min(500);
+    Ok(Json(state.receipts.list_for_account(&account_id, limit)))
+}
+
+/// Handles the /accounts/{id}/nonce endpoint for nonce diagnostics
+///
+/// Compares the nonce this service would hand out next with the node's
+/// pool (`system_accountNextIndex`) and the finalized `System.Account`
+/// nonce, and explains any disagreement. The account's submissions are not
+/// held up while the node is queried.
+///
+/// # Response Format
+/// ```json
+/// {
+///   "account": "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY",
+///   "source": "pool",
+///   "cached": 12,
+///   "pool": 11,
+///   "chain": 9,
+///   "pending": 2,
+///   "consistent": false,
+///   "issues": ["Nonces 11..12 were reserved here but are not in the pool ..."]
+/// }
+/// ```
+///
+/// # Returns
+/// The report, 400 if the account ID is invalid, or 500 if the node can't be queried
+pub async fn get_account_nonce_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<NonceReport>, StatusCode> {
+    let account_id = match parse_account_id(&id) {
+        Ok(account_id) => account_id,
+        Err(e) => {
+            log::warn!("⚠️ {}", e);
+            return Err(StatusCode::BAD_REQUEST);
         }
+    };
+
+    match state.nonce_manager.inspect(&account_id).await {
+        Ok(report) => Ok(Json(report)),
         Err(e) => {
-            // Failed to submit transaction to the mempool
-            // Reset nonce so it can be reused for retry attempts
-            state.nonce_manager.reset_nonce(&account_id, nonce).await;
+            log::error!("❌ Failed to inspect nonces of {}: {:?}", account_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
 
-            log::error!("❌ Failed to submit transaction: {:?}", e);
-            Ok(Json(DoSomethingResponse {
-                success: false,
-                transaction_hash: None,
-                block_hash: None,
-                block_header: None,
-                error: Some(format!("Failed to submit: {:?}", e)),
-            }))
+/// Parses an account ID given as an SS58 address or 0x-prefixed hex
+fn parse_account_id(id: &str) -> Result<AccountId32, String> {
+    match id.strip_prefix("0x") {
+        Some(hex) => {
+            let bytes = hex::decode(hex).map_err(|e| format!("Invalid account hex: {}", e))?;
+            let raw = <[u8; 32]>::try_from(bytes.as_slice())
+                .map_err(|_| format!("Account ID must be 32 bytes, got {}", bytes.len()))?;
+            Ok(AccountId32(raw))
         }
+        None => id
+            .parse()
+            .map_err(|e| format!("Invalid SS58 address '{}': {:?}", id, e)),
     }
 }
 
+/// Handles the /jobs/{id} endpoint for polling asynchronous submissions
+///
+/// Jobs are created by submission endpoints called with `?async=true`.
+/// The job reflects the latest TxProgress state seen by the background
+/// worker and carries the final result once it reaches `finalized` or `failed`.
+///
+/// # Response Format
+/// ```json
+/// {
+///   "job_id": "6f1c...",
+///   "status": "in_block",
+///   "transaction_hash": "0x...",
+///   "block_hash": "0x...",
+///   "result": null,
+///   "error": null,
+///   "created_at": 1700000000,
+///   "updated_at": 1700000006
+/// }
+/// ```
+///
+/// # Returns
+/// JSON job description, or 404 if the job is unknown (or was pruned)
+pub async fn get_job_handler(
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> Result<Json<Job>, StatusCode> {
+    state
+        .jobs
+        .get(&job_id)
+        .await
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
 /// Handles the /storage endpoint for querying blockchain state
 ///
 /// This endpoint allows clients to read the current value stored on the
@@ -570,26 +2692,22 @@ pub async fn get_latest_events(
 
     let mut event_list = Vec::new();
 
-    for event in events.iter() {
-        // event could be:
-        // - Ok(EventDetails) ← Success case
-        // - Err(DecodeError) ← Failure case
-        if let Ok(event) = event {
-            // Attempt to parse this event as a SomethingStored event from the template pallet
-            if let Ok(template_event) =
-                event.as_event::<chain_a::template::events::SomethingStored>()
-            {
-                if let Some(stored_event) = template_event {
-                    // Format the event data into a human-readable string
-                    event_list.push(format!(
-                        "SomethingStored: value={}, who={:?}",
-                        stored_event.something, stored_event.who
-                    ));
-                }
-            }
-            // Additional event types can be parsed here as needed
-            // Example: event.as_event::<chain_a::template::events::SomethingElse>()
+    // event could be:
+    // - Ok(EventDetails) ← Success case
+    // - Err(DecodeError) ← Failure case (skipped by flatten)
+    for event in events.iter().flatten() {
+        // Attempt to parse this event as a SomethingStored event from the template pallet
+        if let Ok(Some(stored_event)) =
+            event.as_event::<chain_a::template::events::SomethingStored>()
+        {
+            // Format the event data into a human-readable string
+            event_list.push(format!(
+                "SomethingStored: value={}, who={:?}",
+                stored_event.something, stored_event.who
+            ));
         }
+        // Additional event types can be parsed here as needed
+        // Example: event.as_event::<chain_a::template::events::SomethingElse>()
     }
 
     // In your blockchain runtime (e.g., pallets/template/src/lib.rs):
@@ -749,3 +2867,35 @@ pub async fn get_latest_events(
 //         signature: "John Smith [signature]"         // ← Seal
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::LocalSigner;
+
+    #[test]
+    fn managed_accounts_are_recognised() {
+        let treasury: Arc<dyn TxSigner> = Arc::new(LocalSigner::from_suri("//Treasury").unwrap());
+        let keystore = Keystore::empty()
+            .with_signers(vec![("treasury".to_string(), treasury.clone())])
+            .unwrap();
+        let users = UserAccounts::new(Arc::new(LocalSigner::from_suri("//Master").unwrap()));
+        let user = users.signer("42").unwrap();
+        let wallet = LocalSigner::from_suri("//Wallet").unwrap().account_id();
+
+        assert_eq!(
+            managed_signer_name(&keystore, Some(&users), &treasury.account_id()),
+            Some("treasury".to_string())
+        );
+        assert_eq!(
+            managed_signer_name(&keystore, Some(&users), &user.account_id()),
+            Some("user:42".to_string())
+        );
+        assert_eq!(managed_signer_name(&keystore, Some(&users), &wallet), None);
+        // Without user accounts, only aliases are recognised
+        assert_eq!(
+            managed_signer_name(&keystore, None, &user.account_id()),
+            None
+        );
+    }
+}
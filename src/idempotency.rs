@@ -0,0 +1,369 @@
+// src/idempotency.rs
+//
+// Idempotency keys for submission endpoints
+//
+// A client that retries a submission after a network blip can't tell whether
+// the first attempt went through, and a blind retry signs a second
+// transaction with a new nonce. Clients can send an `Idempotency-Key` header
+// instead: the first request with a key is processed normally and its
+// response is stored, and every repeat within the configured window gets
+// that stored response back (or a 409 while the first request is still
+// running) without signing anything.
+//
+// Keys are namespaced by method, path and API client (`X-Api-Client`), so
+// two clients, or two endpoints, never share a key. Within its namespace a
+// key is bound to a fingerprint of the request (query and body included), so
+// reusing a key for a different request is rejected.
+//
+// Request and response bodies are buffered in memory, the responses for the
+// whole window, so both are capped at `IDEMPOTENCY_MAX_BODY_BYTES`. Larger
+// requests are refused with 413; larger responses are passed on without being
+// stored.
+
+use axum::{
+    body::{to_bytes, Body, Bytes, HttpBody},
+    extract::{Request, State},
+    http::{header::CONTENT_TYPE, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use http_body_util::LengthLimitError;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use subxt::ext::sp_core::hashing::blake2_256;
+use tokio::sync::Mutex;
+
+use crate::handlers::AppState;
+use crate::policy::API_CLIENT_HEADER;
+
+/// Request header carrying the client's idempotency key
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Response header set when a stored response is replayed
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Longest accepted idempotency key
+const MAX_KEY_LENGTH: usize = 255;
+
+/// A buffered response that can be replayed
+#[derive(Clone)]
+struct StoredResponse {
+    status: StatusCode,
+    content_type: Option<HeaderValue>,
+    body: Bytes,
+}
+
+/// Processing state of a request with a given key
+enum EntryState {
+    /// The first request is still being handled
+    InProgress,
+    /// The first request finished with this response
+    Completed(StoredResponse),
+}
+
+/// A stored idempotency key
+struct Entry {
+    /// Fingerprint of the request that first used the key
+    fingerprint: String,
+    /// Whether that request is still running, and its response once done
+    state: EntryState,
+    /// When the key was first seen
+    created_at: Instant,
+}
+
+/// What to do with an incoming request carrying an idempotency key
+enum Lookup {
+    /// The key is new (or expired): process the request
+    New,
+    /// The key is already being processed
+    InProgress,
+    /// The key was processed before: replay its response
+    Completed(StoredResponse),
+    /// The key was used for a different request
+    Mismatch,
+}
+
+/// Thread-safe in-memory store of idempotency keys and their responses
+#[derive(Clone)]
+pub struct IdempotencyStore {
+    /// Entries keyed by namespaced idempotency key (see `namespaced_key`)
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+    /// How long a key is remembered after it is first used
+    window: Duration,
+}
+
+impl IdempotencyStore {
+    /// Creates an empty store that remembers keys for `window`
+    pub fn new(window: Duration) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            window,
+        }
+    }
+
+    /// Looks up a key, claiming it for this request if it is new
+    async fn begin(&self, key: &str, fingerprint: &str) -> Lookup {
+        let mut entries = self.entries.lock().await;
+
+        match entries.get(key) {
+            Some(entry) if entry.created_at.elapsed() < self.window => {
+                if entry.fingerprint != fingerprint {
+                    Lookup::Mismatch
+                } else {
+                    match &entry.state {
+                        EntryState::InProgress => Lookup::InProgress,
+                        EntryState::Completed(stored) => Lookup::Completed(stored.clone()),
+                    }
+                }
+            }
+            _ => {
+                entries.insert(
+                    key.to_string(),
+                    Entry {
+                        fingerprint: fingerprint.to_string(),
+                        state: EntryState::InProgress,
+                        created_at: Instant::now(),
+                    },
+                );
+                Lookup::New
+            }
+        }
+    }
+
+    /// Records the response for a key claimed with `begin`
+    async fn complete(&self, key: &str, response: StoredResponse) {
+        if let Some(entry) = self.entries.lock().await.get_mut(key) {
+            entry.state = EntryState::Completed(response);
+        }
+    }
+
+    /// Forgets a key so that the request can be retried with it
+    async fn abandon(&self, key: &str) {
+        self.entries.lock().await.remove(key);
+    }
+
+    /// Removes keys older than the window
+    ///
+    /// Keys that are still in progress are never removed.
+    pub async fn prune(&self) {
+        let mut entries = self.entries.lock().await;
+        let before = entries.len();
+        entries.retain(|_, entry| {
+            matches!(entry.state, EntryState::InProgress)
+                || entry.created_at.elapsed() < self.window
+        });
+        if entries.len() < before {
+            log::info!("🧹 Pruned {} idempotency key(s)", before - entries.len());
+        }
+    }
+}
+
+/// Middleware that makes submission endpoints idempotent
+///
+/// Requests without an `Idempotency-Key` header pass straight through. For
+/// requests with one:
+/// - New key: the request is processed and its response stored
+/// - Same key, same request, finished: the stored response is returned with
+///   an `Idempotent-Replayed: true` header
+/// - Same key, same request, still running: `409 Conflict`
+/// - Same key, different request: `422 Unprocessable Entity`
+/// - Body over `IDEMPOTENCY_MAX_BODY_BYTES`: `413 Payload Too Large`
+///
+/// Server errors (5xx) and responses over the body cap are not stored, so
+/// the request can be retried with the same key.
+pub async fn idempotency_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let key = match request.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        None => return next.run(request).await,
+        Some(value) => match value.to_str() {
+            Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LENGTH => key.to_string(),
+            _ => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Idempotency-Key must be 1 to {} visible ASCII characters",
+                        MAX_KEY_LENGTH
+                    ),
+                )
+            }
+        },
+    };
+
+    // The body has to be read to fingerprint it, then handed on unchanged
+    let max_body = state.config.idempotency_max_body;
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, max_body).await {
+        Ok(body) => body,
+        Err(e) if std::error::Error::source(&e).is_some_and(|e| e.is::<LengthLimitError>()) => {
+            return error_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!(
+                    "Requests with an Idempotency-Key may have at most {} bytes of body",
+                    max_body
+                ),
+            )
+        }
+        Err(e) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                format!("Failed to read body: {}", e),
+            )
+        }
+    };
+    let client = parts
+        .headers
+        .get(API_CLIENT_HEADER)
+        .and_then(|value| value.to_str().ok());
+    let slot = namespaced_key(parts.method.as_str(), parts.uri.path(), client, &key);
+    let fingerprint = fingerprint(parts.method.as_str(), &parts.uri.to_string(), body.as_ref());
+    let request = Request::from_parts(parts, Body::from(body));
+
+    let store = state.idempotency.clone();
+    match store.begin(&slot, &fingerprint).await {
+        Lookup::New => {}
+        Lookup::InProgress => {
+            log::warn!("🔁 Idempotency key {} is still in progress", key);
+            return error_response(
+                StatusCode::CONFLICT,
+                "A request with this Idempotency-Key is still being processed",
+            );
+        }
+        Lookup::Completed(stored) => {
+            log::info!("🔁 Replaying stored response for idempotency key {}", key);
+            return replay(stored);
+        }
+        Lookup::Mismatch => {
+            log::warn!("⚠️ Idempotency key {} reused for a different request", key);
+            return error_response(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "This Idempotency-Key was already used for a different request",
+            );
+        }
+    }
+
+    // Run the handler in its own task so the outcome is recorded even if the
+    // client disconnects; the retry will then find the stored response
+    let task = tokio::spawn(async move {
+        let response = next.run(request).await;
+
+        // Too large to keep around for the window: hand it on as it is
+        if response.body().size_hint().lower() > max_body as u64 {
+            store.abandon(&slot).await;
+            log::warn!(
+                "⚠️ Response for key {} exceeds {} bytes and is not stored",
+                key,
+                max_body
+            );
+            return response;
+        }
+        let (parts, body) = response.into_parts();
+
+        let body = match to_bytes(body, max_body).await {
+            Ok(body) => body,
+            Err(e) => {
+                store.abandon(&slot).await;
+                log::error!("❌ Failed to buffer response for key {}: {}", key, e);
+                return error_response(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to read response",
+                );
+            }
+        };
+
+        if parts.status.is_server_error() {
+            store.abandon(&slot).await;
+        } else {
+            store
+                .complete(
+                    &slot,
+                    StoredResponse {
+                        status: parts.status,
+                        content_type: parts.headers.get(CONTENT_TYPE).cloned(),
+                        body: body.clone(),
+                    },
+                )
+                .await;
+        }
+
+        Response::from_parts(parts, Body::from(body))
+    });
+
+    match task.await {
+        Ok(response) => response,
+        Err(e) => {
+            log::error!("❌ Idempotent request task failed: {:?}", e);
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Request failed")
+        }
+    }
+}
+
+/// Rebuilds a stored response
+fn replay(stored: StoredResponse) -> Response {
+    let mut response = (stored.status, stored.body).into_response();
+    if let Some(content_type) = stored.content_type {
+        response.headers_mut().insert(CONTENT_TYPE, content_type);
+    }
+    response
+        .headers_mut()
+        .insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+/// Scopes a client's idempotency key to the endpoint and the API client
+///
+/// Requests without an `X-Api-Client` header share one namespace per endpoint.
+fn namespaced_key(method: &str, path: &str, client: Option<&str>, key: &str) -> String {
+    format!(
+        "{} {} {}\n{}",
+        method,
+        path,
+        client.unwrap_or_default(),
+        key
+    )
+}
+
+/// Hashes the parts of a request that make it "the same request"
+fn fingerprint(method: &str, uri: &str, body: &[u8]) -> String {
+    let mut data = Vec::with_capacity(method.len() + uri.len() + body.len() + 2);
+    data.extend_from_slice(method.as_bytes());
+    data.push(b' ');
+    data.extend_from_slice(uri.as_bytes());
+    data.push(b'\n');
+    data.extend_from_slice(body);
+    hex::encode(blake2_256(&data))
+}
+
+/// Builds a JSON error response in the same shape as the handlers' failures
+fn error_response(status: StatusCode, error: impl Into<String>) -> Response {
+    let body = serde_json::json!({ "success": false, "error": error.into() });
+    (status, Json(body)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_namespaced_by_endpoint_and_client() {
+        let key = namespaced_key("POST", "/tx/batch", Some("payouts"), "k1");
+        assert_ne!(
+            key,
+            namespaced_key("POST", "/do-something", Some("payouts"), "k1")
+        );
+        assert_ne!(
+            key,
+            namespaced_key("POST", "/tx/batch", Some("billing"), "k1")
+        );
+        assert_ne!(key, namespaced_key("POST", "/tx/batch", None, "k1"));
+        assert_eq!(
+            key,
+            namespaced_key("POST", "/tx/batch", Some("payouts"), "k1")
+        );
+    }
+}
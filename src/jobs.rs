@@ -0,0 +1,229 @@
+// src/jobs.rs
+//
+// Asynchronous transaction jobs
+//
+// Waiting for finality can take tens of seconds, which is longer than many
+// HTTP clients are willing to wait. In async mode the submission endpoints
+// hand the signed transaction to a background task and return a job ID
+// straight away. The background task follows the TxProgress stream (retrying
+// like the synchronous path) and records every lifecycle change here, so
+// clients can poll GET /jobs/{id}.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::Mutex;
+
+use crate::handlers::{AppState, TransactionResponse, TxStage};
+use crate::retry::{submit_with_retry, Resigner};
+use crate::transaction::ManagedTransaction;
+
+/// Lifecycle state of an asynchronous transaction job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    /// The job was created and the transaction is about to be submitted
+    Pending,
+    /// The transaction was accepted into the node's transaction pool
+    Submitted,
+    /// The transaction was broadcast to other nodes
+    Broadcast,
+    /// The transaction was included in a best (not yet finalized) block
+    InBlock,
+    /// The transaction was included in a finalized block and succeeded
+    Finalized,
+    /// The transaction was rejected, dropped, or failed during execution
+    Failed,
+}
+
+impl JobStatus {
+    /// Returns true if the job will not change state anymore
+    pub fn is_terminal(self) -> bool {
+        matches!(self, JobStatus::Finalized | JobStatus::Failed)
+    }
+}
+
+/// A single asynchronous transaction job as reported by GET /jobs/{id}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    /// Unique job identifier returned by the submission endpoint
+    pub job_id: String,
+    /// Current lifecycle state
+    pub status: JobStatus,
+    /// The transaction hash (known as soon as the transaction is signed)
+    pub transaction_hash: String,
+    /// Hash of the block the transaction was last seen in
+    pub block_hash: Option<String>,
+    /// Final result once the job reaches a terminal state
+    pub result: Option<TransactionResponse>,
+    /// Error message if the job failed
+    pub error: Option<String>,
+    /// Unix timestamp (seconds) when the job was created
+    pub created_at: u64,
+    /// Unix timestamp (seconds) of the last state change
+    pub updated_at: u64,
+}
+
+/// Response returned with `202 Accepted` when a job is created
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobAccepted {
+    /// The job ID to poll
+    pub job_id: String,
+    /// The transaction hash of the submitted extrinsic
+    pub transaction_hash: String,
+    /// Relative URL where the job status can be polled
+    pub status_url: String,
+}
+
+/// Thread-safe in-memory store of asynchronous transaction jobs
+#[derive(Clone, Default)]
+pub struct JobStore {
+    /// Jobs keyed by job ID
+    jobs: Arc<Mutex<HashMap<String, Job>>>,
+}
+
+impl JobStore {
+    /// Creates an empty job store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a new pending job for a signed transaction and returns its ID
+    pub async fn create(&self, transaction_hash: String) -> String {
+        let job_id = uuid::Uuid::new_v4().to_string();
+        let now = unix_now();
+
+        let job = Job {
+            job_id: job_id.clone(),
+            status: JobStatus::Pending,
+            transaction_hash,
+            block_hash: None,
+            result: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+        self.jobs.lock().await.insert(job_id.clone(), job);
+
+        job_id
+    }
+
+    /// Returns a snapshot of a job, if it exists
+    pub async fn get(&self, job_id: &str) -> Option<Job> {
+        self.jobs.lock().await.get(job_id).cloned()
+    }
+
+    /// Applies an update to a job and bumps its `updated_at` timestamp
+    async fn update(&self, job_id: &str, apply: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.jobs.lock().await.get_mut(job_id) {
+            apply(job);
+            job.updated_at = unix_now();
+            log::info!("📋 Job {} -> {:?}", job_id, job.status);
+        }
+    }
+
+    /// Records a lifecycle change of a job's transaction
+    ///
+    /// # Arguments
+    /// * `job_id` - The job to update
+    /// * `status` - The new lifecycle state
+    /// * `transaction_hash` - The new transaction hash, if the transaction was signed again
+    /// * `block_hash` - The block the transaction was seen in, if any
+    pub async fn set_status(
+        &self,
+        job_id: &str,
+        status: JobStatus,
+        transaction_hash: Option<String>,
+        block_hash: Option<String>,
+    ) {
+        self.update(job_id, |job| {
+            job.status = status;
+            if let Some(hash) = transaction_hash {
+                job.transaction_hash = hash;
+            }
+            if block_hash.is_some() {
+                job.block_hash = block_hash;
+            }
+        })
+        .await;
+    }
+
+    /// Moves a job to its terminal state with the final response
+    pub async fn finish(&self, job_id: &str, response: TransactionResponse) {
+        self.update(job_id, |job| {
+            job.status = if response.success {
+                JobStatus::Finalized
+            } else {
+                JobStatus::Failed
+            };
+            if let Some(hash) = &response.transaction_hash {
+                job.transaction_hash = hash.clone();
+            }
+            if response.block_hash.is_some() {
+                job.block_hash = response.block_hash.clone();
+            }
+            job.error = response.error.clone();
+            job.result = Some(response);
+        })
+        .await;
+    }
+
+    /// Removes finished jobs that haven't changed for longer than `max_age`
+    ///
+    /// Jobs that are still in flight are never removed.
+    pub async fn prune(&self, max_age: Duration) {
+        let cutoff = unix_now().saturating_sub(max_age.as_secs());
+        let mut jobs = self.jobs.lock().await;
+        let before = jobs.len();
+        jobs.retain(|_, job| !job.status.is_terminal() || job.updated_at >= cutoff);
+        if jobs.len() < before {
+            log::info!("🧹 Pruned {} finished job(s)", before - jobs.len());
+        }
+    }
+}
+
+/// Submits a signed transaction and follows its progress until a terminal state
+///
+/// This runs in a background task spawned by the submission handlers. Every
+/// TxProgress event is mirrored into the job store. Failures are retried and
+/// nonces handed back exactly like on the synchronous path (see
+/// `retry::submit_with_retry`), so a job follows the transaction that
+/// eventually replaced the original one.
+///
+/// The transaction's receipt is updated along the way.
+///
+/// # Arguments
+/// * `state` - Shared application state (job store, nonce manager, receipts)
+/// * `job_id` - The job created for this transaction
+/// * `tx` - The signed transaction and the nonce it reserved
+/// * `resigner` - Used to sign the call again after nonce problems (`None` for client-signed transactions)
+pub async fn track_job(
+    state: AppState,
+    job_id: String,
+    tx: ManagedTransaction,
+    resigner: Option<Resigner>,
+) {
+    let response = submit_with_retry(
+        &state,
+        tx,
+        resigner.as_ref(),
+        TxStage::Finalized,
+        Some(&job_id),
+    )
+    .await;
+    if !response.success {
+        log::error!("❌ Job {}: {:?}", job_id, response.error);
+    }
+    state.jobs.finish(&job_id, response).await;
+}
+
+/// Current Unix time in seconds
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,502 @@
+// src/keystore.rs
+//
+// Managed keystore of named signers
+//
+// Secret keys don't belong in request bodies or logs. Instead, signers live in
+// a JSON keystore file and requests refer to them by alias. The file is loaded
+// once at startup and decrypted with a password from the environment, which
+// must not be empty; the decrypted keys only ever exist in memory.
+//
+// File layout:
+// - One PBKDF2-HMAC-SHA256 salt and iteration count for the whole file; the
+//   password is stretched into a 256-bit key once
+// - One entry per signer with its alias, public key and SS58 address in the
+//   clear (so the file can be inspected without the password) and its secret
+//   URI encrypted with ChaCha20-Poly1305 under a random nonce. The alias is
+//   authenticated as associated data, so entries can't be swapped around.
+//
+// Keys are added with `cargo run -- keystore add <alias>`, which reads the
+// secret URI from stdin (see `run_cli`). The URI may start with a scheme
+// prefix (`ed25519:`, `ecdsa:`); keys are sr25519 by default. Keys held by a remote signing
+// service are added to the same registry at startup, so requests use them
+// by alias just the same (see `signer`).
+
+use crate::signer::{LocalSigner, SignatureScheme, SignerBackend, TxSigner};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, io::BufRead, num::NonZeroU32, path::Path, sync::Arc};
+use subxt::utils::AccountId32;
+
+/// Current keystore file format version
+const KEYSTORE_VERSION: u32 = 1;
+
+/// PBKDF2 iterations for new keystore files
+const PBKDF2_ITERATIONS: u32 = 210_000;
+
+/// Length of the PBKDF2 salt for new keystore files
+const SALT_LEN: usize = 16;
+
+/// Public description of a managed signer, as listed by GET /keys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyInfo {
+    /// The name requests use to refer to the signer
+    pub alias: String,
+    /// The signer's public key, as 0x-prefixed hex
+    pub public_key: String,
+    /// The signer's SS58 address
+    pub address: String,
+    /// The key's signature scheme (sr25519 for files written before schemes were recorded)
+    #[serde(default)]
+    pub scheme: SignatureScheme,
+    /// Where the key lives
+    pub backend: SignerBackend,
+}
+
+impl KeyInfo {
+    /// Describes the signer stored under `alias`
+    fn new(alias: &str, signer: &dyn TxSigner) -> Self {
+        Self {
+            alias: alias.to_string(),
+            public_key: format!("0x{}", hex::encode(signer.public_key())),
+            address: signer.account_id().to_string(),
+            scheme: signer.scheme(),
+            backend: signer.backend(),
+        }
+    }
+}
+
+/// The keystore file as stored on disk
+#[derive(Debug, Serialize, Deserialize)]
+struct KeystoreFile {
+    /// File format version
+    version: u32,
+    /// How the encryption key is derived from the password
+    kdf: KdfParams,
+    /// One entry per signer
+    keys: Vec<KeyEntry>,
+}
+
+/// PBKDF2 parameters of a keystore file
+#[derive(Debug, Serialize, Deserialize)]
+struct KdfParams {
+    /// Number of PBKDF2-HMAC-SHA256 iterations
+    iterations: u32,
+    /// Salt as hex
+    salt: String,
+}
+
+/// One encrypted signer in a keystore file
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyEntry {
+    /// Public description of the signer
+    #[serde(flatten)]
+    info: KeyInfo,
+    /// ChaCha20-Poly1305 nonce as hex
+    nonce: String,
+    /// The encrypted secret URI followed by its authentication tag, as hex
+    ciphertext: String,
+}
+
+/// Named signers, decrypted from the keystore file or held remotely
+///
+/// Cheap to clone; the signers are shared and never change after startup.
+#[derive(Clone, Default)]
+pub struct Keystore {
+    /// Signers keyed by alias
+    signers: Arc<BTreeMap<String, Arc<dyn TxSigner>>>,
+}
+
+impl Keystore {
+    /// Creates a keystore without any signers
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Loads and decrypts every signer in a keystore file
+    ///
+    /// # Arguments
+    /// * `path` - The keystore file
+    /// * `password` - The password the file was encrypted with
+    ///
+    /// # Returns
+    /// * `Ok(Keystore)` - Every signer in the file
+    /// * `Err(String)` - If the file can't be read or the password is wrong
+    pub fn load(path: &Path, password: &str) -> Result<Self, String> {
+        let file = read_file(path)?;
+        let key = derive_key(&file.kdf, password)?;
+
+        let mut signers: BTreeMap<String, Arc<dyn TxSigner>> = BTreeMap::new();
+        for entry in &file.keys {
+            let signer = decrypt_entry(&key, entry)?;
+            signers.insert(entry.info.alias.clone(), Arc::new(signer));
+        }
+
+        Ok(Self {
+            signers: Arc::new(signers),
+        })
+    }
+
+    /// Adds signers held elsewhere (e.g. by a remote signing service)
+    ///
+    /// # Returns
+    /// * `Ok(Keystore)` - The keystore with the additional signers
+    /// * `Err(String)` - If an alias is already taken
+    pub fn with_signers(self, extra: Vec<(String, Arc<dyn TxSigner>)>) -> Result<Self, String> {
+        let mut signers = (*self.signers).clone();
+        for (alias, signer) in extra {
+            validate_alias(&alias)?;
+            if signers.contains_key(&alias) {
+                return Err(format!("Alias '{}' is defined more than once", alias));
+            }
+            signers.insert(alias, signer);
+        }
+        Ok(Self {
+            signers: Arc::new(signers),
+        })
+    }
+
+    /// Returns the signer stored under `alias`
+    pub fn get(&self, alias: &str) -> Option<Arc<dyn TxSigner>> {
+        self.signers.get(alias).cloned()
+    }
+
+    /// Returns the alias of the signer with the given account, if any
+    pub fn alias_of(&self, account_id: &AccountId32) -> Option<String> {
+        self.signers
+            .iter()
+            .find(|(_, signer)| signer.account_id() == *account_id)
+            .map(|(alias, _)| alias.clone())
+    }
+
+    /// Describes every signer, sorted by alias
+    pub fn list(&self) -> Vec<KeyInfo> {
+        self.signers
+            .iter()
+            .map(|(alias, signer)| KeyInfo::new(alias, signer.as_ref()))
+            .collect()
+    }
+
+    /// Number of signers in the keystore
+    pub fn len(&self) -> usize {
+        self.signers.len()
+    }
+}
+
+/// Handles `keystore` command line invocations
+///
+/// - `keystore add <alias>` reads a secret URI from stdin, encrypts it and
+///   adds it to the keystore file (creating the file if needed)
+/// - `keystore list` prints the public keys and addresses in the file
+///
+/// # Arguments
+/// * `args` - The arguments following `keystore`
+/// * `path` - The keystore file
+/// * `password` - The keystore password
+pub fn run_cli(args: &[String], path: &Path, password: &str) -> Result<(), String> {
+    match args {
+        [command, alias] if command == "add" => {
+            let mut suri = String::new();
+            std::io::stdin()
+                .lock()
+                .read_line(&mut suri)
+                .map_err(|e| format!("Failed to read the secret URI from stdin: {}", e))?;
+            let info = add_key(path, password, alias, suri.trim())?;
+            println!(
+                "Added {} ({}) to {}",
+                info.alias,
+                info.address,
+                path.display()
+            );
+            Ok(())
+        }
+        [command] if command == "list" => {
+            for info in read_file(path)?.keys.into_iter().map(|entry| entry.info) {
+                println!(
+                    "{}\t{:?}\t{}\t{}",
+                    info.alias, info.scheme, info.address, info.public_key
+                );
+            }
+            Ok(())
+        }
+        _ => Err("Usage: keystore add <alias> (secret URI on stdin) | keystore list".to_string()),
+    }
+}
+
+/// Encrypts a secret URI and adds it to a keystore file
+///
+/// A new file is created with a fresh salt. For an existing file, every
+/// entry is decrypted first, so a wrong password is caught before anything
+/// is written.
+fn add_key(path: &Path, password: &str, alias: &str, suri: &str) -> Result<KeyInfo, String> {
+    validate_alias(alias)?;
+    // The scheme goes into the clear part of the entry; only the bare
+    // secret URI is encrypted
+    let (scheme, suri) = SignatureScheme::split_suri(suri);
+    let signer = LocalSigner::with_scheme(suri, scheme)?;
+    let rng = SystemRandom::new();
+
+    let mut file = if path.exists() {
+        read_file(path)?
+    } else {
+        let mut salt = [0u8; SALT_LEN];
+        rng.fill(&mut salt)
+            .map_err(|_| "Failed to generate a salt".to_string())?;
+        KeystoreFile {
+            version: KEYSTORE_VERSION,
+            kdf: KdfParams {
+                iterations: PBKDF2_ITERATIONS,
+                salt: hex::encode(salt),
+            },
+            keys: Vec::new(),
+        }
+    };
+    if file.keys.iter().any(|entry| entry.info.alias == alias) {
+        return Err(format!("Alias '{}' already exists", alias));
+    }
+
+    let key = derive_key(&file.kdf, password)?;
+    for entry in &file.keys {
+        decrypt_entry(&key, entry)?;
+    }
+
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut nonce)
+        .map_err(|_| "Failed to generate a nonce".to_string())?;
+    let mut ciphertext = suri.as_bytes().to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(alias.as_bytes()),
+        &mut ciphertext,
+    )
+    .map_err(|_| "Failed to encrypt the secret URI".to_string())?;
+
+    let info = KeyInfo::new(alias, &signer);
+    file.keys.push(KeyEntry {
+        info: info.clone(),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    });
+
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize the keystore: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    Ok(info)
+}
+
+/// Reads and parses a keystore file
+fn read_file(path: &Path) -> Result<KeystoreFile, String> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read keystore {}: {}", path.display(), e))?;
+    let file: KeystoreFile = serde_json::from_str(&json)
+        .map_err(|e| format!("Invalid keystore {}: {}", path.display(), e))?;
+    if file.version != KEYSTORE_VERSION {
+        return Err(format!(
+            "Unsupported keystore version {} (expected {})",
+            file.version, KEYSTORE_VERSION
+        ));
+    }
+    Ok(file)
+}
+
+/// Stretches the password into the file's encryption key
+///
+/// An empty password is refused, so that an unset `KEYSTORE_PASSWORD` can
+/// neither encrypt new entries nor open a file encrypted without one.
+fn derive_key(kdf: &KdfParams, password: &str) -> Result<LessSafeKey, String> {
+    if password.is_empty() {
+        return Err("KEYSTORE_PASSWORD must be set to a non-empty password".to_string());
+    }
+    let iterations = NonZeroU32::new(kdf.iterations).ok_or("Invalid PBKDF2 iteration count")?;
+    let salt = hex::decode(&kdf.salt).map_err(|e| format!("Invalid keystore salt: {}", e))?;
+
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        &salt,
+        password.as_bytes(),
+        &mut key,
+    );
+    let key = UnboundKey::new(&CHACHA20_POLY1305, &key)
+        .map_err(|_| "Failed to create the keystore key".to_string())?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Decrypts one entry and checks it against its recorded public key
+fn decrypt_entry(key: &LessSafeKey, entry: &KeyEntry) -> Result<LocalSigner, String> {
+    let alias = &entry.info.alias;
+    let nonce = hex::decode(&entry.nonce)
+        .ok()
+        .and_then(|nonce| Nonce::try_assume_unique_for_key(&nonce).ok())
+        .ok_or_else(|| format!("Invalid nonce for '{}'", alias))?;
+    let mut ciphertext = hex::decode(&entry.ciphertext)
+        .map_err(|e| format!("Invalid ciphertext for '{}': {}", alias, e))?;
+
+    let plaintext = key
+        .open_in_place(nonce, Aad::from(alias.as_bytes()), &mut ciphertext)
+        .map_err(|_| {
+            format!(
+                "Cannot decrypt '{}': wrong password or corrupted entry",
+                alias
+            )
+        })?;
+    let suri =
+        std::str::from_utf8(plaintext).map_err(|_| format!("Corrupted entry for '{}'", alias))?;
+
+    let signer = LocalSigner::with_scheme(suri, entry.info.scheme)
+        .map_err(|e| format!("{} stored for '{}'", e, alias))?;
+    if format!("0x{}", hex::encode(signer.public_key())) != entry.info.public_key {
+        return Err(format!("Public key mismatch for '{}'", alias));
+    }
+    Ok(signer)
+}
+
+/// Checks that an alias can never be mistaken for a secret URI
+fn validate_alias(alias: &str) -> Result<(), String> {
+    let valid = !alias.is_empty()
+        && !alias.starts_with("0x")
+        && alias
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid alias '{}': use letters, digits, '-', '_' and '.' only",
+            alias
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const PASSWORD: &str = "correct horse battery staple";
+
+    /// A keystore file in the temp directory, removed when dropped
+    struct TempKeystore(PathBuf);
+
+    impl TempKeystore {
+        /// Creates an empty keystore file with few PBKDF2 iterations, so the
+        /// tests don't spend their time stretching passwords
+        fn new() -> Self {
+            let path = std::env::temp_dir().join(format!("keystore-{}.json", uuid::Uuid::new_v4()));
+            let file = KeystoreFile {
+                version: KEYSTORE_VERSION,
+                kdf: KdfParams {
+                    iterations: 1_000,
+                    salt: hex::encode([7u8; SALT_LEN]),
+                },
+                keys: Vec::new(),
+            };
+            write_file(&path, &file);
+            Self(path)
+        }
+
+        fn read(&self) -> KeystoreFile {
+            read_file(&self.0).unwrap()
+        }
+
+        fn write(&self, file: &KeystoreFile) {
+            write_file(&self.0, file);
+        }
+    }
+
+    impl Drop for TempKeystore {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn write_file(path: &Path, file: &KeystoreFile) {
+        std::fs::write(path, serde_json::to_string(file).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn keys_survive_an_encryption_round_trip() {
+        let keystore = TempKeystore::new();
+        let alice = add_key(&keystore.0, PASSWORD, "alice", "//Alice").unwrap();
+        let bob = add_key(&keystore.0, PASSWORD, "bob", "ed25519://Bob").unwrap();
+
+        let loaded = Keystore::load(&keystore.0, PASSWORD).unwrap();
+        assert_eq!(loaded.len(), 2);
+        let signer = loaded.get("alice").unwrap();
+        assert_eq!(signer.account_id().to_string(), alice.address);
+        assert_eq!(
+            loaded.get("bob").unwrap().scheme(),
+            SignatureScheme::Ed25519
+        );
+        assert_eq!(
+            loaded.alias_of(&signer.account_id()).as_deref(),
+            Some("alice")
+        );
+        assert_eq!(bob.scheme, SignatureScheme::Ed25519);
+        // Only the encrypted form of the secret URI is written
+        let json = std::fs::read_to_string(&keystore.0).unwrap();
+        assert!(!json.contains("//Alice"));
+    }
+
+    #[test]
+    fn a_wrong_or_empty_password_is_refused() {
+        let keystore = TempKeystore::new();
+        add_key(&keystore.0, PASSWORD, "alice", "//Alice").unwrap();
+
+        assert!(Keystore::load(&keystore.0, "wrong").is_err());
+        assert!(Keystore::load(&keystore.0, "").is_err());
+        // Adding checks the existing entries before writing anything
+        assert!(add_key(&keystore.0, "wrong", "bob", "//Bob").is_err());
+        assert!(add_key(&keystore.0, "", "bob", "//Bob").is_err());
+        assert_eq!(keystore.read().keys.len(), 1);
+    }
+
+    #[test]
+    fn tampered_entries_are_refused() {
+        let keystore = TempKeystore::new();
+        add_key(&keystore.0, PASSWORD, "alice", "//Alice").unwrap();
+        let original = keystore.read();
+
+        // A flipped ciphertext byte fails authentication
+        let mut file = keystore.read();
+        let mut ciphertext = hex::decode(&file.keys[0].ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        file.keys[0].ciphertext = hex::encode(ciphertext);
+        keystore.write(&file);
+        assert!(Keystore::load(&keystore.0, PASSWORD).is_err());
+
+        // So does a renamed entry, as the alias is authenticated
+        let mut file = original;
+        file.keys[0].info.alias = "treasury".to_string();
+        keystore.write(&file);
+        assert!(Keystore::load(&keystore.0, PASSWORD).is_err());
+    }
+
+    #[test]
+    fn unsupported_versions_are_refused() {
+        let keystore = TempKeystore::new();
+        let mut file = keystore.read();
+        file.version = KEYSTORE_VERSION + 1;
+        keystore.write(&file);
+
+        let err = Keystore::load(&keystore.0, PASSWORD).err().unwrap();
+        assert!(err.contains("Unsupported keystore version"));
+    }
+
+    #[test]
+    fn aliases_cannot_look_like_secrets() {
+        assert!(validate_alias("treasury").is_ok());
+        assert!(validate_alias("hot-wallet_2.eu").is_ok());
+
+        assert!(validate_alias("").is_err());
+        assert!(validate_alias("0xdeadbeef").is_err());
+        assert!(validate_alias("//Alice").is_err());
+        assert!(validate_alias("user:42").is_err());
+        assert!(validate_alias("two words").is_err());
+    }
+}
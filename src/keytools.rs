@@ -0,0 +1,245 @@
+// src/keytools.rs
+//
+// Key and address utilities for integrators
+//
+// - Generating key pairs for any supported scheme (dev mode only; the secret
+//   leaves the server in the response)
+// - Inspecting a secret URI: its public key, account and address
+// - Converting and validating addresses across SS58 prefixes
+//
+// Addresses are rendered with the chain's SS58 prefix unless a request asks
+// for another one. The chain's prefix is read from `system_properties` once
+// at startup.
+
+use serde::{Deserialize, Serialize};
+use subxt::{
+    backend::legacy::LegacyRpcMethods,
+    ext::sp_core::{
+        crypto::{AccountId32 as CryptoAccountId, Ss58AddressFormat, Ss58Codec},
+        sr25519, Pair as PairTrait,
+    },
+    utils::AccountId32,
+    SubstrateConfig,
+};
+
+use crate::signer::{LocalSigner, SignatureScheme, TxSigner};
+
+/// SS58 prefix used when the chain doesn't report one (generic Substrate)
+pub const DEFAULT_SS58_PREFIX: u16 = 42;
+
+/// Highest prefix the SS58 format can encode
+const MAX_SS58_PREFIX: u16 = 16_383;
+
+/// Request payload for the /util/keys/generate endpoint
+#[derive(Debug, Deserialize)]
+pub struct GenerateKeyRequest {
+    /// Scheme of the new key (defaults to sr25519)
+    #[serde(default)]
+    pub scheme: SignatureScheme,
+    /// SS58 prefix of the returned address (defaults to the chain's)
+    pub prefix: Option<u16>,
+}
+
+/// Request payload for the /util/keys/inspect endpoint
+#[derive(Debug, Deserialize)]
+pub struct InspectKeyRequest {
+    /// The secret URI, optionally prefixed with its scheme ("ed25519://Alice")
+    pub suri: String,
+    /// SS58 prefix of the returned address (defaults to the chain's)
+    pub prefix: Option<u16>,
+}
+
+/// Public details of a key, plus the secret phrase of a generated key
+#[derive(Debug, Serialize)]
+pub struct KeyDetails {
+    /// Secret URI of a newly generated key (with its scheme prefix); never
+    /// returned when inspecting
+    pub secret_uri: Option<String>,
+    /// The key's signature scheme
+    pub scheme: SignatureScheme,
+    /// The public key, as 0x-prefixed hex
+    pub public_key: String,
+    /// The account ID, as 0x-prefixed hex
+    pub account_id: String,
+    /// The account's SS58 address
+    pub address: String,
+    /// The SS58 prefix `address` is encoded with
+    pub prefix: u16,
+}
+
+impl KeyDetails {
+    /// Describes a key pair
+    fn new(signer: &LocalSigner, prefix: u16, secret_uri: Option<String>) -> Self {
+        let account_id = signer.account_id();
+        Self {
+            secret_uri,
+            scheme: signer.scheme(),
+            public_key: format!("0x{}", hex::encode(signer.public_key())),
+            account_id: format!("0x{}", hex::encode(account_id.0)),
+            address: to_ss58(&account_id, prefix),
+            prefix,
+        }
+    }
+}
+
+/// Query parameters of the /util/address/{address} endpoint
+#[derive(Debug, Deserialize)]
+pub struct AddressQuery {
+    /// SS58 prefix to convert to (defaults to the chain's)
+    pub prefix: Option<u16>,
+}
+
+/// An address checked and converted to another SS58 prefix
+#[derive(Debug, Serialize)]
+pub struct AddressInfo {
+    /// Whether the input is a valid SS58 address or 32 byte hex account ID
+    pub valid: bool,
+    /// The address as given
+    pub input: String,
+    /// SS58 prefix of the input (`None` for hex input)
+    pub input_prefix: Option<u16>,
+    /// The account ID, as 0x-prefixed hex
+    pub account_id: Option<String>,
+    /// The address encoded with `prefix`
+    pub address: Option<String>,
+    /// The SS58 prefix `address` is encoded with
+    pub prefix: u16,
+    /// Why the input is invalid
+    pub error: Option<String>,
+}
+
+impl AddressInfo {
+    /// Describes an input that isn't a valid address
+    pub fn invalid(input: &str, prefix: u16, error: impl Into<String>) -> Self {
+        Self {
+            valid: false,
+            input: input.to_string(),
+            input_prefix: None,
+            account_id: None,
+            address: None,
+            prefix,
+            error: Some(error.into()),
+        }
+    }
+
+    /// Describes a valid input
+    pub fn valid(input: &str, account_id: &AccountId32, prefix: u16) -> Self {
+        Self {
+            valid: true,
+            input: input.to_string(),
+            input_prefix: ss58_prefix_of(input),
+            account_id: Some(format!("0x{}", hex::encode(account_id.0))),
+            address: Some(to_ss58(account_id, prefix)),
+            prefix,
+            error: None,
+        }
+    }
+}
+
+/// Generates a new key pair with a fresh mnemonic
+pub fn generate_key(scheme: SignatureScheme, prefix: u16) -> Result<KeyDetails, String> {
+    // A mnemonic is valid for every scheme; the scheme decides how it is used
+    let (_, phrase, _) = sr25519::Pair::generate_with_phrase(None);
+    let signer = LocalSigner::with_scheme(&phrase, scheme)?;
+    let secret_uri = match scheme {
+        SignatureScheme::Sr25519 => phrase,
+        SignatureScheme::Ed25519 => format!("ed25519:{}", phrase),
+        SignatureScheme::Ecdsa => format!("ecdsa:{}", phrase),
+    };
+    Ok(KeyDetails::new(&signer, prefix, Some(secret_uri)))
+}
+
+/// Describes the key a secret URI resolves to, without echoing the secret
+pub fn inspect_key(suri: &str, prefix: u16) -> Result<KeyDetails, String> {
+    let signer = LocalSigner::from_suri(suri)?;
+    Ok(KeyDetails::new(&signer, prefix, None))
+}
+
+/// Checks that a prefix can be encoded in SS58
+pub fn validate_prefix(prefix: u16) -> Result<u16, String> {
+    if prefix <= MAX_SS58_PREFIX {
+        Ok(prefix)
+    } else {
+        Err(format!(
+            "SS58 prefix {} is out of range (0 to {})",
+            prefix, MAX_SS58_PREFIX
+        ))
+    }
+}
+
+/// Encodes an account as an SS58 address with the given prefix
+pub fn to_ss58(account_id: &AccountId32, prefix: u16) -> String {
+    CryptoAccountId::new(account_id.0).to_ss58check_with_version(Ss58AddressFormat::custom(prefix))
+}
+
+/// Reads the prefix of an SS58 address
+fn ss58_prefix_of(address: &str) -> Option<u16> {
+    CryptoAccountId::from_ss58check_with_version(address)
+        .ok()
+        .map(|(_, format)| u16::from(format))
+}
+
+/// Asks the node for the chain's SS58 prefix (`ss58Format` in `system_properties`)
+///
+/// Falls back to the generic Substrate prefix if the node doesn't say.
+pub async fn chain_ss58_prefix(rpc: &LegacyRpcMethods<SubstrateConfig>) -> u16 {
+    let reported = match rpc.system_properties().await {
+        Ok(properties) => properties
+            .get("ss58Format")
+            .and_then(|format| format.as_u64())
+            .and_then(|format| u16::try_from(format).ok()),
+        Err(e) => {
+            log::warn!("⚠️ Failed to read system_properties: {:?}", e);
+            None
+        }
+    };
+    reported.unwrap_or_else(|| {
+        log::warn!(
+            "⚠️ Chain reports no SS58 prefix, using {}",
+            DEFAULT_SS58_PREFIX
+        );
+        DEFAULT_SS58_PREFIX
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+    fn alice() -> AccountId32 {
+        LocalSigner::from_suri("//Alice").unwrap().account_id()
+    }
+
+    #[test]
+    fn prefixes_are_limited_to_the_ss58_range() {
+        for prefix in [0, DEFAULT_SS58_PREFIX, 63, 64, MAX_SS58_PREFIX] {
+            assert_eq!(validate_prefix(prefix), Ok(prefix));
+        }
+        assert!(validate_prefix(MAX_SS58_PREFIX + 1).is_err());
+        assert!(validate_prefix(u16::MAX).is_err());
+    }
+
+    #[test]
+    fn addresses_round_trip_through_every_prefix() {
+        assert_eq!(to_ss58(&alice(), DEFAULT_SS58_PREFIX), ALICE);
+        assert_eq!(ss58_prefix_of(ALICE), Some(DEFAULT_SS58_PREFIX));
+
+        // One- and two-byte prefixes alike
+        for prefix in [0, 2, 63, 64, 1_000, MAX_SS58_PREFIX] {
+            let address = to_ss58(&alice(), prefix);
+            assert_eq!(ss58_prefix_of(&address), Some(prefix), "{}", address);
+            let (account, _) = CryptoAccountId::from_ss58check_with_version(&address).unwrap();
+            assert_eq!(AccountId32::from(<[u8; 32]>::from(account)), alice());
+        }
+    }
+
+    #[test]
+    fn invalid_addresses_have_no_prefix() {
+        assert_eq!(ss58_prefix_of(""), None);
+        assert_eq!(ss58_prefix_of("not an address"), None);
+        // A changed character breaks the checksum
+        assert_eq!(ss58_prefix_of(&ALICE.replace('G', "H")), None);
+    }
+}
@@ -1,20 +1,62 @@
 // src/main.rs
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
-use log;
-use subxt::{OnlineClient, SubstrateConfig};
+use subxt::{
+    backend::{legacy::LegacyRpcMethods, rpc::RpcClient},
+    OnlineClient, SubstrateConfig,
+};
 use tower_http::cors::CorsLayer;
 
 // Import our modules
+mod batch;
+mod call_builder;
+mod chain;
+mod config;
+mod events;
+mod gaps;
 mod handlers;
+mod idempotency;
+mod jobs;
+mod keystore;
+mod keytools;
+mod metrics;
+#[cfg(test)]
+mod mock_signer;
 mod nonce_manager;
+mod policy;
+mod receipts;
+mod relay;
+mod replace;
+mod retry;
+mod session;
+mod signer;
+mod tickets;
 mod transaction;
+mod users;
+use chain::ChainNonces;
+use config::Config;
 use handlers::{
-    do_something_handler, get_latest_events, get_storage_handler, health_check, AppState,
+    address_handler, batch_handler, cancel_handler, complete_handler, create_user_handler,
+    do_something_handler, dry_run_handler, estimate_fee_handler, generate_key_handler,
+    get_account_nonce_handler, get_account_txs_handler, get_job_handler, get_key_handler,
+    get_latest_events, get_receipt_handler, get_storage_handler, get_user_handler, health_check,
+    inspect_key_handler, list_keys_handler, metrics_handler, prepare_handler, replace_handler,
+    rotate_keys_handler, submit_call_handler, submit_raw_handler, AppState,
 };
+use idempotency::{idempotency_middleware, IdempotencyStore};
+use jobs::JobStore;
+use keystore::Keystore;
+use metrics::Metrics;
 use nonce_manager::NonceManager;
+use policy::{Policy, NO_POLICY};
+use receipts::ReceiptStore;
+use signer::{LocalSigner, RemoteSigner, TxSigner};
+use std::sync::Arc;
+use tickets::TicketStore;
+use users::UserAccounts;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -26,12 +68,131 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     env_logger::init();
 
+    // Load configuration from the environment
+    let config = Config::from_env();
+    let keystore_path = std::path::Path::new(&config.keystore_path);
+    // Unset counts as empty, which the keystore refuses to encrypt or decrypt with
+    let keystore_password = std::env::var("KEYSTORE_PASSWORD").unwrap_or_default();
+
+    // `keystore add <alias>` / `keystore list` manage the keystore file and exit
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("keystore") {
+        keystore::run_cli(&args[1..], keystore_path, &keystore_password)?;
+        return Ok(());
+    }
+
+    config.validate()?;
+    log::info!("⚙️ Configuration: {:?}", config);
+
+    // Managed signers are decrypted once; requests refer to them by alias
+    let keystore = if keystore_path.exists() {
+        let keystore = Keystore::load(keystore_path, &keystore_password)?;
+        log::info!(
+            "🔑 Loaded {} signer(s) from {}",
+            keystore.len(),
+            config.keystore_path
+        );
+        keystore
+    } else {
+        log::warn!(
+            "⚠️ No keystore at {}, no managed signers available",
+            config.keystore_path
+        );
+        Keystore::empty()
+    };
+    // Keys held by a remote signing service join the keystore's signers
+    let keystore = match &config.remote_signer_url {
+        Some(url) => {
+            let remote = RemoteSigner::discover(url).await?;
+            log::info!(
+                "🔑 Found {} signer(s) at remote signer {}",
+                remote.len(),
+                url
+            );
+            keystore.with_signers(remote)?
+        }
+        None => keystore,
+    };
+
+    // End users' accounts are derived from one master signer on demand
+    // The setting is never logged: in dev mode it may be a secret URI
+    let users = match config.user_master_signer.as_deref() {
+        Some(master) => {
+            let master: Arc<dyn TxSigner> = match keystore.get(master) {
+                Some(signer) => signer,
+                None if config.dev_mode => Arc::new(LocalSigner::from_suri(master)?),
+                None => {
+                    return Err(
+                        "USER_MASTER_SIGNER is not a keystore alias (secret URIs are only accepted in dev mode)"
+                            .into(),
+                    )
+                }
+            };
+            if let Err(e) = master.derive(&[]) {
+                return Err(
+                    format!("USER_MASTER_SIGNER cannot derive user accounts: {}", e).into(),
+                );
+            }
+            log::info!("👥 User accounts are derived from {}", master.account_id());
+            Some(UserAccounts::new(master))
+        }
+        None => {
+            log::info!("👥 No USER_MASTER_SIGNER, user accounts are disabled");
+            None
+        }
+    };
+
+    // Which signer may submit what, for whom; checked before nonces are reserved
+    let policy_path = std::path::Path::new(&config.policy_path);
+    let policy = if config.policy_path == NO_POLICY {
+        log::warn!("⚠️ POLICY_PATH is none, every signer may submit any call");
+        Policy::allow_all()
+    } else if policy_path.exists() {
+        let policy = Policy::load(policy_path)?;
+        log::info!(
+            "🛡️ Loaded policy for {} signer(s) from {}",
+            policy.len(),
+            config.policy_path
+        );
+        policy
+    } else if config.dev_mode {
+        log::warn!(
+            "⚠️ No policy at {}, every signer may submit any call (dev mode)",
+            config.policy_path
+        );
+        Policy::allow_all()
+    } else {
+        return Err(format!(
+            "No policy at {} (set POLICY_PATH=none to allow every request)",
+            config.policy_path
+        )
+        .into());
+    };
+    if config.dev_mode {
+        log::warn!("⚠️ DEV_MODE is on: raw secret URIs are accepted as signers");
+    }
+
     // Connect to Chain A
-    let client = OnlineClient::<SubstrateConfig>::from_url("ws://localhost:9944").await?;
+    // The raw RPC client is shared by subxt and by the legacy RPC methods
+    // we call directly (dry runs etc.), so both use a single connection
+    let rpc_client = RpcClient::from_url("ws://localhost:9944").await?;
+    let client = OnlineClient::<SubstrateConfig>::from_rpc_client(rpc_client.clone()).await?;
+    let rpc = LegacyRpcMethods::<SubstrateConfig>::new(rpc_client);
     log::info!("Connected to Chain A at ws://localhost:9944");
 
+    // Addresses in utility responses default to the chain's own prefix
+    let ss58_prefix = keytools::chain_ss58_prefix(&rpc).await;
+    log::info!("🏷️ Chain SS58 prefix: {}", ss58_prefix);
+
     // Create nonce manager
-    let nonce_manager = NonceManager::new(client.clone());
+    let metrics = Metrics::new();
+    let nonce_manager = NonceManager::new(
+        Arc::new(ChainNonces::new(client.clone(), rpc.clone())),
+        config.nonce_source,
+        metrics.clone(),
+        config.nonce_gap_timeout,
+    );
+    log::info!("🔢 Nonce source: {:?}", config.nonce_source);
 
     // Start background sync task
     let sync_manager = nonce_manager.clone();
@@ -65,14 +226,110 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
+    // Finished async jobs are kept for an hour so clients can still poll them
+    let jobs = JobStore::new();
+    let prune_jobs = jobs.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            prune_jobs
+                .prune(tokio::time::Duration::from_secs(60 * 60))
+                .await;
+        }
+    });
+
+    // Idempotency keys are remembered for the configured window
+    let idempotency = IdempotencyStore::new(config.idempotency_window);
+    let prune_idempotency = idempotency.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            prune_idempotency.prune().await;
+        }
+    });
+
+    // Expired signing tickets give their nonce back, so they are pruned often
+    let tickets = TicketStore::new(config.ticket_ttl, config.max_tickets_per_account);
+    let prune_tickets = tickets.clone();
+    let prune_nonce_manager = nonce_manager.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(10));
+        loop {
+            interval.tick().await;
+            prune_tickets.prune(&prune_nonce_manager).await;
+        }
+    });
+
+    // Receipts of every signed transaction survive restarts
+    let receipts = ReceiptStore::open(&config.receipts_db_path)?;
+    log::info!("🧾 Receipt store opened at {}", config.receipts_db_path);
+
     let state = AppState {
         client,
         nonce_manager,
+        jobs,
+        rpc,
+        config: Arc::new(config),
+        idempotency,
+        receipts,
+        tickets,
+        metrics,
+        keystore,
+        policy,
+        users,
+        ss58_prefix,
     };
 
+    // Reserved nonces that never reach the pool are filled or reported
+    log::info!(
+        "🕳️ Nonce gaps older than {:?} are handled with action {:?}",
+        state.config.nonce_gap_timeout,
+        state.config.nonce_gap_action
+    );
+    let gap_state = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(15));
+        loop {
+            interval.tick().await;
+            gaps::check_nonce_gaps(&gap_state).await;
+        }
+    });
+
+    // Endpoints that sign and submit transactions honour the Idempotency-Key header
+    let submissions = Router::new()
+        .route("/do-something", post(do_something_handler))
+        .route("/tx/batch", post(batch_handler))
+        .route("/tx/submit-raw", post(submit_raw_handler))
+        .route("/tx/prepare", post(prepare_handler))
+        .route("/tx/complete", post(complete_handler))
+        .route("/tx/:hash/replace", post(replace_handler))
+        .route("/tx/:hash/cancel", post(cancel_handler))
+        .route("/tx/:pallet/:call", post(submit_call_handler))
+        .route("/validators/:signer/rotate-keys", post(rotate_keys_handler))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            idempotency_middleware,
+        ));
+
     let app = Router::new()
         .route("/health", get(health_check))
-        .route("/do-something", post(do_something_handler))
+        .route("/metrics", get(metrics_handler))
+        .route("/keys", get(list_keys_handler))
+        .route("/keys/:alias", get(get_key_handler))
+        .route("/users", post(create_user_handler))
+        .route("/users/:user_id", get(get_user_handler))
+        .route("/util/keys/generate", post(generate_key_handler))
+        .route("/util/keys/inspect", post(inspect_key_handler))
+        .route("/util/address/:address", get(address_handler))
+        .route("/tx/dry-run", post(dry_run_handler))
+        .route("/tx/estimate-fee", post(estimate_fee_handler))
+        .merge(submissions)
+        .route("/tx/:hash", get(get_receipt_handler))
+        .route("/accounts/:id/txs", get(get_account_txs_handler))
+        .route("/accounts/:id/nonce", get(get_account_nonce_handler))
+        .route("/jobs/:id", get(get_job_handler))
         .route("/get-storage", get(get_storage_handler))
         .route("/latest-events", get(get_latest_events))
         .layer(CorsLayer::permissive())
@@ -0,0 +1,113 @@
+// src/metrics.rs
+//
+// Operational counters
+//
+// Some things the backend does are worth watching from the outside because
+// they hint at trouble before requests start failing, e.g. signatures that
+// had to fall back to a nonce the nonce manager didn't hand out, nonces the
+// cache, the pool and the chain disagree about, or nonce gaps. They are
+// counted here and exposed in the Prometheus text format on GET /metrics.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use crate::gaps::GapAction;
+use crate::transaction::SigningPath;
+
+/// The counters behind `Metrics`
+#[derive(Default)]
+struct Counters {
+    /// Transactions signed with the reserved nonce
+    signed_explicit: AtomicU64,
+    /// Transactions signed with the node's next nonce after explicit signing failed
+    signed_fallback: AtomicU64,
+    /// Times the cached, pool and chain nonces of an account disagreed
+    nonce_disagreements: AtomicU64,
+    /// Nonce gaps filled with a `System.remark`
+    gaps_filled: AtomicU64,
+    /// Nonce gaps only reported (by configuration, or because filling failed)
+    gaps_alerted: AtomicU64,
+}
+
+/// Thread-safe, cheaply clonable set of counters
+#[derive(Clone, Default)]
+pub struct Metrics {
+    counters: Arc<Counters>,
+}
+
+impl Metrics {
+    /// Creates a set of counters starting at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts a transaction signed by this service
+    pub fn record_signing(&self, path: SigningPath) {
+        let counter = match path {
+            SigningPath::Explicit => &self.counters.signed_explicit,
+            SigningPath::Fallback => &self.counters.signed_fallback,
+            SigningPath::External => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a disagreement between the cached, pool and chain nonces of an account
+    pub fn record_nonce_disagreement(&self) {
+        self.counters
+            .nonce_disagreements
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a nonce gap found by the gap check, by what was done about it
+    pub fn record_nonce_gap(&self, action: GapAction) {
+        let counter = match action {
+            GapAction::Fill => &self.counters.gaps_filled,
+            GapAction::Alert => &self.counters.gaps_alerted,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Renders every counter in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        out.push_str(
+            "# HELP tx_signed_total Transactions signed by this service, by signing path\n",
+        );
+        out.push_str("# TYPE tx_signed_total counter\n");
+        for (path, counter) in [
+            ("explicit", &self.counters.signed_explicit),
+            ("fallback", &self.counters.signed_fallback),
+        ] {
+            out.push_str(&format!(
+                "tx_signed_total{{path=\"{}\"}} {}\n",
+                path,
+                counter.load(Ordering::Relaxed)
+            ));
+        }
+        out.push_str(
+            "# HELP nonce_disagreements_total Times the cached, pool and chain nonces of an account disagreed\n",
+        );
+        out.push_str("# TYPE nonce_disagreements_total counter\n");
+        out.push_str(&format!(
+            "nonce_disagreements_total {}\n",
+            self.counters.nonce_disagreements.load(Ordering::Relaxed)
+        ));
+        out.push_str(
+            "# HELP nonce_gaps_total Nonce gaps found by the gap check, by action taken\n",
+        );
+        out.push_str("# TYPE nonce_gaps_total counter\n");
+        for (action, counter) in [
+            ("fill", &self.counters.gaps_filled),
+            ("alert", &self.counters.gaps_alerted),
+        ] {
+            out.push_str(&format!(
+                "nonce_gaps_total{{action=\"{}\"}} {}\n",
+                action,
+                counter.load(Ordering::Relaxed)
+            ));
+        }
+        out
+    }
+}
@@ -0,0 +1,99 @@
+// src/mock_signer.rs
+//
+// A stand-in for a remote signing service (HSM / KMS gateway), for tests
+//
+// Serves the API `RemoteSigner` expects (see `signer`) with sr25519 keys held
+// in memory. Only compiled for tests, where the signer tests serve `router`
+// on a random port and sign through `RemoteSigner` against it, and into the
+// `mock_signer` example, which serves it on a fixed port:
+//
+//   MOCK_SIGNER_KEYS="remote-alice=//Alice,remote-bob=//Bob" cargo run --example mock_signer
+//   DEV_MODE=true REMOTE_SIGNER_URL=http://127.0.0.1:3002 cargo run
+//
+// Never use it with real keys.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc};
+use subxt::ext::sp_core::{sr25519::Pair, Pair as PairTrait};
+
+/// Key pairs keyed by alias
+pub type Keys = Arc<BTreeMap<String, Pair>>;
+
+/// One entry of GET /keys
+#[derive(Serialize)]
+struct KeyEntry {
+    alias: String,
+    public_key: String,
+    scheme: &'static str,
+}
+
+/// Body of POST /sign
+#[derive(Deserialize)]
+struct SignRequest {
+    alias: String,
+    payload: String,
+}
+
+/// Response of POST /sign
+#[derive(Serialize)]
+struct SignResponse {
+    signature: String,
+}
+
+async fn list_keys(State(keys): State<Keys>) -> Json<Vec<KeyEntry>> {
+    Json(
+        keys.iter()
+            .map(|(alias, pair)| KeyEntry {
+                alias: alias.clone(),
+                public_key: format!("0x{}", hex::encode(pair.public().0)),
+                scheme: "sr25519",
+            })
+            .collect(),
+    )
+}
+
+async fn sign(
+    State(keys): State<Keys>,
+    Json(request): Json<SignRequest>,
+) -> Result<Json<SignResponse>, StatusCode> {
+    let pair = keys.get(&request.alias).ok_or(StatusCode::NOT_FOUND)?;
+    let payload = hex::decode(request.payload.trim_start_matches("0x"))
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    println!(
+        "✍️ Signing {} byte payload with '{}'",
+        payload.len(),
+        request.alias
+    );
+    Ok(Json(SignResponse {
+        signature: format!("0x{}", hex::encode(pair.sign(&payload).0)),
+    }))
+}
+
+/// Parses `alias=suri` pairs separated by commas into key pairs
+pub fn parse_keys(spec: &str) -> Result<Keys, String> {
+    let mut keys = BTreeMap::new();
+    for entry in spec.split(',').filter(|entry| !entry.is_empty()) {
+        let (alias, suri) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("Expected alias=suri, got '{}'", entry))?;
+        let pair = Pair::from_string(suri, None)
+            .map_err(|_| format!("Invalid secret URI for '{}'", alias))?;
+        keys.insert(alias.to_string(), pair);
+    }
+    Ok(Arc::new(keys))
+}
+
+/// The signing service's routes, serving `keys`
+pub fn router(keys: Keys) -> Router {
+    Router::new()
+        .route("/keys", get(list_keys))
+        .route("/sign", post(sign))
+        .with_state(keys)
+}
@@ -1,33 +1,358 @@
-use std::{collections::HashMap, sync::Arc};
-use subxt::{utils::AccountId32, OnlineClient, SubstrateConfig};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+    sync::{Arc, Mutex as StdMutex, Weak},
+    time::{Duration, Instant},
+};
+use subxt::utils::AccountId32;
 use tokio::sync::Mutex;
 
+use crate::chain::NonceQueries;
+use crate::metrics::Metrics;
+use crate::signer::TxSigner;
+
+/// Number of shards the account map is split into
+///
+/// Each shard's lock is only held while looking up an account's entry, never
+/// across an await, so a handful of shards is plenty.
+const SHARD_COUNT: usize = 16;
+
+/// What the nonce manager knows about one account
+#[derive(Default)]
+struct AccountNonces {
+    /// The next expected nonce, `None` until it is first read from the chain
+    next: Option<u64>,
+    /// Reserved nonces not yet seen in the pool or on chain
+    reserved: BTreeMap<u64, Reservation>,
+    /// The signer that last reserved a nonce for the account, for filling gaps
+    signer: Option<Arc<dyn TxSigner>>,
+}
+
+/// A reserved nonce the nonce source hasn't seen yet
+struct Reservation {
+    /// When a missing nonce counts as a gap, once its owner is gone
+    deadline: Instant,
+    /// The lease of whoever is going to submit the nonce
+    owner: Weak<()>,
+}
+
+impl Reservation {
+    /// A nonce that went missing without anyone having reserved it
+    fn unowned(deadline: Instant) -> Self {
+        Self {
+            deadline,
+            owner: Weak::new(),
+        }
+    }
+
+    /// Whether the nonce is still in the hands of its owner
+    ///
+    /// A retry waiting out its backoff, an open batch or an unexpired ticket
+    /// can still submit the nonce, however long it has been missing.
+    fn in_flight(&self) -> bool {
+        self.owner.strong_count() > 0
+    }
+}
+
+/// A reservation of one or more consecutive nonces
+///
+/// Whoever is going to submit the reserved nonces (a request, a retry loop, a
+/// batch or a prepared ticket) keeps the lease until it is done with them.
+/// While the lease is alive the gap check leaves its nonces alone; once it is
+/// dropped, a nonce still missing from the pool is a gap after its deadline.
+pub struct NonceLease {
+    /// The first reserved nonce
+    first_nonce: u64,
+    /// Kept alive by the lease; reservations hold a weak reference to it
+    owner: Arc<()>,
+}
+
+impl NonceLease {
+    /// The first reserved nonce (the only one for single reservations)
+    pub fn nonce(&self) -> u64 {
+        self.first_nonce
+    }
+}
+
+/// An account's nonce state
+///
+/// The async mutex is held for the whole read-modify-write of a reservation,
+/// including the RPC round trip, so an account's nonces are handed out
+/// strictly in order.
+type AccountSlot = Arc<Mutex<AccountNonces>>;
+
+/// One part of the account map, keyed by account ID
+type Shard = StdMutex<HashMap<[u8; 32], AccountSlot>>;
+
+/// A nonce that was reserved but never reached the pool
+///
+/// Every later transaction of the account waits in the pool's future queue
+/// until something is submitted at this nonce.
+pub struct NonceGap {
+    /// The account whose transactions are stuck
+    pub account_id: AccountId32,
+    /// The missing nonce
+    pub nonce: u64,
+    /// How many reserved nonces wait behind the gap (`next - nonce`)
+    pub blocked: u64,
+    /// The signer that last reserved a nonce for the account, if it was a
+    /// signer of this service
+    pub signer: Option<Arc<dyn TxSigner>>,
+}
+
+/// Where the nonce manager reads an account's next nonce from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NonceSource {
+    /// `system_accountNextIndex`: the chain's nonce plus the account's
+    /// transactions already waiting in the node's pool, including those
+    /// submitted by other processes
+    #[default]
+    Pool,
+    /// `System.Account` at the latest finalized block; ignores the pool
+    Finalized,
+}
+
+impl FromStr for NonceSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "pool" => Ok(Self::Pool),
+            "finalized" => Ok(Self::Finalized),
+            other => Err(format!("Unknown nonce source '{}'", other)),
+        }
+    }
+}
+
+/// What the cache, the pool and the chain say about one account's next nonce
+///
+/// Returned by the /accounts/{id}/nonce endpoint and logged by the
+/// periodic sync whenever the three disagree.
+#[derive(Debug, Clone, Serialize)]
+pub struct NonceReport {
+    /// The account's SS58 address
+    pub account: String,
+    /// The configured nonce source
+    pub source: NonceSource,
+    /// The next nonce this service would hand out, if the account is cached
+    pub cached: Option<u64>,
+    /// The next nonce according to the node's pool (`system_accountNextIndex`)
+    pub pool: u64,
+    /// The next nonce according to `System.Account` at the finalized block
+    pub chain: u64,
+    /// Transactions in the pool or in unfinalized blocks (`pool - chain`)
+    pub pending: u64,
+    /// Whether the cache agrees with the pool and the pool isn't behind the chain
+    pub consistent: bool,
+    /// What the disagreements suggest, if any
+    pub issues: Vec<String>,
+}
+
+impl NonceReport {
+    /// Compares the three nonces and explains any disagreement
+    fn new(
+        account_id: &AccountId32,
+        source: NonceSource,
+        cached: Option<u64>,
+        pool: u64,
+        chain: u64,
+    ) -> Self {
+        let mut issues = Vec::new();
+        if pool < chain {
+            issues.push(format!(
+                "The pool expects nonce {} but the chain is already at {}",
+                pool, chain
+            ));
+        }
+        match cached {
+            Some(cached) if cached < pool => issues.push(format!(
+                "The pool expects nonce {} but the cache would hand out {}: \
+                 transactions were submitted outside this service",
+                pool, cached
+            )),
+            Some(cached) if cached > pool => issues.push(format!(
+                "Nonces {}..{} were reserved here but are not in the pool \
+                 (still being signed and submitted, or lost)",
+                pool, cached
+            )),
+            _ => {}
+        }
+        Self {
+            account: account_id.to_string(),
+            source,
+            cached,
+            pool,
+            chain,
+            pending: pool.saturating_sub(chain),
+            consistent: issues.is_empty(),
+            issues,
+        }
+    }
+
+    /// The next nonce according to a source
+    fn nonce_from(&self, source: NonceSource) -> u64 {
+        match source {
+            NonceSource::Pool => self.pool,
+            NonceSource::Finalized => self.chain,
+        }
+    }
+}
+
 /// Production-grade nonce manager for blockchain transaction management
 ///
 /// This manager provides thread-safe nonce caching and synchronization
 /// with the blockchain to prevent nonce conflicts in concurrent transactions.
+///
+/// Every account has its own lock. Reservations for the same account wait for
+/// each other; reservations for different accounts proceed in parallel.
 #[derive(Clone)]
 pub struct NonceManager {
-    /// Thread-safe cache mapping account keys to their next expected nonce
-    nonce_cache: Arc<Mutex<HashMap<[u8; 32], u64>>>,
-    /// Blockchain client for querying current nonces
-    client: OnlineClient<SubstrateConfig>,
+    /// Account keys mapped to their nonce slot, split by the key's first byte
+    shards: Arc<Vec<Shard>>,
+    /// The node's view of each account's nonce
+    chain: Arc<dyn NonceQueries>,
+    /// Where the next nonce is read from
+    source: NonceSource,
+    /// Counts disagreements between the cache, the pool and the chain
+    metrics: Metrics,
+    /// How long a reserved nonce may stay out of the pool before it is a gap
+    gap_timeout: Duration,
 }
 
 impl NonceManager {
     /// Creates a new nonce manager with an empty cache
-    pub fn new(client: OnlineClient<SubstrateConfig>) -> Self {
+    ///
+    /// # Arguments
+    /// * `chain` - Queries for the pool and finalized nonces
+    /// * `source` - Where the next nonce is read from (`NONCE_SOURCE`)
+    /// * `metrics` - Counters the manager reports disagreements to
+    /// * `gap_timeout` - How long a reserved nonce may stay out of the pool
+    ///   before it counts as a gap (`NONCE_GAP_TIMEOUT_SECS`)
+    pub fn new(
+        chain: Arc<dyn NonceQueries>,
+        source: NonceSource,
+        metrics: Metrics,
+        gap_timeout: Duration,
+    ) -> Self {
         Self {
-            nonce_cache: Arc::new(Mutex::new(HashMap::new())),
-            client,
+            shards: Arc::new(
+                (0..SHARD_COUNT)
+                    .map(|_| StdMutex::new(HashMap::new()))
+                    .collect(),
+            ),
+            chain,
+            source,
+            metrics,
+            gap_timeout,
+        }
+    }
+
+    /// Returns the shard an account key lives in
+    fn shard(&self, account_key: &[u8; 32]) -> &Shard {
+        &self.shards[account_key[0] as usize % SHARD_COUNT]
+    }
+
+    /// Returns an account's slot, creating an empty one on first use
+    fn slot(&self, account_id: &AccountId32) -> AccountSlot {
+        // A poisoned shard only means another thread panicked mid-insert;
+        // the map itself is still consistent
+        let mut shard = self
+            .shard(&account_id.0)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        shard.entry(account_id.0).or_default().clone()
+    }
+
+    /// Returns an account's slot if the account has been seen before
+    fn existing_slot(&self, account_id: &AccountId32) -> Option<AccountSlot> {
+        self.shard(&account_id.0)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&account_id.0)
+            .cloned()
+    }
+
+    /// Returns every account's slot, for walking the whole cache
+    fn all_slots(&self) -> Vec<([u8; 32], AccountSlot)> {
+        self.shards
+            .iter()
+            .flat_map(|shard| {
+                shard
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .iter()
+                    .map(|(key, slot)| (*key, slot.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Reads an account's next nonce from the configured source
+    async fn source_nonce(
+        &self,
+        account_id: &AccountId32,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        match self.source {
+            NonceSource::Pool => self.chain.pool_nonce(account_id).await,
+            NonceSource::Finalized => self.chain.finalized_nonce(account_id).await,
         }
     }
 
+    /// Reads the pool and chain nonces and compares them with `cached`
+    ///
+    /// Disagreements are logged and counted.
+    async fn report(
+        &self,
+        account_id: &AccountId32,
+        cached: Option<u64>,
+    ) -> Result<NonceReport, Box<dyn std::error::Error + Send + Sync>> {
+        let pool = self.chain.pool_nonce(account_id).await?;
+        let chain = self.chain.finalized_nonce(account_id).await?;
+        let report = NonceReport::new(account_id, self.source, cached, pool, chain);
+
+        if !report.consistent {
+            self.metrics.record_nonce_disagreement();
+            log::warn!(
+                "⚠️ Nonces of account {:?} disagree: cached={:?}, pool={}, chain={}: {}",
+                account_id,
+                cached,
+                pool,
+                chain,
+                report.issues.join("; ")
+            );
+        }
+        Ok(report)
+    }
+
+    /// Compares the cached nonce of an account with the pool and the chain
+    ///
+    /// The cache is only read, so this can be called at any time without
+    /// holding up the account's submissions.
+    ///
+    /// # Arguments
+    /// * `account_id` - The account to inspect
+    ///
+    /// # Returns
+    /// * `Ok(NonceReport)` - The three nonces and what their differences suggest
+    /// * `Err(error)` - If there was an error querying the node
+    pub async fn inspect(
+        &self,
+        account_id: &AccountId32,
+    ) -> Result<NonceReport, Box<dyn std::error::Error + Send + Sync>> {
+        let cached = match self.existing_slot(account_id) {
+            Some(slot) => slot.lock().await.next,
+            None => None,
+        };
+        self.report(account_id, cached).await
+    }
+
     /// Gets the next nonce for an account, handling synchronization with the blockchain.
     ///
     /// This method:
     /// 1. Checks the local cache for the account's next expected nonce
-    /// 2. Queries the blockchain for the current nonce
+    /// 2. Queries the configured nonce source (pool or finalized chain)
     /// 3. Uses the higher of the two to avoid conflicts
     /// 4. Reserves the next nonce (nonce_to_use + 1) for future transactions
     ///
@@ -35,67 +360,385 @@ impl NonceManager {
     /// * `account_id` - The account ID to get the next nonce for
     ///
     /// # Returns
-    /// * `Ok(nonce)` - The nonce to use for the next transaction
+    /// * `Ok(lease)` - The lease of the nonce to use for the next transaction
     /// * `Err(error)` - If there was an error querying the blockchain
     pub async fn get_next_nonce(
         &self,
         account_id: &AccountId32,
-    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
-        let mut cache = self.nonce_cache.lock().await;
+    ) -> Result<NonceLease, Box<dyn std::error::Error + Send + Sync>> {
+        self.reserve_nonces(account_id, 1).await
+    }
+
+    /// Reserves a contiguous range of nonces for an account
+    ///
+    /// Works like `get_next_nonce`, but reserves `count` consecutive nonces in
+    /// one step so that no other request can take a nonce in the middle of
+    /// the range. Used by batch submissions that sign many transactions up front.
+    ///
+    /// # Arguments
+    /// * `account_id` - The account ID to reserve nonces for
+    /// * `count` - How many consecutive nonces to reserve
+    ///
+    /// # Returns
+    /// * `Ok(lease)` - The lease of the range `lease.nonce()..lease.nonce() + count`
+    /// * `Err(error)` - If there was an error querying the blockchain
+    pub async fn reserve_nonces(
+        &self,
+        account_id: &AccountId32,
+        count: u64,
+    ) -> Result<NonceLease, Box<dyn std::error::Error + Send + Sync>> {
+        // Only this account's lock is held across the RPC call below
+        let slot = self.slot(account_id);
+        let mut account = slot.lock().await;
 
         // Get the account's current nonce from our cache
-        // The .0 accesses the first (and only) field of the tuple struct
-        let account_key = account_id.0;
-        let cached_nonce = cache.get(&account_key).copied();
+        let cached_nonce = account.next;
 
-        // Get the chain's current nonce
-        let chain_nonce = self.client.tx().account_nonce(account_id).await?;
+        // Get the current nonce from the configured source
+        let source_nonce = self.source_nonce(account_id).await?;
 
         // Determine which nonce to use
         let nonce_to_use = match cached_nonce {
             Some(cached) => {
-                // Use whichever is higher: our cached nonce or chain nonce
-                cached.max(chain_nonce)
+                if source_nonce > cached {
+                    // Someone else used nonces the cache doesn't know about
+                    self.metrics.record_nonce_disagreement();
+                    log::warn!(
+                        "⚠️ Account {:?}: {:?} nonce {} is ahead of cached nonce {}, \
+                         nonces were used outside this service",
+                        account_id,
+                        self.source,
+                        source_nonce,
+                        cached
+                    );
+                }
+                // Use whichever is higher: our cached nonce or source nonce
+                cached.max(source_nonce)
             }
             None => {
-                // First time seeing this account, use chain nonce
-                chain_nonce
+                // First time seeing this account, use source nonce
+                source_nonce
             }
         };
 
-        // Reserve the next nonce for future transactions
-        cache.insert(account_key, nonce_to_use + 1);
+        // Reserve the range so the next transaction starts after it
+        account.next = Some(nonce_to_use + count);
+
+        // Each nonce of the range belongs to the lease, and has until the
+        // gap timeout to show up in the pool once the lease is dropped
+        let lease = NonceLease {
+            first_nonce: nonce_to_use,
+            owner: Arc::new(()),
+        };
+        let deadline = Instant::now() + self.gap_timeout;
+        for nonce in nonce_to_use..nonce_to_use + count {
+            account.reserved.insert(
+                nonce,
+                Reservation {
+                    deadline,
+                    owner: Arc::downgrade(&lease.owner),
+                },
+            );
+        }
 
         log::info!(
-            "🔢 Account {:?}: chain_nonce={}, cached_nonce={:?}, using_nonce={}",
+            "🔢 Account {:?}: source_nonce={}, cached_nonce={:?}, using_nonce={}, count={}",
             account_id,
-            chain_nonce,
+            source_nonce,
             cached_nonce,
-            nonce_to_use
+            nonce_to_use,
+            count
+        );
+
+        Ok(lease)
+    }
+
+    /// Returns the nonce the next transaction for an account would use, without reserving it
+    ///
+    /// Uses the same rule as `get_next_nonce` (the higher of the cached and
+    /// source nonce) but leaves the cache untouched. This is meant for dry runs
+    /// and estimates that sign a transaction they will never submit.
+    ///
+    /// # Arguments
+    /// * `account_id` - The account ID to peek the next nonce for
+    ///
+    /// # Returns
+    /// * `Ok(nonce)` - The nonce the next transaction would use
+    /// * `Err(error)` - If there was an error querying the blockchain
+    pub async fn peek_next_nonce(
+        &self,
+        account_id: &AccountId32,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let cached_nonce = match self.existing_slot(account_id) {
+            Some(slot) => slot.lock().await.next,
+            None => None,
+        };
+        let source_nonce = self.source_nonce(account_id).await?;
+
+        Ok(cached_nonce.map_or(source_nonce, |cached| cached.max(source_nonce)))
+    }
+
+    /// Remembers the signer of an account, so gaps in its nonces can be filled
+    ///
+    /// Called with the signer that just reserved a nonce; the latest one wins.
+    pub async fn remember_signer(&self, signer: &Arc<dyn TxSigner>) {
+        let slot = self.slot(&signer.account_id());
+        slot.lock().await.signer = Some(signer.clone());
+    }
+
+    /// Hands back a reserved nonce that will never be used, if it is safe to do so
+    ///
+    /// The cache is only rewound when `nonce` is the most recent
+    /// reservation for the account. If later nonces were reserved
+    /// in the meantime, rewinding would hand one of them out twice, so the
+    /// gap is left in place and logged instead.
+    ///
+    /// # Arguments
+    /// * `account_id` - The account the nonce was reserved for
+    /// * `nonce` - The unused nonce
+    ///
+    /// # Returns
+    /// `true` if the nonce was handed back, `false` if a gap remains
+    pub async fn release_nonce(&self, account_id: &AccountId32, nonce: u64) -> bool {
+        self.release_nonces(account_id, nonce, 1).await
+    }
+
+    /// Hands back a range of reserved nonces that will never be used, if it is safe to do so
+    ///
+    /// Works like `release_nonce` for `first_nonce..first_nonce + count`: the
+    /// cache is only rewound when the range is the most recent reservation.
+    ///
+    /// # Arguments
+    /// * `account_id` - The account the nonces were reserved for
+    /// * `first_nonce` - The first unused nonce
+    /// * `count` - How many consecutive nonces are unused
+    ///
+    /// # Returns
+    /// `true` if the range was handed back, `false` if a gap remains
+    pub async fn release_nonces(
+        &self,
+        account_id: &AccountId32,
+        first_nonce: u64,
+        count: u64,
+    ) -> bool {
+        let slot = self.slot(account_id);
+        let mut account = slot.lock().await;
+        let end = first_nonce + count;
+
+        match account.next {
+            Some(next) if next == end => {
+                account.next = Some(first_nonce);
+                account.reserved.retain(|&nonce, _| nonce < first_nonce);
+                log::info!(
+                    "🔄 Released nonces {}..{} for account {:?}",
+                    first_nonce,
+                    end,
+                    account_id
+                );
+                true
+            }
+            next => {
+                log::warn!(
+                    "⚠️ Cannot release nonces {}..{} for account {:?} (next nonce is {:?}), leaving a gap",
+                    first_nonce,
+                    end,
+                    account_id,
+                    next
+                );
+                false
+            }
+        }
+    }
+
+    /// Moves the cache to the nonce a fallback signature actually used
+    ///
+    /// When explicit-nonce signing fails and the signing strategy allows a
+    /// fallback, the node's next nonce is used instead of the reservation
+    /// `reserved`. The cache only ever moves forward: a fallback nonce below
+    /// it means the node hasn't seen transactions that are still being
+    /// signed or submitted here, and moving back would hand their nonces out
+    /// twice. A reservation the fallback left unused is picked up by the gap
+    /// check.
+    ///
+    /// # Arguments
+    /// * `account_id` - The account that signed the transaction
+    /// * `reserved` - The nonce that was reserved for the transaction
+    /// * `used` - The nonce the fallback signed with
+    pub async fn adopt_fallback_nonce(&self, account_id: &AccountId32, reserved: u64, used: u64) {
+        let slot = self.slot(account_id);
+        let mut account = slot.lock().await;
+
+        let next = account
+            .next
+            .map_or(used + 1, |current| current.max(used + 1));
+        let previous = account.next.replace(next);
+
+        log::warn!(
+            "🔄 Account {:?}: fallback signed with nonce {} instead of {}, cache {:?} -> {}",
+            account_id,
+            used,
+            reserved,
+            previous,
+            next
         );
+    }
 
-        Ok(nonce_to_use)
+    /// Records a nonce used by a transaction that was signed outside this service
+    ///
+    /// Client-signed transactions relayed through /tx/submit-raw pick their
+    /// own nonces. If the account is also used by this service, the cache is
+    /// moved past that nonce so the next managed transaction doesn't collide
+    /// with it. Accounts that aren't cached are left alone; their first
+    /// managed transaction reads the nonce from the chain anyway.
+    ///
+    /// # Arguments
+    /// * `account_id` - The account that signed the transaction
+    /// * `nonce` - The nonce the transaction was signed with
+    pub async fn observe_external_nonce(&self, account_id: &AccountId32, nonce: u64) {
+        let Some(slot) = self.existing_slot(account_id) else {
+            return;
+        };
+        let mut account = slot.lock().await;
+
+        if let Some(next) = account.next.as_mut() {
+            if nonce >= *next {
+                log::info!(
+                    "🔢 Account {:?}: external nonce {} moves cache {} -> {}",
+                    account_id,
+                    nonce,
+                    *next,
+                    nonce + 1
+                );
+                *next = nonce + 1;
+            }
+        }
     }
 
-    /// Resets the nonce cache for an account to a specific value
+    /// Brings the cached nonce for an account in line with the nonce source
     ///
-    /// This is useful when a transaction fails and you want to reuse the same nonce
-    /// for the next transaction attempt.
+    /// Used after the pool reported a nonce problem (stale, future, or
+    /// usurped) with `failed_nonce`, when the cached value may be wrong in
+    /// either direction. The failed reservation is settled, and the cache
+    /// moves forward to the source whenever it is behind. It only moves
+    /// backwards when no other reservation at or above the source's nonce is
+    /// outstanding: those may still be on their way to the pool, and
+    /// rewinding would hand their nonces out twice.
     ///
     /// # Arguments
-    /// * `account_id` - The account ID to reset the nonce for
-    /// * `failed_nonce` - The nonce that failed and should be reused
-    pub async fn reset_nonce(&self, account_id: &AccountId32, failed_nonce: u64) {
-        let mut cache = self.nonce_cache.lock().await;
+    /// * `account_id` - The account ID to reconcile
+    /// * `failed_nonce` - The nonce the pool disagreed with
+    ///
+    /// # Returns
+    /// * `Ok(nonce)` - The next nonce now stored in the cache
+    /// * `Err(error)` - If there was an error querying the blockchain
+    pub async fn reconcile(
+        &self,
+        account_id: &AccountId32,
+        failed_nonce: u64,
+    ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+        let slot = self.slot(account_id);
+        let mut account = slot.lock().await;
+        let source_nonce = self.source_nonce(account_id).await?;
 
-        // Reset to the failed nonce so it can be reused
-        cache.insert(account_id.0, failed_nonce);
+        account.reserved.remove(&failed_nonce);
+        account.reserved.retain(|&nonce, _| nonce >= source_nonce);
+        let next = match account.next {
+            Some(current) if current > source_nonce && !account.reserved.is_empty() => current,
+            _ => source_nonce,
+        };
+        let previous = account.next.replace(next);
 
         log::warn!(
-            "🔄 Reset nonce for account {:?} to {}",
+            "🔄 Reconciled nonce for account {:?} after nonce {} failed: {:?} -> {} ({:?} nonce {})",
             account_id,
-            failed_nonce
+            failed_nonce,
+            previous,
+            next,
+            self.source,
+            source_nonce
         );
+
+        Ok(next)
+    }
+
+    /// Checks whether an account's transactions wait behind a missing nonce
+    ///
+    /// The pool's next nonce for the account (`system_accountNextIndex`) is
+    /// the first one it is still waiting for, whatever the configured nonce
+    /// source: a transaction waiting in the pool is never missing, even if it
+    /// isn't finalized yet. Reservations below it were seen and are
+    /// forgotten, as are reservations that were handed back. If the pool
+    /// waits for a nonce below the cache's next one, that nonce is a gap once
+    /// its owner dropped the lease and its deadline has passed; a nonce whose
+    /// owner is still in flight is never a gap. A missing nonce that isn't
+    /// reserved (e.g. its transaction was dropped from the pool) gets the gap
+    /// timeout from the moment it is first noticed.
+    ///
+    /// # Arguments
+    /// * `account_id` - The account to check
+    ///
+    /// # Returns
+    /// * `Ok(Some(gap))` - The first gap of the account
+    /// * `Ok(None)` - If nothing is missing, or not for long enough yet
+    /// * `Err(error)` - If there was an error querying the node
+    pub async fn find_gap(
+        &self,
+        account_id: &AccountId32,
+    ) -> Result<Option<NonceGap>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(slot) = self.existing_slot(account_id) else {
+            return Ok(None);
+        };
+        let mut account = slot.lock().await;
+        let Some(next) = account.next else {
+            return Ok(None);
+        };
+        let missing = self.chain.pool_nonce(account_id).await?;
+
+        account
+            .reserved
+            .retain(|&nonce, _| nonce >= missing && nonce < next);
+        if missing >= next {
+            return Ok(None);
+        }
+
+        let now = Instant::now();
+        let reservation = account
+            .reserved
+            .entry(missing)
+            .or_insert_with(|| Reservation::unowned(now + self.gap_timeout));
+        if reservation.in_flight() || now < reservation.deadline {
+            return Ok(None);
+        }
+        Ok(Some(NonceGap {
+            account_id: account_id.clone(),
+            nonce: missing,
+            blocked: next - missing,
+            signer: account.signer.clone(),
+        }))
+    }
+
+    /// Checks every cached account for a missing nonce, see `find_gap`
+    ///
+    /// An account whose pool nonce can't be read is logged and skipped, so
+    /// the other accounts are still checked.
+    ///
+    /// # Returns
+    /// The first gap of every affected account
+    pub async fn find_gaps(&self) -> Vec<NonceGap> {
+        let mut gaps = Vec::new();
+        for (account_key, _) in self.all_slots() {
+            let account_id = AccountId32(account_key);
+            match self.find_gap(&account_id).await {
+                Ok(Some(gap)) => gaps.push(gap),
+                Ok(None) => {}
+                Err(e) => log::error!(
+                    "🕳️ Nonce gap check failed for account {:?}: {:?}",
+                    account_id,
+                    e
+                ),
+            }
+        }
+        gaps
     }
 
     /// Synchronizes the nonce cache with the blockchain state
@@ -103,8 +746,9 @@ impl NonceManager {
     /// This method should be called periodically (e.g., every 30 seconds) to ensure
     /// the local cache stays in sync with the actual blockchain state. It:
     /// 1. Iterates through all cached accounts.
-    /// 2. Queries the blockchain for each account's current nonce  
-    /// 3. Updates the cache if the blockchain is ahead
+    /// 2. Queries the pool and the finalized chain for each account's next nonce
+    /// 3. Reports any disagreement between the cache, the pool and the chain
+    /// 4. Updates the cache if the configured nonce source is ahead
     ///
     /// This prevents issues where external transactions (not from this service)
     /// advance the blockchain nonce ahead of our cache.
@@ -113,29 +757,362 @@ impl NonceManager {
     /// * `Ok(())` - If synchronization completed successfully
     /// * `Err(error)` - If there was an error querying the blockchain
     pub async fn sync_with_chain(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let mut cache = self.nonce_cache.lock().await;
-
         // For each account in our cache, check if we're out of sync
         // HashMap {
         //     [1,2,3...32]: 10,    // Alice's account -> nonce 10
         //     [4,5,6...32]: 25,    // Bob's account   -> nonce 25
         //     [7,8,9...32]: 5,     // Charlie's account -> nonce 5
         // }
-        for (account_key, cached_nonce) in cache.iter_mut() {
-            let account_id = AccountId32(*account_key);
-            let chain_nonce = self.client.tx().account_nonce(&account_id).await?;
+        // The account's lock is not held while the node is queried, so its
+        // submissions carry on during the sync. The cache only ever moves
+        // forward here, which stays correct whatever was reserved meanwhile.
+        for (account_key, slot) in self.all_slots() {
+            let Some(cached_nonce) = slot.lock().await.next else {
+                continue;
+            };
+            let account_id = AccountId32(account_key);
+            let report = self.report(&account_id, Some(cached_nonce)).await?;
+            let source_nonce = report.nonce_from(self.source);
 
-            // If chain is ahead, update our cache
-            if chain_nonce > *cached_nonce {
-                log::info!(
-                    "📊 Syncing account {:?}: {} -> {}",
-                    account_id,
-                    *cached_nonce,
-                    chain_nonce
-                );
-                *cached_nonce = chain_nonce;
+            // If the source is ahead, update our cache
+            let mut account = slot.lock().await;
+            if let Some(next) = account.next.as_mut() {
+                if source_nonce > *next {
+                    log::info!(
+                        "📊 Syncing account {:?}: {} -> {}",
+                        account_id,
+                        *next,
+                        source_nonce
+                    );
+                    *next = source_nonce;
+                }
             }
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use tokio::sync::Semaphore;
+
+    /// In-memory node: fixed nonces per account, optionally held up by a gate
+    #[derive(Default)]
+    struct FakeChain {
+        pool: StdMutex<HashMap<[u8; 32], u64>>,
+        finalized: StdMutex<HashMap<[u8; 32], u64>>,
+        gates: StdMutex<HashMap<[u8; 32], Arc<Semaphore>>>,
+        unreachable: StdMutex<Vec<[u8; 32]>>,
+    }
+
+    impl FakeChain {
+        /// Makes pool queries for the account fail
+        fn fail_pool(&self, account_id: &AccountId32) {
+            self.unreachable.lock().unwrap().push(account_id.0);
+        }
+
+        fn set_pool(&self, account_id: &AccountId32, nonce: u64) {
+            self.pool.lock().unwrap().insert(account_id.0, nonce);
+        }
+
+        fn set_finalized(&self, account_id: &AccountId32, nonce: u64) {
+            self.finalized.lock().unwrap().insert(account_id.0, nonce);
+        }
+
+        /// Makes queries for the account wait until a permit is added
+        fn gate(&self, account_id: &AccountId32) -> Arc<Semaphore> {
+            let gate = Arc::new(Semaphore::new(0));
+            self.gates
+                .lock()
+                .unwrap()
+                .insert(account_id.0, gate.clone());
+            gate
+        }
+
+        async fn wait_for_gate(&self, account_id: &AccountId32) {
+            let gate = self.gates.lock().unwrap().get(&account_id.0).cloned();
+            if let Some(gate) = gate {
+                gate.acquire().await.unwrap().forget();
+            }
+        }
+    }
+
+    #[async_trait]
+    impl NonceQueries for FakeChain {
+        async fn pool_nonce(
+            &self,
+            account_id: &AccountId32,
+        ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+            self.wait_for_gate(account_id).await;
+            if self.unreachable.lock().unwrap().contains(&account_id.0) {
+                return Err("pool unreachable".into());
+            }
+            Ok(*self.pool.lock().unwrap().get(&account_id.0).unwrap_or(&0))
+        }
+
+        async fn finalized_nonce(
+            &self,
+            account_id: &AccountId32,
+        ) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+            self.wait_for_gate(account_id).await;
+            Ok(*self
+                .finalized
+                .lock()
+                .unwrap()
+                .get(&account_id.0)
+                .unwrap_or(&0))
+        }
+    }
+
+    fn account(byte: u8) -> AccountId32 {
+        AccountId32([byte; 32])
+    }
+
+    fn manager(chain: Arc<FakeChain>) -> NonceManager {
+        NonceManager::new(
+            chain,
+            NonceSource::Pool,
+            Metrics::new(),
+            Duration::from_secs(60),
+        )
+    }
+
+    /// A manager whose missing nonces are gaps as soon as nobody owns them
+    fn impatient_manager(chain: Arc<FakeChain>, source: NonceSource) -> NonceManager {
+        NonceManager::new(chain, source, Metrics::new(), Duration::ZERO)
+    }
+
+    #[tokio::test]
+    async fn concurrent_reservations_are_unique_and_contiguous() {
+        let chain = Arc::new(FakeChain::default());
+        let alice = account(1);
+        chain.set_pool(&alice, 7);
+        let manager = manager(chain);
+
+        let tasks: Vec<_> = (0..50)
+            .map(|_| {
+                let manager = manager.clone();
+                let alice = alice.clone();
+                tokio::spawn(async move { manager.get_next_nonce(&alice).await.unwrap().nonce() })
+            })
+            .collect();
+        let mut nonces = Vec::new();
+        for task in tasks {
+            nonces.push(task.await.unwrap());
+        }
+        nonces.sort_unstable();
+
+        assert_eq!(nonces, (7..57).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn a_slow_account_does_not_block_others() {
+        let chain = Arc::new(FakeChain::default());
+        let (alice, bob) = (account(1), account(2));
+        let alice_gate = chain.gate(&alice);
+        let manager = manager(chain);
+
+        let slow = {
+            let manager = manager.clone();
+            let alice = alice.clone();
+            tokio::spawn(async move { manager.get_next_nonce(&alice).await.unwrap().nonce() })
+        };
+        tokio::task::yield_now().await;
+
+        // Bob's reservation completes while Alice's query is still pending
+        let bob_nonce = tokio::time::timeout(Duration::from_secs(1), manager.get_next_nonce(&bob))
+            .await
+            .expect("reservation for another account was blocked")
+            .unwrap()
+            .nonce();
+        assert_eq!(bob_nonce, 0);
+        assert!(!slow.is_finished());
+
+        alice_gate.add_permits(1);
+        assert_eq!(slow.await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn range_reservations_do_not_overlap() {
+        let chain = Arc::new(FakeChain::default());
+        let alice = account(1);
+        let manager = manager(chain);
+
+        assert_eq!(manager.reserve_nonces(&alice, 5).await.unwrap().nonce(), 0);
+        assert_eq!(manager.get_next_nonce(&alice).await.unwrap().nonce(), 5);
+        assert_eq!(manager.reserve_nonces(&alice, 3).await.unwrap().nonce(), 6);
+        assert_eq!(manager.get_next_nonce(&alice).await.unwrap().nonce(), 9);
+    }
+
+    #[tokio::test]
+    async fn source_ahead_of_cache_wins() {
+        let chain = Arc::new(FakeChain::default());
+        let alice = account(1);
+        let manager = manager(chain.clone());
+
+        assert_eq!(manager.get_next_nonce(&alice).await.unwrap().nonce(), 0);
+        // Another process submitted nonces 1..=4
+        chain.set_pool(&alice, 5);
+        assert_eq!(manager.get_next_nonce(&alice).await.unwrap().nonce(), 5);
+    }
+
+    #[tokio::test]
+    async fn fallback_nonce_never_moves_the_cache_back() {
+        let chain = Arc::new(FakeChain::default());
+        let alice = account(1);
+        let manager = manager(chain);
+        manager.reserve_nonces(&alice, 3).await.unwrap();
+
+        // The node hasn't seen nonces 0..=2 yet
+        manager.adopt_fallback_nonce(&alice, 2, 0).await;
+        assert_eq!(manager.peek_next_nonce(&alice).await.unwrap(), 3);
+
+        // Transactions from elsewhere moved the node ahead
+        manager.adopt_fallback_nonce(&alice, 2, 8).await;
+        assert_eq!(manager.peek_next_nonce(&alice).await.unwrap(), 9);
+    }
+
+    #[tokio::test]
+    async fn reconcile_rewinds_only_without_outstanding_reservations() {
+        let chain = Arc::new(FakeChain::default());
+        let alice = account(1);
+        let manager = manager(chain.clone());
+        manager.reserve_nonces(&alice, 3).await.unwrap();
+
+        // Nonce 0 went stale while 1 and 2 are still on their way
+        assert_eq!(manager.reconcile(&alice, 0).await.unwrap(), 3);
+        assert_eq!(manager.peek_next_nonce(&alice).await.unwrap(), 3);
+
+        // Only the failed nonce was outstanding: follow the pool back
+        let bob = account(2);
+        manager.get_next_nonce(&bob).await.unwrap();
+        manager.get_next_nonce(&bob).await.unwrap();
+        chain.set_pool(&bob, 1);
+        assert_eq!(manager.reconcile(&bob, 1).await.unwrap(), 1);
+
+        // A source ahead of the cache always wins
+        chain.set_pool(&alice, 9);
+        assert_eq!(manager.reconcile(&alice, 1).await.unwrap(), 9);
+    }
+
+    #[tokio::test]
+    async fn release_only_rewinds_the_latest_reservation() {
+        let chain = Arc::new(FakeChain::default());
+        let alice = account(1);
+        let manager = manager(chain);
+        manager.reserve_nonces(&alice, 2).await.unwrap();
+
+        assert!(!manager.release_nonce(&alice, 0).await);
+        assert!(manager.release_nonce(&alice, 1).await);
+        assert_eq!(manager.get_next_nonce(&alice).await.unwrap().nonce(), 1);
+    }
+
+    #[tokio::test]
+    async fn a_batch_range_is_only_released_while_nothing_follows_it() {
+        let chain = Arc::new(FakeChain::default());
+        let alice = account(1);
+        let manager = manager(chain);
+
+        // Another request reserved a nonce after the batch
+        assert_eq!(manager.reserve_nonces(&alice, 4).await.unwrap().nonce(), 0);
+        assert_eq!(manager.get_next_nonce(&alice).await.unwrap().nonce(), 4);
+        assert!(!manager.release_nonces(&alice, 0, 4).await);
+        assert_eq!(manager.peek_next_nonce(&alice).await.unwrap(), 5);
+
+        // The tail of the latest batch can be handed back
+        assert_eq!(manager.reserve_nonces(&alice, 4).await.unwrap().nonce(), 5);
+        assert!(manager.release_nonces(&alice, 7, 2).await);
+        assert_eq!(manager.get_next_nonce(&alice).await.unwrap().nonce(), 7);
+    }
+
+    #[tokio::test]
+    async fn a_gap_waits_for_its_owner() {
+        let chain = Arc::new(FakeChain::default());
+        let alice = account(1);
+        let manager = impatient_manager(chain, NonceSource::Pool);
+
+        // A batch (or a retry in backoff) still holds nonces 0 and 1
+        let lease = manager.reserve_nonces(&alice, 2).await.unwrap();
+        assert!(manager.find_gap(&alice).await.unwrap().is_none());
+
+        drop(lease);
+        let gap = manager.find_gap(&alice).await.unwrap().expect("gap");
+        assert_eq!((gap.nonce, gap.blocked), (0, 2));
+    }
+
+    #[tokio::test]
+    async fn a_gap_is_not_due_before_its_deadline() {
+        let chain = Arc::new(FakeChain::default());
+        let alice = account(1);
+        let manager = manager(chain);
+
+        drop(manager.get_next_nonce(&alice).await.unwrap());
+        assert!(manager.find_gap(&alice).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn pooled_nonces_are_not_gaps() {
+        let chain = Arc::new(FakeChain::default());
+        let alice = account(1);
+        chain.set_finalized(&alice, 1);
+        chain.set_pool(&alice, 1);
+        let finalized = impatient_manager(chain.clone(), NonceSource::Finalized);
+        let pool = impatient_manager(chain.clone(), NonceSource::Pool);
+        drop(finalized.reserve_nonces(&alice, 2).await.unwrap());
+        drop(pool.reserve_nonces(&alice, 2).await.unwrap());
+
+        // Nonces 1 and 2 are in the pool but not finalized yet
+        chain.set_pool(&alice, 3);
+        assert!(pool.find_gap(&alice).await.unwrap().is_none());
+        assert!(finalized.find_gap(&alice).await.unwrap().is_none());
+
+        // Only nonce 1 is in the pool: 2 is missing, whatever the source
+        chain.set_pool(&alice, 2);
+        for manager in [&pool, &finalized] {
+            let gap = manager.find_gap(&alice).await.unwrap();
+            assert_eq!(gap.map(|gap| gap.nonce), Some(2));
+        }
+    }
+
+    #[tokio::test]
+    async fn a_failed_lookup_does_not_end_the_gap_sweep() {
+        let chain = Arc::new(FakeChain::default());
+        let manager = impatient_manager(chain.clone(), NonceSource::Pool);
+        let accounts: Vec<_> = (1..=3).map(account).collect();
+        for account_id in &accounts {
+            drop(manager.reserve_nonces(account_id, 2).await.unwrap());
+            chain.set_pool(account_id, 1);
+        }
+        chain.fail_pool(&accounts[1]);
+
+        let mut found: Vec<_> = manager
+            .find_gaps()
+            .await
+            .into_iter()
+            .map(|gap| gap.account_id)
+            .collect();
+        found.sort_by_key(|account_id| account_id.0);
+        assert_eq!(found, [accounts[0].clone(), accounts[2].clone()]);
+    }
+
+    #[tokio::test]
+    async fn sync_only_moves_forward() {
+        let chain = Arc::new(FakeChain::default());
+        let alice = account(1);
+        chain.set_pool(&alice, 3);
+        chain.set_finalized(&alice, 3);
+        let manager = manager(chain.clone());
+        manager.reserve_nonces(&alice, 4).await.unwrap();
+
+        // Behind the cache: nothing changes
+        manager.sync_with_chain().await.unwrap();
+        assert_eq!(manager.peek_next_nonce(&alice).await.unwrap(), 7);
+
+        chain.set_pool(&alice, 10);
+        manager.sync_with_chain().await.unwrap();
+        chain.set_pool(&alice, 0);
+        assert_eq!(manager.peek_next_nonce(&alice).await.unwrap(), 10);
+    }
+}
@@ -0,0 +1,504 @@
+// src/policy.rs
+//
+// Signer authorization policy
+//
+// Holding a signer's alias is not enough to sign with it. A policy file says,
+// for every managed signer, which API clients may use it, which pallet calls
+// it may submit and which argument values those calls may carry. Submissions
+// are checked against it before a nonce is reserved, so a denied request
+// costs nothing on chain. Every decision is logged.
+//
+// Policy file (`POLICY_PATH`, JSON):
+//
+//   {
+//     "signers": {
+//       "treasury": {
+//         "clients": ["payouts"],                  // optional, any client if omitted
+//         "calls": {
+//           "Balances.transfer_keep_alive": {
+//             "max": { "value": "1000000000000" },  // numeric upper bounds
+//             "allowed": { "dest": ["5FHneW46..."] } // allowed values
+//           },
+//           "Template.*": {}                        // every call of a pallet
+//         }
+//       }
+//     }
+//   }
+//
+// Signers are listed by keystore alias; raw secret URIs (dev mode) by their
+// SS58 address. Derived user accounts are listed as `user:{user_id}`, or all
+// at once as `user:*`. Accounts this service holds no key for (relayed
+// extrinsics, accounts prepared for external signers) are listed by SS58
+// address, or all at once as `external`. Signers and calls that aren't
+// listed are denied. Clients identify themselves with the `X-Api-Client`
+// header, which is expected to be set by an authenticating gateway in front
+// of this service.
+//
+// A missing policy file stops the server from starting. Every request is
+// only allowed with `POLICY_PATH=none`, or in dev mode when the file is
+// missing.
+
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::{collections::BTreeMap, convert::Infallible, path::Path, str::FromStr, sync::Arc};
+use subxt::utils::AccountId32;
+
+use crate::receipts::CallInfo;
+use crate::users::USER_SIGNER_PREFIX;
+
+/// `POLICY_PATH` value that turns the policy off and allows every request
+pub const NO_POLICY: &str = "none";
+
+/// Header naming the API client a request comes from
+pub const API_CLIENT_HEADER: &str = "x-api-client";
+
+/// Policy entry covering every account this service holds no key for
+pub const EXTERNAL_SIGNERS: &str = "external";
+
+/// Policy entry covering every derived user account
+const ALL_USERS: &str = "user:*";
+
+/// The policy file as stored on disk
+#[derive(Debug, Deserialize)]
+struct PolicyFile {
+    /// Rules keyed by signer alias, SS58 address, `user:*` or `external`
+    #[serde(default)]
+    signers: BTreeMap<String, SignerPolicy>,
+}
+
+/// What one signer may be used for
+#[derive(Debug, Deserialize)]
+struct SignerPolicy {
+    /// API clients allowed to use the signer, or `None` for any client
+    #[serde(default)]
+    clients: Option<Vec<String>>,
+    /// Allowed calls keyed by `Pallet.call`, `Pallet.*` or `*`
+    #[serde(default)]
+    calls: BTreeMap<String, CallPolicy>,
+}
+
+/// Constraints on the arguments of an allowed call
+#[derive(Debug, Default, Deserialize)]
+struct CallPolicy {
+    /// Upper bounds of numeric arguments, as numbers or decimal strings
+    #[serde(default)]
+    max: BTreeMap<String, JsonValue>,
+    /// Allowed values of arguments; accounts match in any encoding
+    #[serde(default)]
+    allowed: BTreeMap<String, Vec<JsonValue>>,
+}
+
+/// The loaded authorization policy
+///
+/// Cheap to clone; the rules never change after startup.
+#[derive(Clone, Default)]
+pub struct Policy {
+    /// The rules, or `None` to allow everything
+    rules: Option<Arc<PolicyFile>>,
+}
+
+impl Policy {
+    /// Creates a policy that allows every request
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Loads a policy file
+    ///
+    /// # Returns
+    /// * `Ok(Policy)` - The policy
+    /// * `Err(String)` - If the file can't be read or parsed
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read policy {}: {}", path.display(), e))?;
+        let file: PolicyFile = serde_json::from_str(&json)
+            .map_err(|e| format!("Invalid policy {}: {}", path.display(), e))?;
+        for (signer, rules) in &file.signers {
+            for (call, constraints) in &rules.calls {
+                if let Some((arg, _)) = constraints
+                    .max
+                    .iter()
+                    .find(|(_, max)| as_u128(max).is_none())
+                {
+                    return Err(format!(
+                        "Invalid policy {}: max of '{}' for {} of '{}' is not a number",
+                        path.display(),
+                        arg,
+                        call,
+                        signer
+                    ));
+                }
+            }
+        }
+        Ok(Self {
+            rules: Some(Arc::new(file)),
+        })
+    }
+
+    /// Number of signers the policy has rules for
+    pub fn len(&self) -> usize {
+        self.rules.as_ref().map_or(0, |rules| rules.signers.len())
+    }
+
+    /// Decides whether `client` may have `signer` submit `call`
+    ///
+    /// The decision is logged either way.
+    ///
+    /// # Arguments
+    /// * `signer` - The signer's alias (or SS58 address for raw secret URIs)
+    /// * `client` - The API client from the `X-Api-Client` header, if any
+    /// * `call` - The call to submit, with its JSON arguments
+    ///
+    /// # Returns
+    /// * `Ok(())` - The request is allowed
+    /// * `Err(String)` - Why it was denied, for the client
+    pub fn authorize(
+        &self,
+        signer: &str,
+        client: Option<&str>,
+        call: &CallInfo,
+    ) -> Result<(), String> {
+        // Derived user accounts share the "user:*" rules unless listed one by one
+        let fallback = signer.starts_with(USER_SIGNER_PREFIX).then_some(ALL_USERS);
+        self.decide(signer, fallback, client, call)
+    }
+
+    /// Decides whether `client` may submit `call` signed by an account this
+    /// service holds no key for
+    ///
+    /// The account is looked up by its SS58 address, then under `external`.
+    ///
+    /// # Returns
+    /// Same as `authorize`
+    pub fn authorize_external(
+        &self,
+        account_id: &AccountId32,
+        client: Option<&str>,
+        call: &CallInfo,
+    ) -> Result<(), String> {
+        self.decide(
+            &account_id.to_string(),
+            Some(EXTERNAL_SIGNERS),
+            client,
+            call,
+        )
+    }
+
+    /// Applies the rules of `signer`, or of `fallback` if it isn't listed,
+    /// and logs the decision
+    fn decide(
+        &self,
+        signer: &str,
+        fallback: Option<&str>,
+        client: Option<&str>,
+        call: &CallInfo,
+    ) -> Result<(), String> {
+        let decision = self.evaluate(signer, fallback, client, call);
+        let client = client.unwrap_or("-");
+        match &decision {
+            Ok(()) => log::info!(
+                "🛡️ Policy ALLOW: signer '{}', client '{}', {}.{}",
+                signer,
+                client,
+                call.pallet,
+                call.call
+            ),
+            Err(reason) => log::warn!(
+                "🛡️ Policy DENY: signer '{}', client '{}', {}.{}: {}",
+                signer,
+                client,
+                call.pallet,
+                call.call,
+                reason
+            ),
+        }
+        decision
+    }
+
+    /// Applies the rules without logging
+    fn evaluate(
+        &self,
+        signer: &str,
+        fallback: Option<&str>,
+        client: Option<&str>,
+        call: &CallInfo,
+    ) -> Result<(), String> {
+        let Some(rules) = &self.rules else {
+            return Ok(());
+        };
+        let signer_rules = rules
+            .signers
+            .get(signer)
+            .or_else(|| fallback.and_then(|fallback| rules.signers.get(fallback)))
+            .ok_or_else(|| format!("Signer '{}' is not covered by the policy", signer))?;
+
+        if let Some(clients) = &signer_rules.clients {
+            let allowed = client.is_some_and(|client| clients.iter().any(|c| c == client));
+            if !allowed {
+                return Err(format!(
+                    "Client '{}' may not use signer '{}'",
+                    client.unwrap_or("-"),
+                    signer
+                ));
+            }
+        }
+
+        // The most specific rule wins
+        let constraints = [
+            format!("{}.{}", call.pallet, call.call),
+            format!("{}.*", call.pallet),
+            "*".to_string(),
+        ]
+        .iter()
+        .find_map(|key| signer_rules.calls.get(key))
+        .ok_or_else(|| {
+            format!(
+                "Signer '{}' may not submit {}.{}",
+                signer, call.pallet, call.call
+            )
+        })?;
+
+        check_arguments(constraints, &call.args)
+    }
+}
+
+/// Checks a call's arguments against the constraints of its rule
+fn check_arguments(constraints: &CallPolicy, args: &JsonValue) -> Result<(), String> {
+    if constraints.max.is_empty() && constraints.allowed.is_empty() {
+        return Ok(());
+    }
+    // Constraints refer to arguments by name
+    let args = args
+        .as_object()
+        .ok_or("Arguments must be given by name for calls with policy constraints")?;
+    let arg = |name: &str| {
+        args.get(name)
+            .ok_or_else(|| format!("Missing argument '{}' required by the policy", name))
+    };
+
+    for (name, max) in &constraints.max {
+        let value =
+            as_u128(arg(name)?).ok_or_else(|| format!("Argument '{}' is not a number", name))?;
+        let max = as_u128(max).unwrap_or(0);
+        if value > max {
+            return Err(format!(
+                "Argument '{}' is {}, above the allowed maximum of {}",
+                name, value, max
+            ));
+        }
+    }
+
+    for (name, allowed) in &constraints.allowed {
+        let value = arg(name)?;
+        if !allowed.iter().any(|candidate| same_value(candidate, value)) {
+            return Err(format!(
+                "Argument '{}' has a value that is not allowed",
+                name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Reads a JSON number or decimal string as an unsigned integer
+fn as_u128(value: &JsonValue) -> Option<u128> {
+    match value {
+        JsonValue::Number(n) => n.as_u64().map(u128::from),
+        JsonValue::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Compares two argument values, treating accounts in different encodings as equal
+fn same_value(a: &JsonValue, b: &JsonValue) -> bool {
+    match (as_account(a), as_account(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Reads an account given as SS58, 0x-prefixed hex or a `{ "Id": ... }` address
+fn as_account(value: &JsonValue) -> Option<AccountId32> {
+    match value {
+        JsonValue::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => hex::decode(hex)
+                .ok()
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                .map(AccountId32),
+            None => AccountId32::from_str(s).ok(),
+        },
+        JsonValue::Object(map) if map.len() == 1 => map.get("Id").and_then(as_account),
+        _ => None,
+    }
+}
+
+/// The API client a request comes from, taken from the `X-Api-Client` header
+pub struct ApiClient(pub Option<String>);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ApiClient {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let client = parts
+            .headers
+            .get(API_CLIENT_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        Ok(Self(client))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const EXTERNAL: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+    fn policy(rules: JsonValue) -> Policy {
+        Policy {
+            rules: Some(Arc::new(serde_json::from_value(rules).unwrap())),
+        }
+    }
+
+    fn remark() -> CallInfo {
+        CallInfo::new("System", "remark", json!({ "remark": "0x" }))
+    }
+
+    #[test]
+    fn external_accounts_use_the_external_rules() {
+        let account = AccountId32::from_str(EXTERNAL).unwrap();
+        let policy = policy(json!({ "signers": {
+            "treasury": { "calls": { "Balances.*": {} } },
+            "external": { "calls": { "System.remark": {} } }
+        } }));
+
+        assert!(policy.authorize_external(&account, None, &remark()).is_ok());
+        // Managed signers never fall back to the external rules
+        assert!(policy.authorize("treasury", None, &remark()).is_err());
+        assert!(policy.authorize("unlisted", None, &remark()).is_err());
+    }
+
+    #[test]
+    fn external_accounts_are_denied_unless_covered() {
+        let account = AccountId32::from_str(EXTERNAL).unwrap();
+        let unlisted = policy(json!({ "signers": {
+            "treasury": { "calls": { "*": {} } }
+        } }));
+        assert!(unlisted
+            .authorize_external(&account, None, &remark())
+            .is_err());
+
+        // An account listed by address wins over the external rules
+        let listed = policy(json!({ "signers": {
+            EXTERNAL: { "calls": { "System.remark": {} } },
+            "external": { "calls": {} }
+        } }));
+        assert!(listed.authorize_external(&account, None, &remark()).is_ok());
+
+        assert!(Policy::allow_all()
+            .authorize_external(&account, None, &remark())
+            .is_ok());
+    }
+
+    fn transfer(dest: JsonValue, value: JsonValue) -> CallInfo {
+        CallInfo::new(
+            "Balances",
+            "transfer_keep_alive",
+            json!({ "dest": dest, "value": value }),
+        )
+    }
+
+    #[test]
+    fn signers_match_by_alias_address_and_user_wildcard() {
+        let policy = policy(json!({ "signers": {
+            "treasury": { "calls": { "System.remark": {} } },
+            EXTERNAL: { "calls": { "System.*": {} } },
+            "user:*": { "calls": { "System.remark": {} } },
+            "user:vip": { "calls": { "*": {} } }
+        } }));
+        let template = CallInfo::new("Template", "do_something", json!({ "something": 1 }));
+
+        assert!(policy.authorize("treasury", None, &remark()).is_ok());
+        assert!(policy.authorize("treasury", None, &template).is_err());
+        assert!(policy.authorize(EXTERNAL, None, &remark()).is_ok());
+        assert!(policy.authorize("user:42", None, &remark()).is_ok());
+        assert!(policy.authorize("user:42", None, &template).is_err());
+        // A user listed one by one doesn't get the wildcard's rules
+        assert!(policy.authorize("user:vip", None, &template).is_ok());
+    }
+
+    #[test]
+    fn unlisted_signers_are_denied() {
+        let policy = policy(json!({ "signers": {
+            "treasury": { "calls": { "*": {} } }
+        } }));
+        assert_eq!(
+            policy.authorize("payroll", None, &remark()),
+            Err("Signer 'payroll' is not covered by the policy".to_string())
+        );
+        // Without a "user:*" entry user accounts are not covered either
+        assert!(policy.authorize("user:42", None, &remark()).is_err());
+    }
+
+    #[test]
+    fn named_arguments_are_checked_against_the_constraints() {
+        let policy = policy(json!({ "signers": {
+            "treasury": { "calls": { "Balances.transfer_keep_alive": {
+                "max": { "value": "1000" },
+                "allowed": { "dest": [EXTERNAL] }
+            } } }
+        } }));
+        let dest = AccountId32::from_str(EXTERNAL).unwrap();
+
+        assert!(policy
+            .authorize("treasury", None, &transfer(json!(EXTERNAL), json!(1000)))
+            .is_ok());
+        // Accounts match in any encoding
+        let hex = format!("0x{}", hex::encode(dest.0));
+        assert!(policy
+            .authorize(
+                "treasury",
+                None,
+                &transfer(json!({ "Id": hex }), json!("5"))
+            )
+            .is_ok());
+
+        assert!(policy
+            .authorize("treasury", None, &transfer(json!(EXTERNAL), json!("1001")))
+            .is_err());
+        let other = AccountId32([7; 32]).to_string();
+        assert!(policy
+            .authorize("treasury", None, &transfer(json!(other), json!(1)))
+            .is_err());
+
+        let missing = CallInfo::new(
+            "Balances",
+            "transfer_keep_alive",
+            json!({ "dest": EXTERNAL }),
+        );
+        assert!(policy.authorize("treasury", None, &missing).is_err());
+        let positional = CallInfo::new("Balances", "transfer_keep_alive", json!([EXTERNAL, 1]));
+        assert!(policy.authorize("treasury", None, &positional).is_err());
+    }
+
+    #[test]
+    fn clients_are_restricted_by_the_api_client_header() {
+        let policy = policy(json!({ "signers": {
+            "treasury": { "clients": ["payouts"], "calls": { "*": {} } },
+            "faucet": { "calls": { "*": {} } }
+        } }));
+
+        assert!(policy
+            .authorize("treasury", Some("payouts"), &remark())
+            .is_ok());
+        assert!(policy
+            .authorize("treasury", Some("billing"), &remark())
+            .is_err());
+        assert!(policy.authorize("treasury", None, &remark()).is_err());
+        // Without a client list any client may use the signer
+        assert!(policy.authorize("faucet", None, &remark()).is_ok());
+    }
+}
@@ -0,0 +1,344 @@
+// src/receipts.rs
+//
+// Persistent transaction receipts
+//
+// Every extrinsic the backend signs gets a receipt in an embedded sled
+// database. The receipt is created when the transaction is signed and then
+// updated as it moves through the pool and into blocks, so support staff can
+// answer "what happened to my transaction?" long after the HTTP request that
+// submitted it has returned, and across restarts.
+//
+// Two trees are used:
+// - `receipts`:    transaction hash -> receipt (JSON)
+// - `account_txs`: account ++ nonce ++ transaction hash -> () (an index for listing by account)
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use subxt::utils::{AccountId32, H256};
+
+use crate::events::EventInfo;
+use crate::handlers::{TransactionResponse, TxStage};
+use crate::transaction::ManagedTransaction;
+
+/// Describes the call a transaction executes, for display in its receipt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallInfo {
+    /// The pallet name as it appears in metadata (e.g. "Template")
+    pub pallet: String,
+    /// The call name as it appears in metadata (e.g. "do_something")
+    pub call: String,
+    /// The call arguments as JSON
+    pub args: JsonValue,
+}
+
+impl CallInfo {
+    /// Creates the description of a call
+    pub fn new(pallet: impl Into<String>, call: impl Into<String>, args: JsonValue) -> Self {
+        Self {
+            pallet: pallet.into(),
+            call: call.into(),
+            args,
+        }
+    }
+}
+
+/// Lifecycle state recorded in a receipt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptStatus {
+    /// Signed, but not (yet) accepted by the node
+    Signed,
+    /// Accepted into the node's transaction pool
+    Submitted,
+    /// Included in a best (not yet finalized) block
+    InBlock,
+    /// Included in a finalized block and succeeded
+    Finalized,
+    /// Rejected, dropped, or failed during execution
+    Failed,
+    /// Taken out of the pool by a replacement at the same nonce (see `replaced_by`)
+    Replaced,
+}
+
+/// Unix timestamps (seconds) of each lifecycle step
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReceiptTimestamps {
+    /// When the transaction was signed
+    pub signed_at: u64,
+    /// When the node accepted it into the pool
+    pub submitted_at: Option<u64>,
+    /// When it was first seen in a block
+    pub in_block_at: Option<u64>,
+    /// When its block was finalized
+    pub finalized_at: Option<u64>,
+    /// When it was found to have failed
+    pub failed_at: Option<u64>,
+}
+
+/// Everything known about one submitted transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Receipt {
+    /// The transaction hash (hex string)
+    pub transaction_hash: String,
+    /// The signer's SS58 address
+    pub signer: String,
+    /// The nonce the transaction was signed with
+    pub nonce: u64,
+    /// The tip the transaction was signed with
+    #[serde(default)]
+    pub tip: u128,
+    /// Pallet of the executed call
+    pub pallet: String,
+    /// Name of the executed call
+    pub call: String,
+    /// Arguments of the executed call
+    pub args: JsonValue,
+    /// Current lifecycle state
+    pub status: ReceiptStatus,
+    /// Hash of the block the transaction was included in
+    pub block_hash: Option<String>,
+    /// Number of the block the transaction was included in
+    pub block_number: Option<u32>,
+    /// Position of the transaction among the block's extrinsics
+    pub extrinsic_index: Option<u32>,
+    /// Events emitted by the transaction
+    pub events: Option<Vec<EventInfo>>,
+    /// Error message if the transaction failed
+    pub error: Option<String>,
+    /// Hash of the transaction submitted to replace this one at the same nonce
+    #[serde(default)]
+    pub replaced_by: Option<String>,
+    /// Hash of the transaction this one was submitted to replace
+    #[serde(default)]
+    pub replaces: Option<String>,
+    /// When each lifecycle step happened
+    pub timestamps: ReceiptTimestamps,
+}
+
+/// Receipt database backed by sled
+///
+/// Cloning is cheap and all clones share the same database. Write failures
+/// are logged rather than returned: a receipt is a record of a transaction,
+/// and failing to write one must never fail the transaction itself.
+#[derive(Clone)]
+pub struct ReceiptStore {
+    /// Transaction hash -> receipt
+    receipts: sled::Tree,
+    /// Account ++ nonce ++ transaction hash -> ()
+    account_txs: sled::Tree,
+}
+
+impl ReceiptStore {
+    /// Opens (or creates) the receipt database at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, sled::Error> {
+        let db = sled::open(path)?;
+        Ok(Self {
+            receipts: db.open_tree("receipts")?,
+            account_txs: db.open_tree("account_txs")?,
+        })
+    }
+
+    /// Creates the receipt for a freshly signed transaction
+    ///
+    /// # Arguments
+    /// * `tx` - The signed transaction with its signer and nonce
+    /// * `call` - What the transaction does
+    pub fn record_signed(&self, tx: &ManagedTransaction, call: &CallInfo) {
+        let hash = tx.extrinsic.hash();
+        let receipt = Receipt {
+            transaction_hash: format!("{:?}", hash),
+            signer: tx.account_id.to_string(),
+            nonce: tx.nonce,
+            tip: tx.tip,
+            pallet: call.pallet.clone(),
+            call: call.call.clone(),
+            args: call.args.clone(),
+            status: ReceiptStatus::Signed,
+            block_hash: None,
+            block_number: None,
+            extrinsic_index: None,
+            events: None,
+            error: None,
+            replaced_by: None,
+            replaces: None,
+            timestamps: ReceiptTimestamps {
+                signed_at: unix_now(),
+                ..Default::default()
+            },
+        };
+
+        let mut index_key = Vec::with_capacity(72);
+        index_key.extend_from_slice(&tx.account_id.0);
+        index_key.extend_from_slice(&tx.nonce.to_be_bytes());
+        index_key.extend_from_slice(hash.as_bytes());
+
+        if let Err(e) = self.account_txs.insert(index_key, &[]) {
+            log::error!("❌ Failed to index receipt {:?}: {:?}", hash, e);
+        }
+        self.put(&hash, &receipt);
+    }
+
+    /// Records that the node accepted the transaction into its pool
+    pub fn mark_submitted(&self, hash: H256) {
+        self.update(hash, |receipt| {
+            receipt.status = ReceiptStatus::Submitted;
+            receipt.timestamps.submitted_at = Some(unix_now());
+        });
+    }
+
+    /// Records that the transaction was seen in a best block
+    pub fn mark_in_block(&self, hash: H256, block_hash: H256) {
+        self.update(hash, |receipt| {
+            receipt.status = ReceiptStatus::InBlock;
+            receipt.block_hash = Some(format!("{:?}", block_hash));
+            receipt.timestamps.in_block_at = Some(unix_now());
+        });
+    }
+
+    /// Records a successful inclusion, copying the block details and events
+    pub fn mark_included(&self, hash: H256, response: &TransactionResponse) {
+        self.update(hash, |receipt| {
+            let now = unix_now();
+            receipt.block_hash = response.block_hash.clone();
+            receipt.block_number = response.block_header.as_ref().map(|h| h.block_number);
+            receipt.extrinsic_index = response.extrinsic_index;
+            receipt.events = response.events.clone();
+            receipt.timestamps.in_block_at.get_or_insert(now);
+
+            if response.stage == Some(TxStage::Finalized) {
+                receipt.status = ReceiptStatus::Finalized;
+                receipt.timestamps.finalized_at = Some(now);
+            } else {
+                receipt.status = ReceiptStatus::InBlock;
+            }
+        });
+    }
+
+    /// Records that the transaction failed
+    ///
+    /// A replaced transaction keeps its `Replaced` status: being evicted by
+    /// its replacement is the expected way for it to fail.
+    pub fn mark_failed(&self, hash: H256, response: &TransactionResponse) {
+        self.update(hash, |receipt| {
+            if receipt.status != ReceiptStatus::Replaced {
+                receipt.status = ReceiptStatus::Failed;
+            }
+            receipt.error = response.error.clone();
+            if response.block_hash.is_some() {
+                receipt.block_hash = response.block_hash.clone();
+            }
+            receipt.timestamps.failed_at = Some(unix_now());
+        });
+    }
+
+    /// Links a transaction to the replacement submitted at its nonce
+    ///
+    /// Must be called before the replacement is submitted, so that whoever is
+    /// watching the original knows its eviction was intended.
+    pub fn link_replacement(&self, original: H256, replacement: H256) {
+        self.update(original, |receipt| {
+            receipt.replaced_by = Some(format!("{:?}", replacement));
+        });
+        self.update(replacement, |receipt| {
+            receipt.replaces = Some(format!("{:?}", original));
+        });
+    }
+
+    /// Undoes `link_replacement` after the replacement was rejected
+    pub fn unlink_replacement(&self, original: H256) {
+        self.update(original, |receipt| receipt.replaced_by = None);
+    }
+
+    /// Records that the replacement was included instead of the original
+    pub fn mark_replaced(&self, original: H256) {
+        self.update(original, |receipt| {
+            receipt.status = ReceiptStatus::Replaced;
+            receipt.timestamps.failed_at = Some(unix_now());
+        });
+    }
+
+    /// Returns the hash of the replacement a transaction was linked to, if any
+    pub fn replaced_by(&self, hash: H256) -> Option<String> {
+        self.get(&format!("{:?}", hash))?.replaced_by
+    }
+
+    /// Looks up a receipt by transaction hash (hex, with or without 0x)
+    pub fn get(&self, hash: &str) -> Option<Receipt> {
+        let bytes = hex::decode(hash.trim_start_matches("0x")).ok()?;
+        let raw = self.receipts.get(bytes).ok()??;
+        serde_json::from_slice(&raw).ok()
+    }
+
+    /// Lists an account's receipts, newest nonce first
+    ///
+    /// # Arguments
+    /// * `account_id` - The signer to list transactions for
+    /// * `limit` - Maximum number of receipts to return
+    pub fn list_for_account(&self, account_id: &AccountId32, limit: usize) -> Vec<Receipt> {
+        self.account_txs
+            .scan_prefix(account_id.0)
+            .keys()
+            .rev()
+            .filter_map(|key| key.ok())
+            .filter_map(|key| self.receipts.get(&key[40..]).ok().flatten())
+            .filter_map(|raw| serde_json::from_slice(&raw).ok())
+            .take(limit)
+            .collect()
+    }
+
+    /// Applies a change to an existing receipt
+    ///
+    /// The read-modify-write is atomic: sled re-runs `apply` on the latest
+    /// version if another update got in between, so concurrent updates (e.g.
+    /// a status change racing a replacement link) never overwrite each other.
+    fn update(&self, hash: H256, apply: impl Fn(&mut Receipt)) {
+        let result = self.receipts.update_and_fetch(hash.as_bytes(), |raw| {
+            // Not every transaction has a receipt (e.g. dry runs)
+            let raw = raw?;
+            let mut receipt = match serde_json::from_slice::<Receipt>(raw) {
+                Ok(receipt) => receipt,
+                Err(e) => {
+                    log::error!("❌ Corrupt receipt {:?}: {:?}", hash, e);
+                    return Some(raw.to_vec());
+                }
+            };
+            apply(&mut receipt);
+            match serde_json::to_vec(&receipt) {
+                Ok(updated) => Some(updated),
+                Err(e) => {
+                    log::error!("❌ Failed to encode receipt {:?}: {:?}", hash, e);
+                    Some(raw.to_vec())
+                }
+            }
+        });
+        if let Err(e) = result {
+            log::error!("❌ Failed to update receipt {:?}: {:?}", hash, e);
+        }
+    }
+
+    /// Writes a receipt
+    fn put(&self, hash: &H256, receipt: &Receipt) {
+        let result = serde_json::to_vec(receipt)
+            .map_err(|e| e.to_string())
+            .and_then(|raw| {
+                self.receipts
+                    .insert(hash.as_bytes(), raw)
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            log::error!("❌ Failed to write receipt {:?}: {}", hash, e);
+        }
+    }
+}
+
+/// Current Unix time in seconds
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,240 @@
+// src/relay.rs
+//
+// Relaying extrinsics signed by clients
+//
+// Users who sign in their own wallets never hand their seeds to the backend.
+// They send the finished, SCALE-encoded extrinsic instead, and the backend
+// only relays it. Before relaying, the extrinsic is decoded against the
+// runtime metadata, which both validates its shape and tells us who signed
+// it, which call it makes and which nonce it uses. That is everything a
+// receipt needs, so relayed transactions are tracked exactly like the ones
+// the backend signs itself.
+//
+// The backend doesn't manage the nonces of relayed transactions, so they are
+// never re-signed: a relayed transaction that fails for anything but a
+// transient reason fails for good.
+
+use serde::{Deserialize, Serialize};
+use subxt::{
+    ext::{
+        codec::{Compact, Decode},
+        scale_decode::Field,
+        scale_value,
+        subxt_core::blocks::Extrinsics,
+    },
+    tx::SubmittableExtrinsic,
+    utils::{AccountId32, MultiAddress},
+    Metadata, OnlineClient, SubstrateConfig,
+};
+
+use crate::events::fields_to_json;
+use crate::handlers::TxStage;
+use crate::receipts::CallInfo;
+use crate::transaction::{ManagedTransaction, SigningPath};
+
+/// Request payload for the /tx/submit-raw endpoint
+#[derive(Debug, Deserialize)]
+pub struct SubmitRawRequest {
+    /// The signed extrinsic, SCALE-encoded (with its length prefix) as 0x-prefixed hex
+    pub extrinsic: String,
+    /// Lifecycle stage to wait for before responding (defaults to "finalized")
+    #[serde(default)]
+    pub wait_for: TxStage,
+}
+
+/// What decoding a client-signed extrinsic revealed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodedExtrinsic {
+    /// The signer's SS58 address
+    pub signer: String,
+    /// The nonce the extrinsic was signed with
+    pub nonce: u64,
+    /// The tip the extrinsic pays
+    pub tip: u128,
+    /// The call the extrinsic makes, with its arguments decoded to JSON
+    pub call: CallInfo,
+}
+
+/// Decodes a client-signed extrinsic and prepares it for submission
+///
+/// The extrinsic must be signed, use an account ID as its address, and decode
+/// completely against the metadata (no unknown pallet or call, no trailing
+/// bytes). The signature itself is checked by the node when the extrinsic is
+/// submitted.
+///
+/// # Arguments
+/// * `client` - The blockchain client the extrinsic will be submitted through
+/// * `hex_extrinsic` - The extrinsic as hex, with or without `0x`
+///
+/// # Returns
+/// * `Ok((ManagedTransaction, DecodedExtrinsic))` - The submittable transaction and its decoded details
+/// * `Err(String)` - Why the extrinsic was rejected
+pub fn decode_signed_extrinsic(
+    client: &OnlineClient<SubstrateConfig>,
+    hex_extrinsic: &str,
+) -> Result<(ManagedTransaction, DecodedExtrinsic), String> {
+    let bytes = hex::decode(hex_extrinsic.trim().trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid extrinsic hex: {}", e))?;
+
+    // The length prefix must cover exactly the rest of the bytes
+    let cursor = &mut bytes.as_slice();
+    let Compact(length) = Compact::<u32>::decode(cursor)
+        .map_err(|e| format!("Invalid extrinsic length prefix: {}", e))?;
+    if length as usize != cursor.len() {
+        return Err(format!(
+            "Extrinsic length prefix says {} bytes, but {} follow",
+            length,
+            cursor.len()
+        ));
+    }
+
+    let metadata = client.metadata();
+    let extrinsics =
+        Extrinsics::<SubstrateConfig>::decode_from(vec![bytes.clone()], metadata.clone())
+            .map_err(|e| format!("Failed to decode extrinsic: {:?}", e))?;
+    let details = extrinsics
+        .iter()
+        .next()
+        .ok_or("Failed to decode extrinsic")?
+        .map_err(|e| format!("Failed to decode extrinsic: {:?}", e))?;
+
+    if !details.is_signed() {
+        return Err("Extrinsic is not signed".to_string());
+    }
+
+    // Who signed it: only plain account IDs can be tracked per account
+    let address_bytes = details.address_bytes().ok_or("Extrinsic has no address")?;
+    let account_id = match MultiAddress::<AccountId32, ()>::decode(&mut &address_bytes[..]) {
+        Ok(MultiAddress::Id(account_id)) => account_id,
+        Ok(_) => return Err("Only account ID addresses are supported".to_string()),
+        Err(e) => return Err(format!("Invalid signer address: {}", e)),
+    };
+
+    let extensions = details
+        .signed_extensions()
+        .ok_or("Extrinsic has no signed extensions")?;
+    let nonce = extensions.nonce().ok_or("Extrinsic has no nonce")?;
+    let tip = extensions.tip().unwrap_or_default();
+
+    let pallet = details
+        .pallet_name()
+        .map_err(|e| format!("Unknown pallet: {:?}", e))?;
+    let call = details
+        .variant_name()
+        .map_err(|e| format!("Unknown call: {:?}", e))?;
+    let args = decode_call_args(&metadata, details.field_bytes(), pallet, call)?;
+
+    let decoded = DecodedExtrinsic {
+        signer: account_id.to_string(),
+        nonce,
+        tip,
+        call: CallInfo::new(pallet, call, args),
+    };
+    let tx = ManagedTransaction {
+        extrinsic: SubmittableExtrinsic::from_bytes(client.clone(), bytes),
+        account_id,
+        nonce,
+        tip,
+        reserved: false,
+        signing_path: SigningPath::External,
+        lease: None,
+    };
+
+    Ok((tx, decoded))
+}
+
+/// Decodes a call's argument bytes to JSON, requiring every byte to be used
+fn decode_call_args(
+    metadata: &Metadata,
+    mut field_bytes: &[u8],
+    pallet: &str,
+    call: &str,
+) -> Result<serde_json::Value, String> {
+    let variant = metadata
+        .pallet_by_name(pallet)
+        .and_then(|p| p.call_variant_by_name(call))
+        .ok_or_else(|| format!("Unknown call {}.{}", pallet, call))?;
+
+    let mut fields = variant
+        .fields
+        .iter()
+        .map(|f| Field::new(f.ty.id, f.name.as_deref()));
+    let values =
+        scale_value::scale::decode_as_fields(&mut field_bytes, &mut fields, metadata.types())
+            .map_err(|e| format!("Failed to decode {}.{} arguments: {}", pallet, call, e))?;
+
+    if !field_bytes.is_empty() {
+        return Err(format!(
+            "{} unexpected trailing byte(s) after the {}.{} arguments",
+            field_bytes.len(),
+            pallet,
+            call
+        ));
+    }
+
+    // Calls without arguments are reported with an empty object, like other call args
+    if variant.fields.is_empty() {
+        return Ok(serde_json::json!({}));
+    }
+    Ok(fields_to_json(&values, metadata))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::chain_a;
+    use serde_json::json;
+    use subxt::tx::Payload;
+
+    fn metadata() -> Metadata {
+        Metadata::decode(&mut &include_bytes!("metadata.scale")[..]).expect("valid metadata")
+    }
+
+    /// The argument bytes of a call, without its pallet and call index
+    fn arg_bytes(call: &impl Payload, metadata: &Metadata) -> Vec<u8> {
+        call.encode_call_data(metadata).expect("encodable call")[2..].to_vec()
+    }
+
+    #[test]
+    fn call_args_decode_to_json() {
+        let metadata = metadata();
+        let args = arg_bytes(&chain_a::tx().template().do_something(42), &metadata);
+        assert_eq!(
+            decode_call_args(&metadata, &args, "Template", "do_something"),
+            Ok(json!({ "something": 42 }))
+        );
+
+        let dest = AccountId32([1; 32]);
+        let transfer = chain_a::tx()
+            .balances()
+            .transfer_keep_alive(dest.clone().into(), 5);
+        assert_eq!(
+            decode_call_args(
+                &metadata,
+                &arg_bytes(&transfer, &metadata),
+                "Balances",
+                "transfer_keep_alive"
+            ),
+            Ok(json!({ "dest": { "Id": dest.to_string() }, "value": 5 }))
+        );
+    }
+
+    #[test]
+    fn call_args_must_be_used_up() {
+        let metadata = metadata();
+        let mut args = arg_bytes(&chain_a::tx().template().do_something(42), &metadata);
+
+        args.push(0);
+        assert_eq!(
+            decode_call_args(&metadata, &args, "Template", "do_something"),
+            Err(
+                "1 unexpected trailing byte(s) after the Template.do_something arguments"
+                    .to_string()
+            )
+        );
+
+        args.truncate(2);
+        assert!(decode_call_args(&metadata, &args, "Template", "do_something").is_err());
+        assert!(decode_call_args(&metadata, &[], "Template", "no_such_call").is_err());
+    }
+}
@@ -0,0 +1,439 @@
+// src/replace.rs
+//
+// Replacing and cancelling pending transactions
+//
+// A transaction that is still in the pool can be displaced by another
+// transaction from the same account with the same nonce and a higher
+// priority. Priority grows with the tip, so re-signing at the original nonce
+// with a higher tip swaps the pooled transaction out:
+// - Replace -> the new transaction carries a different call
+// - Cancel  -> the new transaction is a System.remark that does nothing
+//
+// Only one transaction per nonce can ever be included, so exactly one of the
+// two wins. Which one it was is recorded in both receipts. The nonce manager
+// is never touched: the nonce was reserved for the original, whose submitter
+// still settles it, and a nonce neither transaction ends up using is left to
+// the periodic gap check.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use subxt::utils::{AccountId32, H256};
+
+use crate::handlers::{watch_progress, AppState, TransactionResponse, TxFailure, TxStage};
+use crate::receipts::{CallInfo, Receipt, ReceiptStatus};
+use crate::signer::TxSigner;
+use crate::transaction::{
+    create_signed_transaction_with_nonce, ManagedTransaction, SigningStrategy, TxParams,
+};
+
+/// Minimum tip increase over the original, in percent, when no tip is given
+const DEFAULT_TIP_BUMP_PERCENT: u128 = 10;
+
+/// Request payload for the /tx/{hash}/replace endpoint
+#[derive(Debug, Deserialize)]
+pub struct ReplaceRequest {
+    /// Pallet of the new call (e.g. "Template")
+    pub pallet: String,
+    /// Name of the new call (e.g. "do_something")
+    pub call: String,
+    /// Arguments of the new call, in the same format as POST /tx/{pallet}/{call}
+    #[serde(default)]
+    pub args: serde_json::Value,
+    /// Signer alias; must be the signer of the original transaction (defaults to `DEFAULT_SIGNER`)
+    pub signer: Option<String>,
+    /// Tip for the replacement; must exceed the original's (defaults to the original's plus 10%)
+    pub tip: Option<u128>,
+    /// Optional mortality period in blocks, 0 for immortal (defaults to server config)
+    pub mortality: Option<u64>,
+    /// Lifecycle stage to wait for before responding (defaults to "finalized")
+    #[serde(default)]
+    pub wait_for: TxStage,
+}
+
+/// Request payload for the /tx/{hash}/cancel endpoint
+#[derive(Debug, Deserialize)]
+pub struct CancelRequest {
+    /// Signer alias; must be the signer of the original transaction (defaults to `DEFAULT_SIGNER`)
+    pub signer: Option<String>,
+    /// Tip for the cancellation; must exceed the original's (defaults to the original's plus 10%)
+    pub tip: Option<u128>,
+    /// Optional mortality period in blocks, 0 for immortal (defaults to server config)
+    pub mortality: Option<u64>,
+    /// Lifecycle stage to wait for before responding (defaults to "finalized")
+    #[serde(default)]
+    pub wait_for: TxStage,
+}
+
+/// Which transaction ended up using the contested nonce
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplaceWinner {
+    /// The original transaction was included before it could be replaced
+    Original,
+    /// The replacement (or cancellation) was included
+    Replacement,
+}
+
+/// Response payload for the /tx/{hash}/replace and /tx/{hash}/cancel endpoints
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplaceResponse {
+    /// Whether the replacement was included and succeeded
+    pub success: bool,
+    /// Hash of the transaction being replaced
+    pub original_hash: String,
+    /// Hash of the replacement (null if it was never signed)
+    pub replacement_hash: Option<String>,
+    /// The contested nonce
+    pub nonce: Option<u64>,
+    /// The replacement's tip
+    pub tip: Option<u128>,
+    /// Which transaction used the nonce (null while undecided or if neither did)
+    pub winner: Option<ReplaceWinner>,
+    /// The replacement's result, in the same format as single submissions
+    pub result: Option<TransactionResponse>,
+    /// Error message if the replacement failed
+    pub error: Option<String>,
+}
+
+impl ReplaceResponse {
+    /// Builds a failed response for a replacement that was never submitted
+    pub fn failure(original_hash: impl Into<String>, error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            original_hash: original_hash.into(),
+            replacement_hash: None,
+            nonce: None,
+            tip: None,
+            winner: None,
+            result: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Why a replacement could not be attempted
+#[derive(Debug)]
+pub enum ReplaceError {
+    /// No receipt exists for the original transaction
+    NotFound,
+    /// The original is no longer pending (already included, failed or replaced)
+    NotPending(ReceiptStatus),
+    /// The request is invalid (wrong signer, tip too low)
+    BadRequest(String),
+    /// The signer's policy doesn't allow the new call
+    Forbidden(String),
+}
+
+/// Checks that a transaction can be replaced by `signer` and resolves the tip
+///
+/// Without a requested tip, the original's is raised by
+/// `DEFAULT_TIP_BUMP_PERCENT`, rounded up and by at least 1.
+///
+/// # Returns
+/// * `Ok(u128)` - The tip to sign the replacement with
+/// * `Err(ReplaceError)` - If the original isn't pending, was signed by
+///   another account, or the requested tip doesn't exceed its tip
+fn replacement_tip(
+    receipt: &Receipt,
+    signer: &AccountId32,
+    tip: Option<u128>,
+) -> Result<u128, ReplaceError> {
+    // Once in a block the nonce is spoken for; a replacement would only be rejected
+    if !matches!(
+        receipt.status,
+        ReceiptStatus::Signed | ReceiptStatus::Submitted
+    ) || receipt.replaced_by.is_some()
+    {
+        return Err(ReplaceError::NotPending(receipt.status));
+    }
+
+    if signer.to_string() != receipt.signer {
+        return Err(ReplaceError::BadRequest(
+            "Signer does not match the original transaction".to_string(),
+        ));
+    }
+
+    // The pool only swaps transactions for one with a strictly higher priority
+    let min_tip = receipt.tip.saturating_add(1);
+    let tip = tip.unwrap_or_else(|| {
+        let bump = receipt
+            .tip
+            .saturating_mul(DEFAULT_TIP_BUMP_PERCENT)
+            .div_ceil(100);
+        receipt.tip.saturating_add(bump.max(1))
+    });
+    if tip < min_tip {
+        return Err(ReplaceError::BadRequest(format!(
+            "Tip must be at least {} to replace a transaction with tip {}",
+            min_tip, receipt.tip
+        )));
+    }
+    Ok(tip)
+}
+
+/// Re-signs a call at the nonce of a pending transaction and submits it
+///
+/// # Arguments
+/// * `state` - Shared application state
+/// * `original` - Hash of the pending transaction to replace (hex)
+/// * `signer` - The signer of the original transaction
+/// * `call` - The new call (or a System.remark to cancel)
+/// * `call_info` - Description of the new call for its receipt
+/// * `tip` - Requested tip, or `None` to outbid the original by 10%
+/// * `mortality` - Optional mortality period override
+/// * `wait_for` - The lifecycle stage to wait for
+///
+/// # Returns
+/// * `Ok(ReplaceResponse)` - The outcome, including which transaction won
+/// * `Err(ReplaceError)` - If the original can't be replaced
+#[allow(clippy::too_many_arguments)]
+pub async fn replace_transaction<Call>(
+    state: &AppState,
+    original: &str,
+    signer: Arc<dyn TxSigner>,
+    call: &Call,
+    call_info: CallInfo,
+    tip: Option<u128>,
+    mortality: Option<u64>,
+    wait_for: TxStage,
+) -> Result<ReplaceResponse, ReplaceError>
+where
+    Call: subxt::tx::Payload,
+{
+    let receipt = state.receipts.get(original).ok_or(ReplaceError::NotFound)?;
+    let original_hash: H256 = receipt
+        .transaction_hash
+        .parse()
+        .map_err(|_| ReplaceError::NotFound)?;
+
+    let account_id = signer.account_id();
+    let tip = replacement_tip(&receipt, &account_id, tip)?;
+
+    let params = TxParams {
+        tip,
+        ..state.config.tx_params(mortality, None)
+    };
+    let nonce = receipt.nonce;
+    let mut response = ReplaceResponse {
+        success: false,
+        original_hash: receipt.transaction_hash.clone(),
+        replacement_hash: None,
+        nonce: Some(nonce),
+        tip: Some(tip),
+        winner: None,
+        result: None,
+        error: None,
+    };
+
+    // Sign at the original's nonce; it is already reserved, so the nonce
+    // manager isn't asked for a new one. Any other nonce would not replace
+    // anything, so there is no fallback.
+    let signed = match create_signed_transaction_with_nonce(
+        &state.client,
+        &state.rpc,
+        call,
+        signer.as_ref(),
+        nonce,
+        &params,
+        SigningStrategy::Strict,
+    )
+    .await
+    {
+        Ok(signed) => signed,
+        Err(e) => {
+            log::error!("❌ Failed to sign replacement: {:?}", e);
+            response.error = Some(format!("Failed to create transaction: {:?}", e));
+            return Ok(response);
+        }
+    };
+    state.metrics.record_signing(signed.path);
+    let tx = ManagedTransaction {
+        extrinsic: signed.extrinsic,
+        account_id,
+        nonce,
+        tip,
+        reserved: true,
+        signing_path: signed.path,
+        // The original's submitter holds the lease of this nonce
+        lease: None,
+    };
+    let replacement_hash = tx.extrinsic.hash();
+    response.replacement_hash = Some(format!("{:?}", replacement_hash));
+    state.receipts.record_signed(&tx, &call_info);
+
+    // Link first: the original's watcher must know its eviction is intended
+    state
+        .receipts
+        .link_replacement(original_hash, replacement_hash);
+    log::info!(
+        "🔀 Replacing {:?} with {:?} at nonce {} (tip {} -> {})",
+        original_hash,
+        replacement_hash,
+        nonce,
+        receipt.tip,
+        tip
+    );
+
+    let progress = match tx.extrinsic.submit_and_watch().await {
+        Ok(progress) => progress,
+        Err(e) => {
+            // The pool kept the original; it carries on as if nothing happened
+            state.receipts.unlink_replacement(original_hash);
+            log::error!("❌ Replacement rejected: {:?}", e);
+
+            let mut result = TransactionResponse::failure(format!("Failed to submit: {:?}", e));
+            result.transaction_hash = response.replacement_hash.clone();
+            state.receipts.mark_failed(replacement_hash, &result);
+
+            if original_included(state, original_hash) {
+                response.winner = Some(ReplaceWinner::Original);
+            }
+            response.error = result.error.clone();
+            response.result = Some(result);
+            return Ok(response);
+        }
+    };
+    state.receipts.mark_submitted(replacement_hash);
+
+    let result = match watch_progress(state, progress, wait_for, None).await {
+        Ok(result) => {
+            state.receipts.mark_replaced(original_hash);
+            response.success = true;
+            response.winner = Some(ReplaceWinner::Replacement);
+            result
+        }
+        Err(TxFailure::DispatchFailed(result)) => {
+            // Included, so it won the nonce even though its call failed
+            state.receipts.mark_replaced(original_hash);
+            response.winner = Some(ReplaceWinner::Replacement);
+            result
+        }
+        Err(TxFailure::NotIncluded(kind, result)) => {
+            // The nonce stays with the original's submitter; if the original
+            // was evicted too, the gap check fills the nonce
+            state.receipts.unlink_replacement(original_hash);
+            if original_included(state, original_hash) {
+                response.winner = Some(ReplaceWinner::Original);
+            }
+            log::warn!(
+                "⚠️ Replacement of {:?} at nonce {} was not included ({:?})",
+                original_hash,
+                nonce,
+                kind
+            );
+            result
+        }
+        Err(TxFailure::Unknown(result)) => result,
+    };
+
+    log::info!(
+        "🔀 Replacement of {:?} finished, winner: {:?}",
+        original_hash,
+        response.winner
+    );
+    response.error = result.error.clone();
+    response.result = Some(result);
+    Ok(response)
+}
+
+/// Returns true if the original transaction's receipt shows it in a block
+fn original_included(state: &AppState, original: H256) -> bool {
+    state
+        .receipts
+        .get(&format!("{:?}", original))
+        .map(|receipt| {
+            matches!(
+                receipt.status,
+                ReceiptStatus::InBlock | ReceiptStatus::Finalized
+            )
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::LocalSigner;
+
+    fn pending(signer: &AccountId32, tip: u128) -> Receipt {
+        Receipt {
+            transaction_hash: format!("{:?}", H256::zero()),
+            signer: signer.to_string(),
+            nonce: 7,
+            tip,
+            pallet: "System".to_string(),
+            call: "remark".to_string(),
+            args: serde_json::json!({ "remark": "0x" }),
+            status: ReceiptStatus::Submitted,
+            block_hash: None,
+            block_number: None,
+            extrinsic_index: None,
+            events: None,
+            error: None,
+            replaced_by: None,
+            replaces: None,
+            timestamps: Default::default(),
+        }
+    }
+
+    fn account(suri: &str) -> AccountId32 {
+        LocalSigner::from_suri(suri).unwrap().account_id()
+    }
+
+    #[test]
+    fn default_tips_are_bumped_by_ten_percent_rounded_up() {
+        let alice = account("//Alice");
+        let tip = |original| replacement_tip(&pending(&alice, original), &alice, None).unwrap();
+
+        assert_eq!(tip(0), 1);
+        assert_eq!(tip(5), 6);
+        assert_eq!(tip(99), 109);
+        assert_eq!(tip(100), 110);
+        assert_eq!(tip(1_001), 1_102);
+        assert_eq!(tip(u128::MAX - 1), u128::MAX);
+    }
+
+    #[test]
+    fn requested_tips_must_exceed_the_original() {
+        let alice = account("//Alice");
+        let receipt = pending(&alice, 100);
+
+        assert_eq!(replacement_tip(&receipt, &alice, Some(101)).unwrap(), 101);
+        assert!(matches!(
+            replacement_tip(&receipt, &alice, Some(100)),
+            Err(ReplaceError::BadRequest(_))
+        ));
+        assert!(matches!(
+            replacement_tip(&receipt, &alice, Some(0)),
+            Err(ReplaceError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn only_the_original_signer_replaces_pending_transactions() {
+        let alice = account("//Alice");
+        let receipt = pending(&alice, 100);
+
+        assert!(matches!(
+            replacement_tip(&receipt, &account("//Bob"), Some(200)),
+            Err(ReplaceError::BadRequest(message)) if message.contains("Signer")
+        ));
+
+        let included = Receipt {
+            status: ReceiptStatus::InBlock,
+            ..receipt.clone()
+        };
+        assert!(matches!(
+            replacement_tip(&included, &alice, Some(200)),
+            Err(ReplaceError::NotPending(ReceiptStatus::InBlock))
+        ));
+        let replaced = Receipt {
+            replaced_by: Some(format!("{:?}", H256::repeat_byte(1))),
+            ..receipt
+        };
+        assert!(matches!(
+            replacement_tip(&replaced, &alice, Some(200)),
+            Err(ReplaceError::NotPending(_))
+        ));
+    }
+}
@@ -0,0 +1,556 @@
+// src/retry.rs
+//
+// Retry and resubmission policy for transaction submissions
+//
+// Not every failed submission is final. The failure is classified first and
+// the classification decides what happens next:
+// - Transient (RPC hiccup, dropped from the pool) -> submit the same signed bytes again
+// - Stale / Future / Usurped (nonce problems)     -> re-sign with a freshly reconciled nonce
+// - Invalid (bad signature, can't pay fees, ...)  -> fail immediately
+//
+// Submission errors are classified by the JSON-RPC error code the pool
+// answers with, status updates by the fixed messages subxt reports them with.
+// - Dispatch error (included but the call failed) -> fail immediately; the nonce is used
+// - Usurped by a requested replacement            -> stop; the replacement owns the nonce
+//
+// Attempts are spaced with exponential backoff and capped by the configured
+// maximum. Every attempt is reported in the response.
+
+use jsonrpsee::core::client::Error as ClientError;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use subxt::{error::RpcError, tx::TxProgress, OnlineClient, SubstrateConfig};
+
+use crate::handlers::{
+    sign_with_managed_nonce, watch_progress, AppState, TransactionResponse, TxFailure, TxStage,
+};
+use crate::jobs::JobStatus;
+use crate::receipts::CallInfo;
+use crate::signer::TxSigner;
+use crate::transaction::{ManagedTransaction, RawCall, SigningPath, TxParams};
+
+/// How often and how patiently failed submissions are retried
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between retries
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Delay before the attempt following attempt number `attempt` (1-based)
+    ///
+    /// Doubles with every attempt, starting at `initial_backoff` and never
+    /// exceeding `max_backoff`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// JSON-RPC error codes of the node's transaction pool (`author_*` methods)
+mod pool_error {
+    /// The transaction is invalid; the data names the `InvalidTransaction` reason
+    pub const INVALID_TX: i32 = 1010;
+    /// The same transaction is already in the pool
+    pub const ALREADY_IMPORTED: i32 = 1013;
+    /// A transaction with the same nonce and a higher priority is in the pool
+    pub const TOO_LOW_PRIORITY: i32 = 1014;
+    /// The pool is full and the transaction was dropped right away
+    pub const IMMEDIATELY_DROPPED: i32 = 1016;
+}
+
+/// `InvalidTransaction::Stale`, as the node describes it in an `INVALID_TX` error
+const INVALID_TX_STALE: &str = "Transaction is outdated";
+/// `InvalidTransaction::Future`, as the node describes it in an `INVALID_TX` error
+const INVALID_TX_FUTURE: &str = "Transaction will be valid in the future";
+
+/// The message subxt reports a usurped transaction's `Invalid` status with
+const STATUS_USURPED: &str = "Transaction was usurped by another with the same nonce";
+/// The message subxt reports a finality timeout's `Invalid` status with
+const STATUS_FINALITY_TIMEOUT: &str = "Finality timeout";
+
+/// Returns the JSON-RPC error object the node answered a request with, if any
+fn rpc_error_object(error: &subxt::Error) -> Option<&jsonrpsee::types::ErrorObjectOwned> {
+    match error {
+        subxt::Error::Rpc(RpcError::ClientError(e)) => match e.downcast_ref::<ClientError>() {
+            Some(ClientError::Call(object)) => Some(object),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether the pool rejected a submission because the same bytes are already in it
+pub fn is_already_imported(error: &subxt::Error) -> bool {
+    rpc_error_object(error).is_some_and(|object| object.code() == pool_error::ALREADY_IMPORTED)
+}
+
+/// Why a transaction didn't make it into a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    /// Connection or RPC problem, or dropped from the pool; the same bytes can be resubmitted
+    Transient,
+    /// The nonce was already used on chain
+    Stale,
+    /// The nonce is ahead of the account's next nonce
+    Future,
+    /// Another transaction with the same nonce took this one's place
+    Usurped,
+    /// The transaction itself is invalid and will never be accepted
+    Invalid,
+}
+
+impl FailureKind {
+    /// Classifies an error returned when submitting a transaction
+    ///
+    /// Errors the node answered with are classified by their JSON-RPC code
+    /// (see `from_pool_error`). Errors that never got an answer (connection
+    /// problems, a dropped subscription) are transient; anything else subxt
+    /// reports is invalid.
+    pub fn from_submit_error(error: &subxt::Error) -> Self {
+        if let Some(object) = rpc_error_object(error) {
+            let data = object
+                .data()
+                .and_then(|data| serde_json::from_str::<String>(data.get()).ok());
+            return Self::from_pool_error(object.code(), data.as_deref());
+        }
+        match error {
+            subxt::Error::Rpc(
+                RpcError::ClientError(_)
+                | RpcError::SubscriptionDropped
+                | RpcError::DisconnectedWillReconnect(_),
+            ) => Self::Transient,
+            _ => Self::Invalid,
+        }
+    }
+
+    /// Classifies a pool error by its JSON-RPC code and data
+    ///
+    /// `INVALID_TX` errors carry the `InvalidTransaction` reason as their
+    /// data; only the nonce-related reasons are worth another attempt.
+    fn from_pool_error(code: i32, data: Option<&str>) -> Self {
+        match (code, data) {
+            (pool_error::INVALID_TX, Some(INVALID_TX_STALE)) => Self::Stale,
+            (pool_error::INVALID_TX, Some(INVALID_TX_FUTURE)) => Self::Future,
+            (pool_error::TOO_LOW_PRIORITY, _) => Self::Usurped,
+            (pool_error::IMMEDIATELY_DROPPED, _) => Self::Transient,
+            _ => Self::Invalid,
+        }
+    }
+
+    /// Classifies the message of an `Invalid` transaction status
+    pub fn from_invalid_status(message: &str) -> Self {
+        match message {
+            STATUS_USURPED => Self::Usurped,
+            STATUS_FINALITY_TIMEOUT => Self::Transient,
+            _ => Self::Invalid,
+        }
+    }
+
+    /// Whether another attempt can succeed
+    pub fn is_retryable(self) -> bool {
+        !matches!(self, Self::Invalid)
+    }
+}
+
+/// How the transaction of an attempt was produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttemptAction {
+    /// The originally signed transaction
+    Initial,
+    /// The same signed bytes, submitted again
+    Resubmit,
+    /// The call signed again with a new nonce
+    Resign,
+}
+
+/// One submission attempt, as reported in the response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionAttempt {
+    /// Attempt number, starting at 1
+    pub attempt: u32,
+    /// How the submitted transaction was produced
+    pub action: AttemptAction,
+    /// The nonce the transaction was signed with
+    pub nonce: u64,
+    /// How the transaction was signed (explicit nonce, fallback or by the client)
+    pub signing_path: SigningPath,
+    /// The hash of the submitted transaction
+    pub transaction_hash: String,
+    /// Why the attempt failed (null if it succeeded or failed during execution)
+    pub failure: Option<FailureKind>,
+    /// Error message if the attempt failed
+    pub error: Option<String>,
+}
+
+/// Everything needed to sign the same call again with a different nonce
+#[derive(Clone)]
+pub struct Resigner {
+    /// The signer of the original transaction
+    pub signer: Arc<dyn TxSigner>,
+    /// The SCALE-encoded call
+    pub call_data: Vec<u8>,
+    /// Mortality and tip of the original transaction
+    pub params: TxParams,
+    /// Description of the call for the new transaction's receipt
+    pub call_info: CallInfo,
+}
+
+impl Resigner {
+    /// Reserves a new nonce and signs the call with it
+    async fn sign(&self, state: &AppState) -> Result<ManagedTransaction, String> {
+        let call = RawCall(self.call_data.clone());
+        let tx = sign_with_managed_nonce(state, &self.signer, &call, &self.params).await?;
+        state.receipts.record_signed(&tx, &self.call_info);
+        Ok(tx)
+    }
+}
+
+/// Submits a transaction, retrying according to the configured policy
+///
+/// # Arguments
+/// * `state` - Shared application state
+/// * `tx` - The signed transaction and the nonce it reserved
+/// * `resigner` - Used to sign the call again after nonce problems; `None` for
+///   transactions signed by clients, which can only be resubmitted unchanged
+/// * `wait_for` - The lifecycle stage to wait for
+/// * `job_id` - The async job to mirror progress into, if any
+///
+/// # Returns
+/// The response of the last attempt, with every attempt listed in `attempts`
+pub async fn submit_with_retry(
+    state: &AppState,
+    mut tx: ManagedTransaction,
+    resigner: Option<&Resigner>,
+    wait_for: TxStage,
+    job_id: Option<&str>,
+) -> TransactionResponse {
+    let policy = state.config.retry;
+    let mut attempts = Vec::new();
+    let mut action = AttemptAction::Initial;
+    let mut attempt = 1;
+
+    loop {
+        let mut record = SubmissionAttempt {
+            attempt,
+            action,
+            nonce: tx.nonce,
+            signing_path: tx.signing_path,
+            transaction_hash: format!("{:?}", tx.extrinsic.hash()),
+            failure: None,
+            error: None,
+        };
+
+        let (kind, mut response) = match submit_once(state, &tx, wait_for, job_id).await {
+            Ok(mut response) => {
+                attempts.push(record);
+                response.attempts = attempts;
+                return response;
+            }
+            Err(TxFailure::NotIncluded(kind, response)) => (kind, response),
+            Err(TxFailure::DispatchFailed(mut response)) => {
+                // The call ran and failed: retrying can't help, and the nonce is used up
+                record.error = response.error.clone();
+                attempts.push(record);
+                response.attempts = attempts;
+                return response;
+            }
+            Err(TxFailure::Unknown(mut response)) => {
+                // The transaction may still be included, so its nonce stays reserved
+                record.error = response.error.clone();
+                attempts.push(record);
+                response.attempts = attempts;
+                return response;
+            }
+        };
+
+        // A replacement submitted through /tx/{hash}/replace or /cancel took the
+        // nonce on purpose: the replacement owns it now, so don't fight it
+        if kind == FailureKind::Usurped {
+            if let Some(replacement) = state.receipts.replaced_by(tx.extrinsic.hash()) {
+                log::info!("🔀 Transaction was replaced by {}", replacement);
+                response.error = Some(format!("Replaced by {}", replacement));
+                record.failure = Some(kind);
+                record.error = response.error.clone();
+                attempts.push(record);
+                response.attempts = attempts;
+                return response;
+            }
+        }
+
+        record.failure = Some(kind);
+        record.error = response.error.clone();
+        attempts.push(record);
+
+        // Without a resigner only the original bytes can be sent again
+        let retryable = match resigner {
+            Some(_) => kind.is_retryable(),
+            None => kind == FailureKind::Transient,
+        };
+        if !retryable || attempt >= policy.max_attempts {
+            if tx.reserved {
+                release_nonce(state, &tx, kind).await;
+            }
+            // Nobody is going to submit the nonce anymore: if it is still
+            // missing from the pool, it is up to the gap check now
+            drop(tx.lease.take());
+            log::error!(
+                "❌ Giving up after {} attempt(s): {:?} ({:?})",
+                attempt,
+                kind,
+                response.error
+            );
+            response.attempts = attempts;
+            return response;
+        }
+
+        let delay = policy.backoff(attempt);
+        log::warn!(
+            "🔁 Attempt {} failed ({:?}), retrying in {:?}",
+            attempt,
+            kind,
+            delay
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+
+        if kind == FailureKind::Transient {
+            // The signed bytes are still good: send them again
+            action = AttemptAction::Resubmit;
+            continue;
+        }
+
+        // Nonce problem: let the chain decide the nonce, then sign again
+        if let Some(resigner) = resigner {
+            if let Err(e) = state
+                .nonce_manager
+                .reconcile(&tx.account_id, tx.nonce)
+                .await
+            {
+                log::error!("❌ Failed to reconcile nonce: {:?}", e);
+            }
+            tx = match resigner.sign(state).await {
+                Ok(tx) => tx,
+                Err(e) => {
+                    let mut response = TransactionResponse::failure(e);
+                    response.attempts = attempts;
+                    return response;
+                }
+            };
+            action = AttemptAction::Resign;
+        }
+
+        if let Some(job_id) = job_id {
+            let hash = format!("{:?}", tx.extrinsic.hash());
+            state
+                .jobs
+                .set_status(job_id, JobStatus::Pending, Some(hash), None)
+                .await;
+        }
+    }
+}
+
+/// Submits a transaction once and follows it to `wait_for`
+async fn submit_once(
+    state: &AppState,
+    tx: &ManagedTransaction,
+    wait_for: TxStage,
+    job_id: Option<&str>,
+) -> Result<TransactionResponse, TxFailure> {
+    let hash = tx.extrinsic.hash();
+
+    let progress: TxProgress<SubstrateConfig, OnlineClient<SubstrateConfig>> =
+        match tx.extrinsic.submit_and_watch().await {
+            Ok(progress) => progress,
+            Err(e) => {
+                log::error!("❌ Failed to submit transaction: {:?}", e);
+                let mut response =
+                    TransactionResponse::failure(format!("Failed to submit: {:?}", e));
+                response.transaction_hash = Some(format!("{:?}", hash));
+                state.receipts.mark_failed(hash, &response);
+
+                // Resubmitted bytes that are already in the pool can't be watched
+                // again, but they are on their way
+                if is_already_imported(&e) {
+                    return Err(TxFailure::Unknown(response));
+                }
+                return Err(TxFailure::NotIncluded(
+                    FailureKind::from_submit_error(&e),
+                    response,
+                ));
+            }
+        };
+
+    state.receipts.mark_submitted(hash);
+    if let Some(job_id) = job_id {
+        state
+            .jobs
+            .set_status(job_id, JobStatus::Submitted, None, None)
+            .await;
+    }
+
+    watch_progress(state, progress, wait_for, job_id).await
+}
+
+/// Settles the nonce of a transaction that was given up on
+///
+/// - Transient: the signed bytes may still reach the pool (e.g. they were
+///   sent before the connection broke), so the nonce stays reserved; if it
+///   never shows up, the gap check fills it once the deadline has passed
+/// - Stale / Future / Usurped: the pool disagreed with the nonce, so the
+///   cache is reconciled with the chain's view
+/// - Invalid: nobody will use the nonce; it is handed back if no later
+///   nonce was reserved in the meantime
+async fn release_nonce(state: &AppState, tx: &ManagedTransaction, kind: FailureKind) {
+    match kind {
+        FailureKind::Transient => {
+            log::warn!(
+                "⏳ Keeping nonce {} of account {:?} reserved, the transaction may still land",
+                tx.nonce,
+                tx.account_id
+            );
+        }
+        FailureKind::Stale | FailureKind::Future | FailureKind::Usurped => {
+            if let Err(e) = state
+                .nonce_manager
+                .reconcile(&tx.account_id, tx.nonce)
+                .await
+            {
+                log::error!("❌ Failed to reconcile nonce: {:?}", e);
+            }
+        }
+        FailureKind::Invalid => {
+            state
+                .nonce_manager
+                .release_nonce(&tx.account_id, tx.nonce)
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::types::ErrorObjectOwned;
+
+    /// An error like the one the node answers a rejected submission with
+    fn pool_error(code: i32, data: Option<&str>) -> subxt::Error {
+        let object = ErrorObjectOwned::owned(code, "Invalid Transaction", data);
+        subxt::Error::Rpc(RpcError::ClientError(Box::new(ClientError::Call(object))))
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(3),
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(2));
+        assert_eq!(policy.backoff(4), Duration::from_secs(3));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn submit_errors_are_classified_by_code() {
+        let cases = [
+            (
+                pool_error::INVALID_TX,
+                Some(INVALID_TX_STALE),
+                FailureKind::Stale,
+            ),
+            (
+                pool_error::INVALID_TX,
+                Some(INVALID_TX_FUTURE),
+                FailureKind::Future,
+            ),
+            (
+                pool_error::INVALID_TX,
+                Some("Transaction has a bad signature"),
+                FailureKind::Invalid,
+            ),
+            (pool_error::INVALID_TX, None, FailureKind::Invalid),
+            (pool_error::TOO_LOW_PRIORITY, None, FailureKind::Usurped),
+            (
+                pool_error::IMMEDIATELY_DROPPED,
+                None,
+                FailureKind::Transient,
+            ),
+            (pool_error::ALREADY_IMPORTED, None, FailureKind::Invalid),
+            // The reason is only trusted under its own code
+            (1011, Some(INVALID_TX_STALE), FailureKind::Invalid),
+        ];
+        for (code, data, kind) in cases {
+            assert_eq!(
+                FailureKind::from_submit_error(&pool_error(code, data)),
+                kind,
+                "code {} data {:?}",
+                code,
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn errors_without_an_answer_are_transient() {
+        let dropped = subxt::Error::Rpc(RpcError::SubscriptionDropped);
+        assert_eq!(
+            FailureKind::from_submit_error(&dropped),
+            FailureKind::Transient
+        );
+
+        let transport =
+            subxt::Error::Rpc(RpcError::ClientError(Box::new(ClientError::RequestTimeout)));
+        assert_eq!(
+            FailureKind::from_submit_error(&transport),
+            FailureKind::Transient
+        );
+
+        let other = subxt::Error::Other("Transaction is outdated".into());
+        assert_eq!(FailureKind::from_submit_error(&other), FailureKind::Invalid);
+    }
+
+    #[test]
+    fn already_imported_is_recognised_by_code() {
+        assert!(is_already_imported(&pool_error(
+            pool_error::ALREADY_IMPORTED,
+            None
+        )));
+        assert!(!is_already_imported(&pool_error(
+            pool_error::INVALID_TX,
+            None
+        )));
+        assert!(!is_already_imported(&subxt::Error::Other(
+            "Already Imported".into()
+        )));
+    }
+
+    #[test]
+    fn invalid_statuses_are_classified_by_message() {
+        assert_eq!(
+            FailureKind::from_invalid_status(STATUS_USURPED),
+            FailureKind::Usurped
+        );
+        assert_eq!(
+            FailureKind::from_invalid_status(STATUS_FINALITY_TIMEOUT),
+            FailureKind::Transient
+        );
+        assert_eq!(
+            FailureKind::from_invalid_status(
+                "Transaction is invalid (eg because of a bad nonce, signature etc)"
+            ),
+            FailureKind::Invalid
+        );
+    }
+}
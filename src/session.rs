@@ -0,0 +1,287 @@
+// src/session.rs
+//
+// Session key rotation for the validators we operate
+//
+// Rotating a validator's session keys takes three steps:
+// 1. `author_rotateKeys` on the local node generates fresh keys in the node's
+//    keystore and returns their public halves, SCALE-encoded as the runtime's
+//    `SessionKeys`
+// 2. `Session.set_keys(keys, proof)` registers them for the validator's
+//    account. The signer policy is checked against the actual keys and proof,
+//    so it can only happen once the keys exist; a denied rotation leaves
+//    unregistered keys in the node's keystore, which are never used. The
+//    call is signed by the validator's signer through the usual nonce-managed
+//    path, retries included.
+// 3. Once the transaction is in a block, `Session.NextKeys` is read back to
+//    confirm the keys were stored. They only move to `Session.QueuedKeys` at
+//    the next session change, which the response reports separately.
+//
+// The node's RPC must allow unsafe methods for `author_rotateKeys`.
+
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::Arc};
+use subxt::{
+    ext::codec::{Decode, Encode},
+    tx::Payload,
+    utils::{AccountId32, H256},
+};
+
+use crate::chain::chain_a;
+use crate::handlers::{sign_with_managed_nonce, AppState, TransactionResponse, TxStage};
+use crate::receipts::CallInfo;
+use crate::retry::{submit_with_retry, Resigner};
+use crate::signer::TxSigner;
+use crate::transaction::TxParams;
+
+/// The runtime's `SessionKeys`, as taken by `Session.set_keys`
+type SessionKeys = chain_a::session::calls::types::set_keys::Keys;
+
+/// Request payload for the /validators/{signer}/rotate-keys endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateKeysRequest {
+    /// Optional mortality period in blocks (0 for immortal; defaults to `TX_MORTALITY_PERIOD`)
+    pub mortality: Option<u64>,
+    /// Optional tip (defaults to `TX_DEFAULT_TIP`)
+    pub tip: Option<u128>,
+    /// Lifecycle stage to wait for before confirming (`in_block` or `finalized`, the default)
+    #[serde(default)]
+    pub wait_for: TxStage,
+}
+
+/// Response of the /validators/{signer}/rotate-keys endpoint
+#[derive(Debug, Serialize)]
+pub struct RotateKeysResponse {
+    /// Whether the keys were rotated, registered and confirmed in storage
+    pub success: bool,
+    /// The validator's SS58 address
+    pub validator: Option<String>,
+    /// The new session keys as returned by `author_rotateKeys` (0x-prefixed hex)
+    pub session_keys: Option<String>,
+    /// Outcome of the `Session.set_keys` transaction
+    pub transaction: Option<TransactionResponse>,
+    /// Whether `Session.NextKeys` holds the new keys
+    pub next_keys_confirmed: bool,
+    /// Whether `Session.QueuedKeys` holds the new keys yet (only after the next session change)
+    pub queued: bool,
+    /// Error message if any step failed
+    pub error: Option<String>,
+}
+
+impl RotateKeysResponse {
+    /// Builds a failed response carrying only an error message
+    pub fn failure(error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            validator: None,
+            session_keys: None,
+            transaction: None,
+            next_keys_confirmed: false,
+            queued: false,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Rotates a validator's session keys and registers them on chain
+///
+/// # Arguments
+/// * `state` - Shared application state
+/// * `signer_name` - The signer's name in the signer policy
+/// * `client` - The API client from the `X-Api-Client` header, if any
+/// * `signer` - The validator's signer; its account is the validator ID
+/// * `params` - Mortality and tip of the `set_keys` transaction
+/// * `wait_for` - `InBlock` or `Finalized`; the keys are read back at that block
+///
+/// # Returns
+/// * `Ok(RotateKeysResponse)` - The new keys, the transaction outcome and what
+///   storage says about them
+/// * `Err(RotateKeysResponse)` - The signer's policy doesn't allow registering
+///   the new keys; nothing was submitted
+pub async fn rotate_session_keys(
+    state: &AppState,
+    signer_name: &str,
+    client: Option<&str>,
+    signer: Arc<dyn TxSigner>,
+    params: TxParams,
+    wait_for: TxStage,
+) -> Result<RotateKeysResponse, RotateKeysResponse> {
+    if wait_for == TxStage::Submitted {
+        return Ok(RotateKeysResponse::failure(
+            "wait_for must be in_block or finalized to confirm the keys in storage",
+        ));
+    }
+    let validator = signer.account_id();
+
+    // The node generates the keys; only their public halves come back
+    let raw_keys = match state.rpc.author_rotate_keys().await {
+        Ok(keys) => keys,
+        Err(e) => {
+            log::error!("❌ author_rotateKeys failed: {:?}", e);
+            return Ok(RotateKeysResponse::failure(format!(
+                "author_rotateKeys failed: {}",
+                e
+            )));
+        }
+    };
+    let session_keys = format!("0x{}", hex::encode(&raw_keys));
+    log::info!("🔐 Rotated session keys of {}: {}", validator, session_keys);
+
+    let mut response = RotateKeysResponse {
+        success: false,
+        validator: Some(validator.to_string()),
+        session_keys: Some(session_keys.clone()),
+        transaction: None,
+        next_keys_confirmed: false,
+        queued: false,
+        error: None,
+    };
+
+    let keys = match decode_session_keys(&raw_keys) {
+        Ok(keys) => keys,
+        Err(e) => {
+            response.error = Some(e);
+            return Ok(response);
+        }
+    };
+
+    // The proof of ownership is empty: the runtime doesn't check it
+    let call = chain_a::tx().session().set_keys(keys, Vec::new());
+    let call_info = CallInfo::new(
+        "Session",
+        "set_keys",
+        serde_json::json!({ "keys": session_keys, "proof": "0x" }),
+    );
+    if let Err(e) = state.policy.authorize(signer_name, client, &call_info) {
+        response.error = Some(e);
+        return Err(response);
+    }
+    let call_data = match call.encode_call_data(&state.client.metadata()) {
+        Ok(call_data) => call_data,
+        Err(e) => {
+            response.error = Some(format!("Failed to encode call: {:?}", e));
+            return Ok(response);
+        }
+    };
+
+    // Same nonce-managed path as every other submission
+    let tx = match sign_with_managed_nonce(state, &signer, &call, &params).await {
+        Ok(tx) => tx,
+        Err(e) => {
+            response.error = Some(e);
+            return Ok(response);
+        }
+    };
+    state.receipts.record_signed(&tx, &call_info);
+    let resigner = Resigner {
+        signer,
+        call_data,
+        params,
+        call_info,
+    };
+    let outcome = submit_with_retry(state, tx, Some(&resigner), wait_for, None).await;
+
+    let block_hash = outcome
+        .block_hash
+        .as_deref()
+        .and_then(|hash| H256::from_str(hash).ok());
+    let succeeded = outcome.success;
+    let error = outcome.error.clone();
+    response.transaction = Some(outcome);
+    let block_hash = match block_hash {
+        Some(block_hash) if succeeded => block_hash,
+        _ => {
+            response.error = Some(format!(
+                "Session.set_keys failed: {}",
+                error.unwrap_or_default()
+            ));
+            return Ok(response);
+        }
+    };
+
+    match confirm_keys(state, &validator, &raw_keys, block_hash).await {
+        Ok((next_keys_confirmed, queued)) => {
+            response.next_keys_confirmed = next_keys_confirmed;
+            response.queued = queued;
+            response.success = next_keys_confirmed;
+            if !next_keys_confirmed {
+                response.error = Some("Session.NextKeys does not hold the new keys".to_string());
+            }
+        }
+        Err(e) => {
+            log::error!("❌ Failed to read session keys: {:?}", e);
+            response.error = Some(format!("Failed to read session keys: {}", e));
+        }
+    }
+    log::info!(
+        "🔐 Session keys of {}: next_keys_confirmed={}, queued={}",
+        validator,
+        response.next_keys_confirmed,
+        response.queued
+    );
+    Ok(response)
+}
+
+/// Decodes the keys returned by `author_rotateKeys` as the runtime's `SessionKeys`
+///
+/// Every byte must be used: keys for another runtime could decode from a
+/// prefix of the bytes and register only part of what the node generated.
+fn decode_session_keys(raw_keys: &[u8]) -> Result<SessionKeys, String> {
+    match SessionKeys::decode(&mut &raw_keys[..]) {
+        Ok(keys) if keys.encoded_size() == raw_keys.len() => Ok(keys),
+        _ => Err("The node returned keys that don't match the runtime's SessionKeys".to_string()),
+    }
+}
+
+/// Checks `Session.NextKeys` and `Session.QueuedKeys` for the new keys
+///
+/// # Returns
+/// * `Ok((bool, bool))` - Whether the keys are in NextKeys and in QueuedKeys
+/// * `Err(subxt::Error)` - If storage couldn't be read
+async fn confirm_keys(
+    state: &AppState,
+    validator: &AccountId32,
+    raw_keys: &[u8],
+    block_hash: H256,
+) -> Result<(bool, bool), subxt::Error> {
+    let storage = state.client.storage().at(block_hash);
+
+    let next_keys = storage
+        .fetch(&chain_a::storage().session().next_keys(validator.clone()))
+        .await?;
+    let confirmed = next_keys.is_some_and(|keys| keys.encode() == raw_keys);
+
+    let queued_keys = storage
+        .fetch(&chain_a::storage().session().queued_keys())
+        .await?
+        .unwrap_or_default();
+    let queued = queued_keys
+        .iter()
+        .any(|(account, keys)| account == validator && keys.encode() == raw_keys);
+
+    Ok((confirmed, queued))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Length of the runtime's encoded `SessionKeys`
+    fn keys_len() -> usize {
+        SessionKeys::decode(&mut &[0u8; 1024][..])
+            .unwrap()
+            .encoded_size()
+    }
+
+    #[test]
+    fn session_keys_must_use_every_byte() {
+        let len = keys_len();
+        let raw_keys: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let keys = decode_session_keys(&raw_keys).unwrap();
+        assert_eq!(keys.encode(), raw_keys);
+
+        assert!(decode_session_keys(&[]).is_err());
+        assert!(decode_session_keys(&raw_keys[..len - 1]).is_err());
+        let longer = [raw_keys.as_slice(), &[0]].concat();
+        assert!(decode_session_keys(&longer).is_err());
+    }
+}
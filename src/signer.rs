@@ -0,0 +1,459 @@
+// src/signer.rs
+//
+// Signer backends
+//
+// Everything that signs transactions goes through the `TxSigner` trait, so
+// keys don't have to live in this process:
+// - LocalSigner  -> an sr25519, ed25519 or ecdsa key pair held in memory
+//                   (from the keystore, or a raw secret URI in dev mode)
+// - RemoteSigner -> a key held by a signing service (an HSM or KMS gateway)
+//                   that is sent the signer payload over HTTP and returns
+//                   the signature
+//
+// Transactions are built around the signature: the signer payload is
+// produced first, handed to the signer, and the returned signature is
+// attached afterwards (see `transaction::create_signed_transaction_with_nonce`).
+// The signature's scheme picks the `MultiSignature` variant, and the account
+// is derived from the public key the same way the runtime does it, so the
+// nonce manager tracks the right `AccountId32` for every scheme.
+//
+// Remote signing service API:
+// - GET  {url}/keys -> [{ "alias": "treasury", "public_key": "0x...", "scheme": "sr25519" }]
+// - POST {url}/sign    { "alias": "treasury", "payload": "0x..." }
+//                   -> { "signature": "0x..." }
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use subxt::{
+    ext::sp_core::{
+        ecdsa, ed25519, hashing::blake2_256, sr25519, DeriveJunction, Pair as PairTrait,
+    },
+    utils::{AccountId32, MultiSignature},
+};
+
+/// How long a remote signer may take to answer
+const REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Signature scheme of a key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureScheme {
+    /// Schnorrkel signature (64 bytes)
+    #[default]
+    Sr25519,
+    /// Ed25519 signature (64 bytes)
+    Ed25519,
+    /// Recoverable secp256k1 signature (65 bytes)
+    Ecdsa,
+}
+
+impl SignatureScheme {
+    /// Splits an optional `<scheme>:` prefix off a secret URI
+    ///
+    /// A secret URI never contains a colon before its first `/`, so the
+    /// prefix can't be mistaken for part of the secret. Without a prefix the
+    /// key is sr25519.
+    pub fn split_suri(suri: &str) -> (Self, &str) {
+        match suri.split_once(':') {
+            Some(("sr25519", rest)) => (Self::Sr25519, rest),
+            Some(("ed25519", rest)) => (Self::Ed25519, rest),
+            Some(("ecdsa", rest)) => (Self::Ecdsa, rest),
+            _ => (Self::Sr25519, suri),
+        }
+    }
+}
+
+/// Where a signer's key lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignerBackend {
+    /// The key pair is held in this process
+    Local,
+    /// The key is held by a remote signing service
+    Remote,
+}
+
+/// Anything that can sign transaction payloads for one account
+#[async_trait]
+pub trait TxSigner: Send + Sync {
+    /// The account the signatures are valid for
+    fn account_id(&self) -> AccountId32;
+
+    /// The public key, in the encoding of its scheme
+    fn public_key(&self) -> Vec<u8>;
+
+    /// The key's signature scheme
+    fn scheme(&self) -> SignatureScheme;
+
+    /// Where the key lives
+    fn backend(&self) -> SignerBackend;
+
+    /// Derives a child signer along hard junctions, like appending
+    /// `//a//b` to the secret URI would
+    ///
+    /// # Returns
+    /// * `Ok(Arc<dyn TxSigner>)` - The derived signer
+    /// * `Err(String)` - If this signer can't derive keys
+    fn derive(&self, _hard_junctions: &[&str]) -> Result<Arc<dyn TxSigner>, String> {
+        Err(format!("{:?} signers cannot derive keys", self.backend()))
+    }
+
+    /// Signs a transaction's signer payload
+    ///
+    /// # Returns
+    /// * `Ok(MultiSignature)` - The signature, ready to be attached
+    /// * `Err(String)` - Why the payload could not be signed
+    async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, String>;
+}
+
+/// A signer whose key pair is held in memory
+///
+/// The pairs are boxed; they differ a lot in size.
+pub enum LocalSigner {
+    /// An sr25519 key pair
+    Sr25519(Box<sr25519::Pair>),
+    /// An ed25519 key pair
+    Ed25519(Box<ed25519::Pair>),
+    /// A secp256k1 key pair
+    Ecdsa(Box<ecdsa::Pair>),
+}
+
+impl LocalSigner {
+    /// Parses a secret URI, optionally prefixed with its scheme
+    ///
+    /// `"ed25519://Alice"` and `"ecdsa://Alice"` select the scheme; a plain
+    /// `"//Alice"` is an sr25519 key.
+    pub fn from_suri(suri: &str) -> Result<Self, String> {
+        let (scheme, suri) = SignatureScheme::split_suri(suri);
+        Self::with_scheme(suri, scheme)
+    }
+
+    /// Parses a secret URI ("//Alice", a mnemonic, a hex seed, optionally
+    /// with derivation paths) as a key pair of the given scheme
+    ///
+    /// The error never contains the secret URI.
+    pub fn with_scheme(suri: &str, scheme: SignatureScheme) -> Result<Self, String> {
+        let signer = match scheme {
+            SignatureScheme::Sr25519 => {
+                sr25519::Pair::from_string(suri, None).map(|pair| Self::Sr25519(Box::new(pair)))
+            }
+            SignatureScheme::Ed25519 => {
+                ed25519::Pair::from_string(suri, None).map(|pair| Self::Ed25519(Box::new(pair)))
+            }
+            SignatureScheme::Ecdsa => {
+                ecdsa::Pair::from_string(suri, None).map(|pair| Self::Ecdsa(Box::new(pair)))
+            }
+        };
+        signer.map_err(|_| format!("Invalid {:?} secret URI", scheme))
+    }
+}
+
+#[async_trait]
+impl TxSigner for LocalSigner {
+    fn account_id(&self) -> AccountId32 {
+        match self {
+            Self::Sr25519(pair) => AccountId32(pair.public().0),
+            Self::Ed25519(pair) => AccountId32(pair.public().0),
+            Self::Ecdsa(pair) => AccountId32(blake2_256(&pair.public().0)),
+        }
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        match self {
+            Self::Sr25519(pair) => pair.public().0.to_vec(),
+            Self::Ed25519(pair) => pair.public().0.to_vec(),
+            Self::Ecdsa(pair) => pair.public().0.to_vec(),
+        }
+    }
+
+    fn scheme(&self) -> SignatureScheme {
+        match self {
+            Self::Sr25519(_) => SignatureScheme::Sr25519,
+            Self::Ed25519(_) => SignatureScheme::Ed25519,
+            Self::Ecdsa(_) => SignatureScheme::Ecdsa,
+        }
+    }
+
+    fn backend(&self) -> SignerBackend {
+        SignerBackend::Local
+    }
+
+    fn derive(&self, hard_junctions: &[&str]) -> Result<Arc<dyn TxSigner>, String> {
+        // "/x" is the hard junction "//x" of a secret URI, so the result is
+        // the same key `Pair::from_string` resolves for "<suri>//a//b"
+        let path = hard_junctions
+            .iter()
+            .map(|junction| DeriveJunction::from(format!("/{}", junction)));
+        let derived = match self {
+            Self::Sr25519(pair) => pair
+                .derive(path, None)
+                .map(|(pair, _)| Self::Sr25519(Box::new(pair))),
+            Self::Ed25519(pair) => pair
+                .derive(path, None)
+                .map(|(pair, _)| Self::Ed25519(Box::new(pair))),
+            Self::Ecdsa(pair) => pair
+                .derive(path, None)
+                .map(|(pair, _)| Self::Ecdsa(Box::new(pair))),
+        };
+        derived
+            .map(|signer| Arc::new(signer) as Arc<dyn TxSigner>)
+            .map_err(|_| "Key derivation failed".to_string())
+    }
+
+    async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, String> {
+        // ecdsa signs the blake2 hash of the payload, which is what the
+        // runtime recovers the public key from
+        Ok(match self {
+            Self::Sr25519(pair) => MultiSignature::Sr25519(pair.sign(payload).0),
+            Self::Ed25519(pair) => MultiSignature::Ed25519(pair.sign(payload).0),
+            Self::Ecdsa(pair) => MultiSignature::Ecdsa(pair.sign(payload).0),
+        })
+    }
+}
+
+/// A key as advertised by a remote signing service
+#[derive(Debug, Deserialize)]
+struct RemoteKey {
+    /// The name the service knows the key by
+    alias: String,
+    /// The public key as 0x-prefixed hex
+    public_key: String,
+    /// The key's signature scheme
+    #[serde(default)]
+    scheme: SignatureScheme,
+}
+
+/// Body of a remote signing request
+#[derive(Debug, Serialize)]
+struct RemoteSignRequest<'a> {
+    /// The key to sign with
+    alias: &'a str,
+    /// The signer payload as 0x-prefixed hex
+    payload: String,
+}
+
+/// Body of a remote signing response
+#[derive(Debug, Deserialize)]
+struct RemoteSignResponse {
+    /// The signature as 0x-prefixed hex
+    signature: String,
+}
+
+/// A signer whose key is held by a remote signing service
+pub struct RemoteSigner {
+    /// Shared HTTP client
+    http: reqwest::Client,
+    /// Base URL of the signing service
+    url: String,
+    /// The name the service knows the key by
+    alias: String,
+    /// The key's signature scheme
+    scheme: SignatureScheme,
+    /// The advertised public key
+    public_key: Vec<u8>,
+    /// The account derived from the public key
+    account_id: AccountId32,
+}
+
+impl RemoteSigner {
+    /// Fetches the keys a signing service offers and wraps each in a signer
+    ///
+    /// # Arguments
+    /// * `url` - Base URL of the signing service
+    ///
+    /// # Returns
+    /// * `Ok(Vec<(String, Arc<dyn TxSigner>)>)` - The signers, keyed by the service's aliases
+    /// * `Err(String)` - If the service can't be reached or advertises an invalid key
+    pub async fn discover(url: &str) -> Result<Vec<(String, Arc<dyn TxSigner>)>, String> {
+        let url = url.trim_end_matches('/').to_string();
+        let http = reqwest::Client::builder()
+            .timeout(REMOTE_SIGNER_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+        let keys: Vec<RemoteKey> = http
+            .get(format!("{}/keys", url))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Failed to list keys of remote signer {}: {}", url, e))?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid key list from remote signer {}: {}", url, e))?;
+
+        keys.into_iter()
+            .map(|key| {
+                let public_key = hex::decode(key.public_key.trim_start_matches("0x"))
+                    .map_err(|e| format!("Invalid public key for '{}': {}", key.alias, e))?;
+                let account_id = account_id_from_public(key.scheme, &public_key)
+                    .map_err(|e| format!("Invalid public key for '{}': {}", key.alias, e))?;
+                let signer: Arc<dyn TxSigner> = Arc::new(RemoteSigner {
+                    http: http.clone(),
+                    url: url.clone(),
+                    alias: key.alias.clone(),
+                    scheme: key.scheme,
+                    public_key,
+                    account_id,
+                });
+                Ok((key.alias, signer))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl TxSigner for RemoteSigner {
+    fn account_id(&self) -> AccountId32 {
+        self.account_id.clone()
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+
+    fn scheme(&self) -> SignatureScheme {
+        self.scheme
+    }
+
+    fn backend(&self) -> SignerBackend {
+        SignerBackend::Remote
+    }
+
+    async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, String> {
+        let request = RemoteSignRequest {
+            alias: &self.alias,
+            payload: format!("0x{}", hex::encode(payload)),
+        };
+        let response: RemoteSignResponse = self
+            .http
+            .post(format!("{}/sign", self.url))
+            .json(&request)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Remote signer failed for '{}': {}", self.alias, e))?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid response from remote signer: {}", e))?;
+
+        let bytes = hex::decode(response.signature.trim_start_matches("0x"))
+            .map_err(|e| format!("Invalid signature hex from remote signer: {}", e))?;
+        let signature = parse_signature(&bytes, self.scheme)?;
+
+        // Don't let a misbehaving signer get an invalid transaction into the pool
+        if !verify_signature(&signature, payload, &self.account_id) {
+            return Err(format!(
+                "Remote signer returned an invalid signature for '{}'",
+                self.alias
+            ));
+        }
+        Ok(signature)
+    }
+}
+
+/// Derives the on-chain account of a public key
+///
+/// Sr25519 and ed25519 accounts are their public keys; an ecdsa account is
+/// the blake2 hash of the compressed public key.
+pub fn account_id_from_public(
+    scheme: SignatureScheme,
+    public_key: &[u8],
+) -> Result<AccountId32, String> {
+    match scheme {
+        SignatureScheme::Sr25519 | SignatureScheme::Ed25519 => <[u8; 32]>::try_from(public_key)
+            .map(AccountId32)
+            .map_err(|_| format!("expected 32 bytes, got {}", public_key.len())),
+        SignatureScheme::Ecdsa if public_key.len() == 33 => Ok(AccountId32(blake2_256(public_key))),
+        SignatureScheme::Ecdsa => Err(format!(
+            "expected 33 bytes (compressed), got {}",
+            public_key.len()
+        )),
+    }
+}
+
+/// Wraps raw signature bytes in the chain's signature type
+pub fn parse_signature(bytes: &[u8], scheme: SignatureScheme) -> Result<MultiSignature, String> {
+    let invalid_length = |expected: usize| {
+        format!(
+            "A {:?} signature is {} bytes, got {}",
+            scheme,
+            expected,
+            bytes.len()
+        )
+    };
+    match scheme {
+        SignatureScheme::Sr25519 => bytes
+            .try_into()
+            .map(MultiSignature::Sr25519)
+            .map_err(|_| invalid_length(64)),
+        SignatureScheme::Ed25519 => bytes
+            .try_into()
+            .map(MultiSignature::Ed25519)
+            .map_err(|_| invalid_length(64)),
+        SignatureScheme::Ecdsa => bytes
+            .try_into()
+            .map(MultiSignature::Ecdsa)
+            .map_err(|_| invalid_length(65)),
+    }
+}
+
+/// Checks that `signature` over `message` was made by `account_id`
+///
+/// For ecdsa the public key is recovered from the signature and hashed to
+/// compare it with the account.
+pub fn verify_signature(
+    signature: &MultiSignature,
+    message: &[u8],
+    account_id: &AccountId32,
+) -> bool {
+    match signature {
+        MultiSignature::Sr25519(raw) => sr25519::Pair::verify(
+            &sr25519::Signature::from_raw(*raw),
+            message,
+            &sr25519::Public::from_raw(account_id.0),
+        ),
+        MultiSignature::Ed25519(raw) => ed25519::Pair::verify(
+            &ed25519::Signature::from_raw(*raw),
+            message,
+            &ed25519::Public::from_raw(account_id.0),
+        ),
+        MultiSignature::Ecdsa(raw) => ecdsa::Signature::from_raw(*raw)
+            .recover(message)
+            .map(|public| blake2_256(public.as_ref()) == account_id.0)
+            .unwrap_or(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_signer;
+
+    #[tokio::test]
+    async fn remote_signer_signs_through_the_mock_service() {
+        let keys = mock_signer::parse_keys("remote-alice=//Alice,remote-bob=//Bob").unwrap();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let app = mock_signer::router(keys);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let signers = RemoteSigner::discover(&url).await.unwrap();
+        let aliases: Vec<_> = signers.iter().map(|(alias, _)| alias.as_str()).collect();
+        assert_eq!(aliases, ["remote-alice", "remote-bob"]);
+
+        let (_, signer) = &signers[0];
+        let alice = LocalSigner::from_suri("//Alice").unwrap();
+        assert_eq!(signer.account_id(), alice.account_id());
+        assert_eq!(signer.backend(), SignerBackend::Remote);
+
+        let payload = b"signer payload";
+        let signature = signer.sign(payload).await.unwrap();
+        assert!(verify_signature(&signature, payload, &alice.account_id()));
+        assert!(!verify_signature(
+            &signature,
+            b"another payload",
+            &alice.account_id()
+        ));
+    }
+}
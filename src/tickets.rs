@@ -0,0 +1,339 @@
+// src/tickets.rs
+//
+// Two-step signing for external signers
+//
+// Some clients can sign bytes (a hardware wallet, an HSM, a browser
+// extension) but can't build extrinsics. For them signing is split in two:
+// 1. POST /tx/prepare builds the transaction body with a reserved nonce and
+//    returns the bytes to sign together with an opaque ticket
+// 2. POST /tx/complete takes the ticket and the signature, assembles the
+//    extrinsic and submits it like any other transaction
+//
+// A ticket holds a nonce reservation, so tickets expire and every account may
+// only hold a limited number of open tickets. An expired ticket's nonce is
+// handed back to the nonce manager if nothing was reserved after it.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use subxt::{
+    tx::PartialExtrinsic,
+    utils::{AccountId32, MultiAddress},
+    OnlineClient, SubstrateConfig,
+};
+use tokio::sync::Mutex;
+
+use crate::handlers::TxStage;
+use crate::nonce_manager::{NonceLease, NonceManager};
+use crate::receipts::CallInfo;
+use crate::signer::{parse_signature, verify_signature, SignatureScheme};
+use crate::transaction::{
+    create_partial_transaction, ManagedTransaction, RawCall, SigningParams, SigningPath,
+};
+
+/// Request payload for the /tx/prepare endpoint
+#[derive(Debug, Deserialize)]
+pub struct PrepareRequest {
+    /// Pallet name as it appears in metadata (e.g. "Balances")
+    pub pallet: String,
+    /// Call name as it appears in metadata (e.g. "transfer_keep_alive")
+    pub call: String,
+    /// Call arguments, in the same format as POST /tx/{pallet}/{call}
+    #[serde(default)]
+    pub args: serde_json::Value,
+    /// The account that will sign, as an SS58 address or 0x-prefixed hex
+    pub account: String,
+    /// Optional mortality period in blocks, 0 for immortal (defaults to server config)
+    pub mortality: Option<u64>,
+    /// Optional tip in the chain's smallest unit (defaults to server config)
+    pub tip: Option<u128>,
+}
+
+/// Response payload for the /tx/prepare endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PrepareResponse {
+    /// Whether the payload was prepared
+    pub success: bool,
+    /// Opaque ticket to pass to /tx/complete
+    pub ticket: Option<String>,
+    /// The bytes to sign, as 0x-prefixed hex
+    pub signer_payload: Option<String>,
+    /// The nonce reserved for the transaction
+    pub nonce: Option<u64>,
+    /// Unix timestamp (seconds) after which the ticket is no longer accepted
+    pub expires_at: Option<u64>,
+    /// Error message if the payload could not be prepared
+    pub error: Option<String>,
+}
+
+impl PrepareResponse {
+    /// Builds a failed response carrying only an error message
+    pub fn failure(error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            ticket: None,
+            signer_payload: None,
+            nonce: None,
+            expires_at: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Request payload for the /tx/complete endpoint
+#[derive(Debug, Deserialize)]
+pub struct CompleteRequest {
+    /// The ticket returned by /tx/prepare
+    pub ticket: String,
+    /// The signature over `signer_payload`, as 0x-prefixed hex
+    pub signature: String,
+    /// The signature scheme (defaults to "sr25519")
+    #[serde(default)]
+    pub scheme: SignatureScheme,
+    /// Lifecycle stage to wait for before responding (defaults to "finalized")
+    #[serde(default)]
+    pub wait_for: TxStage,
+}
+
+/// A transaction body waiting for its signature
+pub struct PreparedTransaction {
+    /// The encoded call
+    pub call: RawCall,
+    /// Signed extension parameters, including the reserved nonce
+    pub signing_params: SigningParams,
+    /// The account that has to sign
+    pub account_id: AccountId32,
+    /// Description of the call for the transaction's receipt
+    pub call_info: CallInfo,
+    /// The nonce's lease, which keeps the gap check away while the ticket is open
+    pub lease: NonceLease,
+}
+
+impl PreparedTransaction {
+    /// Builds the transaction body from the stored call and parameters
+    fn partial(
+        &self,
+        client: &OnlineClient<SubstrateConfig>,
+    ) -> Result<PartialExtrinsic<SubstrateConfig, OnlineClient<SubstrateConfig>>, String> {
+        create_partial_transaction(client, &self.call, &self.signing_params)
+            .map_err(|e| format!("Failed to create transaction: {:?}", e))
+    }
+}
+
+/// A prepared transaction and the moment its ticket expires
+struct Ticket {
+    /// The transaction waiting for its signature
+    prepared: PreparedTransaction,
+    /// When the ticket stops being accepted
+    expires_at: Instant,
+}
+
+/// Thread-safe in-memory store of prepared signing tickets
+#[derive(Clone)]
+pub struct TicketStore {
+    /// Tickets keyed by ticket ID
+    tickets: Arc<Mutex<HashMap<String, Ticket>>>,
+    /// How long a ticket stays valid
+    ttl: Duration,
+    /// Most open tickets per account
+    max_per_account: usize,
+}
+
+impl TicketStore {
+    /// Creates an empty store whose tickets are valid for `ttl`, with at
+    /// most `max_per_account` open tickets per account
+    pub fn new(ttl: Duration, max_per_account: usize) -> Self {
+        Self {
+            tickets: Arc::new(Mutex::new(HashMap::new())),
+            ttl,
+            max_per_account,
+        }
+    }
+
+    /// Checks whether an account may open another ticket
+    ///
+    /// Expired tickets that haven't been pruned yet don't count.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The account is below the limit
+    /// * `Err(String)` - Error message for the client
+    pub async fn check_capacity(&self, account_id: &AccountId32) -> Result<(), String> {
+        let tickets = self.tickets.lock().await;
+        self.ensure_capacity(&tickets, account_id)
+    }
+
+    /// Counts an account's open tickets against the limit
+    fn ensure_capacity(
+        &self,
+        tickets: &HashMap<String, Ticket>,
+        account_id: &AccountId32,
+    ) -> Result<(), String> {
+        let now = Instant::now();
+        let open = tickets
+            .values()
+            .filter(|ticket| ticket.expires_at > now && ticket.prepared.account_id == *account_id)
+            .count();
+        if open >= self.max_per_account {
+            return Err(format!(
+                "Account already holds {} open ticket(s), the maximum; complete or let some expire first",
+                open
+            ));
+        }
+        Ok(())
+    }
+
+    /// Stores a prepared transaction under a new ticket
+    ///
+    /// # Arguments
+    /// * `client` - The blockchain client, used to build the signer payload
+    /// * `prepared` - The transaction body and its reserved nonce
+    ///
+    /// # Returns
+    /// * `Ok(PrepareResponse)` - The ticket and the bytes to sign
+    /// * `Err(String)` - If the transaction body could not be built, or the
+    ///   account holds too many open tickets
+    pub async fn issue(
+        &self,
+        client: &OnlineClient<SubstrateConfig>,
+        prepared: PreparedTransaction,
+    ) -> Result<PrepareResponse, String> {
+        let signer_payload = prepared.partial(client)?.signer_payload();
+        let ticket_id = uuid::Uuid::new_v4().to_string();
+        let nonce = prepared.signing_params.nonce;
+
+        // Checked again under the lock, for prepares that raced past check_capacity
+        let mut tickets = self.tickets.lock().await;
+        self.ensure_capacity(&tickets, &prepared.account_id)?;
+        let ticket = Ticket {
+            prepared,
+            expires_at: Instant::now() + self.ttl,
+        };
+        tickets.insert(ticket_id.clone(), ticket);
+        drop(tickets);
+        log::info!("🎫 Issued ticket {} for nonce {}", ticket_id, nonce);
+
+        Ok(PrepareResponse {
+            success: true,
+            ticket: Some(ticket_id),
+            signer_payload: Some(format!("0x{}", hex::encode(signer_payload))),
+            nonce: Some(nonce),
+            expires_at: Some(unix_now() + self.ttl.as_secs()),
+            error: None,
+        })
+    }
+
+    /// Attaches a signature to a ticket's transaction
+    ///
+    /// The signature is checked against the ticket's account before the
+    /// ticket is used up, so a wrong signature can be corrected and retried.
+    /// An expired ticket is removed and its nonce released.
+    ///
+    /// # Arguments
+    /// * `client` - The blockchain client the transaction will be submitted through
+    /// * `nonce_manager` - Receives the nonce of an expired ticket
+    /// * `ticket_id` - The ticket returned by /tx/prepare
+    /// * `signature` - The signature over the signer payload, as hex
+    /// * `scheme` - The signature scheme
+    ///
+    /// # Returns
+    /// * `Ok((ManagedTransaction, CallInfo))` - The signed transaction and its receipt description
+    /// * `Err(String)` - Error message for the client
+    pub async fn complete(
+        &self,
+        client: &OnlineClient<SubstrateConfig>,
+        nonce_manager: &NonceManager,
+        ticket_id: &str,
+        signature: &str,
+        scheme: SignatureScheme,
+    ) -> Result<(ManagedTransaction, CallInfo), String> {
+        let mut tickets = self.tickets.lock().await;
+
+        let ticket = tickets.get(ticket_id).ok_or("Unknown ticket")?;
+        if ticket.expires_at <= Instant::now() {
+            if let Some(ticket) = tickets.remove(ticket_id) {
+                drop(tickets);
+                let prepared = ticket.prepared;
+                nonce_manager
+                    .release_nonce(&prepared.account_id, prepared.signing_params.nonce)
+                    .await;
+            }
+            return Err("Ticket expired".to_string());
+        }
+
+        let bytes = hex::decode(signature.trim().trim_start_matches("0x"))
+            .map_err(|e| format!("Invalid signature hex: {}", e))?;
+        let signature = parse_signature(&bytes, scheme)?;
+        let partial = ticket.prepared.partial(client)?;
+        if !verify_signature(
+            &signature,
+            &partial.signer_payload(),
+            &ticket.prepared.account_id,
+        ) {
+            return Err("Signature does not match the ticket's account".to_string());
+        }
+
+        // The signature is good: the ticket is used up
+        let prepared = tickets.remove(ticket_id).ok_or("Unknown ticket")?.prepared;
+        let address = MultiAddress::Id(prepared.account_id.clone());
+        let extrinsic = partial.sign_with_address_and_signature(&address, &signature);
+        log::info!("🎫 Completed ticket {}", ticket_id);
+
+        let tx = ManagedTransaction {
+            extrinsic,
+            account_id: prepared.account_id,
+            nonce: prepared.signing_params.nonce,
+            tip: prepared.signing_params.tip,
+            reserved: true,
+            signing_path: SigningPath::External,
+            lease: Some(prepared.lease),
+        };
+        Ok((tx, prepared.call_info))
+    }
+
+    /// Removes expired tickets and releases their nonces
+    ///
+    /// Nonces are released highest first, so that a run of expired tickets
+    /// for one account rewinds all the way to the lowest of them.
+    pub async fn prune(&self, nonce_manager: &NonceManager) {
+        let now = Instant::now();
+        let mut expired: Vec<Ticket> = {
+            let mut tickets = self.tickets.lock().await;
+            let ids: Vec<String> = tickets
+                .iter()
+                .filter(|(_, ticket)| ticket.expires_at <= now)
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.iter().filter_map(|id| tickets.remove(id)).collect()
+        };
+        if expired.is_empty() {
+            return;
+        }
+
+        log::info!("🧹 Pruning {} expired ticket(s)", expired.len());
+        expired.sort_by(|a, b| {
+            b.prepared
+                .signing_params
+                .nonce
+                .cmp(&a.prepared.signing_params.nonce)
+        });
+        for ticket in expired {
+            nonce_manager
+                .release_nonce(
+                    &ticket.prepared.account_id,
+                    ticket.prepared.signing_params.nonce,
+                )
+                .await;
+        }
+    }
+}
+
+/// Current Unix time in seconds
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
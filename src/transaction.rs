@@ -1,71 +1,237 @@
 // src/transaction.rs
-use log;
-use subxt::{ext::sp_core::sr25519::Pair, tx::PairSigner, OnlineClient, SubstrateConfig};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use subxt::{
+    backend::legacy::LegacyRpcMethods,
+    config::{DefaultExtrinsicParams, DefaultExtrinsicParamsBuilder, ExtrinsicParams},
+    tx::{PartialExtrinsic, SubmittableExtrinsic},
+    utils::{AccountId32, MultiAddress, H256},
+    Metadata, OnlineClient, SubstrateConfig,
+};
+
+use crate::nonce_manager::NonceLease;
+use crate::retry::FailureKind;
+use crate::signer::TxSigner;
+
+/// Tunable extrinsic parameters for a single transaction
+///
+/// Resolved from the server defaults and any per-request overrides
+/// (see `Config::tx_params`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxParams {
+    /// How many blocks the transaction stays valid for, counted from the
+    /// latest finalized block (`None` = immortal)
+    pub mortality_period: Option<u64>,
+    /// Tip paid to the block author on top of the fee; a higher tip raises
+    /// the transaction's priority in the pool
+    pub tip: u128,
+}
+
+/// A signed transaction together with the nonce it reserved
+///
+/// The account and nonce travel with the extrinsic so that whoever submits it
+/// (a request handler or a background job) can hand the nonce back to the
+/// nonce manager if the transaction fails.
+pub struct ManagedTransaction {
+    /// The signed extrinsic, ready for submission
+    pub extrinsic: subxt::tx::SubmittableExtrinsic<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    /// The account that signed the transaction
+    pub account_id: AccountId32,
+    /// The nonce reserved from the nonce manager
+    pub nonce: u64,
+    /// The tip the transaction was signed with
+    pub tip: u128,
+    /// How the transaction was signed
+    pub signing_path: SigningPath,
+    /// Whether the nonce was reserved from the nonce manager, which then has
+    /// to get it back if the transaction fails (false for client-signed transactions)
+    pub reserved: bool,
+    /// The reservation's lease, held for as long as the transaction may still
+    /// be submitted (`None` when someone else holds it, e.g. a batch)
+    pub lease: Option<NonceLease>,
+}
+
+/// What to do when a transaction can't be signed with the reserved nonce
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SigningStrategy {
+    /// Fail the request; the reserved nonce is handed back
+    Strict,
+    /// Sign with the node's next nonce after a nonce error, then move the
+    /// nonce cache past it
+    #[default]
+    Fallback,
+}
+
+impl SigningStrategy {
+    /// Whether a failed explicit signature is tried again with the node's next nonce
+    ///
+    /// Only nonce errors (stale, future or usurped, see `FailureKind`) fall
+    /// back. Another nonce can't help when the node is unreachable or the
+    /// signer refuses, and signing again would only hide those errors.
+    fn falls_back_on(self, error: &subxt::Error) -> bool {
+        self == Self::Fallback
+            && matches!(
+                FailureKind::from_submit_error(error),
+                FailureKind::Stale | FailureKind::Future | FailureKind::Usurped
+            )
+    }
+}
+
+impl FromStr for SigningStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "strict" => Ok(Self::Strict),
+            "fallback" => Ok(Self::Fallback),
+            other => Err(format!("Unknown signing strategy '{}'", other)),
+        }
+    }
+}
+
+/// How a transaction's signature came about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SigningPath {
+    /// Signed here with the nonce reserved from the nonce manager
+    Explicit,
+    /// Signed here with the node's next nonce after explicit signing failed
+    Fallback,
+    /// Signed by the client (relayed or completed from a ticket)
+    External,
+}
+
+/// A transaction signed by `create_signed_transaction_with_nonce`
+pub struct SignedTransaction {
+    /// The signed extrinsic, ready for submission
+    pub extrinsic: SubmittableExtrinsic<SubstrateConfig, OnlineClient<SubstrateConfig>>,
+    /// The nonce the transaction was actually signed with
+    pub nonce: u64,
+    /// Whether the explicit nonce or the fallback was used
+    pub path: SigningPath,
+}
+
+/// A call that is already SCALE-encoded
+///
+/// Lets an encoded call be signed again (e.g. with a new nonce when a
+/// transaction is retried) without knowing which static or dynamic payload
+/// it was built from.
+#[derive(Debug, Clone)]
+pub struct RawCall(pub Vec<u8>);
+
+impl subxt::tx::Payload for RawCall {
+    fn encode_call_data_to(
+        &self,
+        _metadata: &Metadata,
+        out: &mut Vec<u8>,
+    ) -> Result<(), subxt::ext::subxt_core::Error> {
+        out.extend_from_slice(&self.0);
+        Ok(())
+    }
+}
 
 /// Creates a signed transaction with explicit nonce handling for blockchain submission
 ///
-/// This function provides a robust approach to transaction signing by attempting
-/// multiple methods to ensure successful transaction creation:
-/// 1. Try using explicit nonce (preferred for proper nonce management)
-/// 2. Fallback to default signing (subxt handles nonce internally)
+/// The transaction is signed with the explicit nonce first, which keeps the
+/// nonce manager in charge of sequencing. What happens when that fails
+/// depends on the signing strategy:
+/// - `Strict` -> the error is returned
+/// - `Fallback` -> after a nonce error, the node's next nonce
+///   (`system_accountNextIndex`, which counts the account's transactions in
+///   the pool) is used instead; any other error is returned
 ///
-/// The explicit nonce method is preferred because it allows our nonce manager
-/// to maintain proper sequencing of transactions, preventing nonce conflicts
-/// in concurrent transaction scenarios.
+/// The returned transaction says which path was taken and which nonce was
+/// used, so callers can bring the nonce manager in line after a fallback.
 ///
 /// # Arguments
 /// * `client` - The blockchain client for transaction creation
+/// * `rpc` - Raw RPC methods, for the fallback's nonce lookup
 /// * `call` - The extrinsic call to be executed on the blockchain
 /// * `signer` - The cryptographic signer (account) for the transaction
 /// * `nonce` - The specific nonce value to use for this transaction
+/// * `params` - Mortality and tip for this transaction
+/// * `strategy` - Whether a failed explicit signature may fall back
 ///
 /// # Returns
-/// * `Ok(SubmittableExtrinsic)` - A signed transaction ready for submission
-/// * `Err(subxt::Error)` - If both signing methods fail
+/// * `Ok(SignedTransaction)` - A signed transaction ready for submission
+/// * `Err(subxt::Error)` - If signing failed (and the fallback too, if allowed)
 ///
 /// # Example Usage
 /// ```rust
 /// let call = chain_a::tx().template().do_something(42);
-/// let pair_signer = PairSigner::new(pair);
-/// let signed_tx = create_signed_transaction_with_nonce(&client, &call, &pair_signer, nonce).await?;
+/// let signer = LocalSigner::from_suri("ed25519://Alice")?;
+/// let params = config.tx_params(None, None);
+/// let signed = create_signed_transaction_with_nonce(&client, &rpc, &call, &signer, nonce, &params, SigningStrategy::Strict).await?;
 /// ```
 pub async fn create_signed_transaction_with_nonce<Call>(
     client: &OnlineClient<SubstrateConfig>,
+    rpc: &LegacyRpcMethods<SubstrateConfig>,
     call: &Call,
-    signer: &PairSigner<SubstrateConfig, Pair>,
+    signer: &dyn TxSigner,
     nonce: u64,
-) -> Result<
-    subxt::tx::SubmittableExtrinsic<SubstrateConfig, OnlineClient<SubstrateConfig>>,
-    subxt::Error,
->
+    params: &TxParams,
+    strategy: SigningStrategy,
+) -> Result<SignedTransaction, subxt::Error>
 where
     Call: subxt::tx::Payload,
 {
     log::info!(
-        "🔧 Attempting to create signed transaction with nonce: {}",
-        nonce
+        "🔧 Attempting to create signed transaction with nonce: {}, mortality: {:?}, tip: {}",
+        nonce,
+        params.mortality_period,
+        params.tip
     );
 
     // Method 1: Try explicit nonce approach (preferred for nonce management)
     // This allows our nonce manager to maintain proper transaction sequencing
-    if let Ok(tx) = try_with_explicit_nonce(client, call, signer, nonce).await {
-        log::info!("✅ Successfully used explicit nonce method");
-        return Ok(tx);
+    let error = match try_with_explicit_nonce(client, call, signer, nonce, params).await {
+        Ok(extrinsic) => {
+            log::info!("✅ Successfully used explicit nonce method");
+            return Ok(SignedTransaction {
+                extrinsic,
+                nonce,
+                path: SigningPath::Explicit,
+            });
+        }
+        Err(e) => e,
+    };
+
+    if !strategy.falls_back_on(&error) {
+        log::error!(
+            "❌ Explicit nonce signing failed ({:?}): {:?}",
+            strategy,
+            error
+        );
+        return Err(error);
     }
 
-    // Method 2: Fallback to default signing (subxt handles nonce internally)
-    // This is less ideal because it bypasses our nonce management system
-    // but provides a safety net if the explicit method fails
-    log::warn!("⚠️ Explicit nonce failed, falling back to default signing method");
+    // Method 2: Fallback to the node's next nonce
+    // This bypasses our nonce management system, so the nonce that was
+    // used is reported back to the caller. Unlike the storage nonce, the
+    // node's next nonce counts the account's transactions in the pool, so
+    // the fallback doesn't collide with them.
+    log::warn!(
+        "⚠️ Explicit nonce failed ({:?}), falling back to the node's next nonce",
+        error
+    );
 
-    match client
-        .tx()
-        .create_signed(call, signer, Default::default())
-        .await
-    {
-        Ok(tx) => {
-            log::info!("✅ Successfully used fallback signing method");
-            Ok(tx)
+    let fallback = async {
+        let pool_nonce = rpc.system_account_next_index(&signer.account_id()).await?;
+        let extrinsic = try_with_explicit_nonce(client, call, signer, pool_nonce, params).await?;
+        Ok::<_, subxt::Error>(SignedTransaction {
+            extrinsic,
+            nonce: pool_nonce,
+            path: SigningPath::Fallback,
+        })
+    };
+
+    match fallback.await {
+        Ok(signed) => {
+            log::info!(
+                "✅ Successfully used fallback signing method (nonce {})",
+                signed.nonce
+            );
+            Ok(signed)
         }
         Err(e) => {
             log::error!("❌ All signing methods failed: {:?}", e);
@@ -82,29 +248,32 @@ where
 /// - Maintaining proper transaction ordering
 /// - Enabling transaction retry mechanisms with the same nonce
 ///
-/// The function uses DefaultExtrinsicParamsBuilder to construct transaction
-/// parameters with the specified nonce value, then creates an offline-signed
+/// The function builds the transaction body with the specified nonce value,
+/// has the signer sign its payload, then attaches the signature to produce a
 /// transaction that can be submitted to the blockchain.
 ///
 /// # Arguments
 /// * `client` - The blockchain client for accessing transaction APIs
 /// * `call` - The extrinsic call to be signed
-/// * `signer` - The cryptographic signer for the transaction
+/// * `signer` - The signer for the transaction, local or remote
 /// * `nonce` - The specific nonce value to embed in the transaction
+/// * `params` - Mortality and tip for this transaction
 ///
 /// # Returns
 /// * `Ok(SubmittableExtrinsic)` - Successfully created signed transaction
-/// * `Err(subxt::Error)` - If transaction creation fails (e.g., invalid parameters)
+/// * `Err(subxt::Error)` - If transaction creation or signing fails
 ///
 /// # Technical Details
 /// This function creates an "offline" signed transaction, meaning it doesn't
 /// query the blockchain for current state during signing. All required
-/// parameters (including nonce) are provided explicitly.
+/// parameters (including nonce) are provided explicitly. The only chain read
+/// is the finalized block header used to anchor a mortal transaction.
 async fn try_with_explicit_nonce<Call>(
     client: &OnlineClient<SubstrateConfig>,
     call: &Call,
-    signer: &PairSigner<SubstrateConfig, Pair>,
+    signer: &dyn TxSigner,
     nonce: u64,
+    params: &TxParams,
 ) -> Result<
     subxt::tx::SubmittableExtrinsic<SubstrateConfig, OnlineClient<SubstrateConfig>>,
     subxt::Error,
@@ -112,19 +281,110 @@ async fn try_with_explicit_nonce<Call>(
 where
     Call: subxt::tx::Payload,
 {
-    // Import the DefaultExtrinsicParamsBuilder for constructing transaction parameters
-    use subxt::config::DefaultExtrinsicParamsBuilder;
-
-    // Build transaction parameters with the explicit nonce
-    // The builder pattern allows us to set specific parameters while
-    // using defaults for others (like tip, mortality, etc.)
-    let params = DefaultExtrinsicParamsBuilder::<SubstrateConfig>::new()
-        .nonce(nonce) // Set the specific nonce value
-        .build(); // Finalize the parameters
+    // Resolve the parameters with the explicit nonce once, so the body
+    // built before and after signing is the same
+    let params = signing_params_with_nonce(client, nonce, params).await?;
 
-    // Create the signed transaction offline (without querying chain state)
+    // Build the transaction body offline (without querying chain state)
     // This ensures the nonce we specify is actually used in the transaction
-    client.tx().create_signed_offline(call, signer, params)
+    let signer_payload = create_partial_transaction(client, call, &params)?.signer_payload();
+
+    // The signer only ever sees the payload, wherever its key lives. The
+    // body isn't Send, so it is rebuilt rather than held across the await.
+    let signature = signer
+        .sign(&signer_payload)
+        .await
+        .map_err(subxt::Error::Other)?;
+    let address = MultiAddress::Id(signer.account_id());
+    Ok(create_partial_transaction(client, call, &params)?
+        .sign_with_address_and_signature(&address, &signature))
+}
+
+/// Signed extension parameters with every chain-dependent value resolved
+///
+/// Holds the nonce, tip and mortality checkpoint, so the same parameters
+/// always produce the same signer payload, however long ago they were
+/// resolved.
+#[derive(Debug, Clone)]
+pub struct SigningParams {
+    /// The nonce embedded in the transaction
+    pub nonce: u64,
+    /// The tip embedded in the transaction
+    pub tip: u128,
+    /// Mortality checkpoint block (number, hash) and period (`None` = immortal)
+    pub mortality: Option<(u64, H256, u64)>,
+}
+
+impl SigningParams {
+    /// Converts the resolved values into subxt's extrinsic parameters
+    fn build(
+        &self,
+    ) -> <DefaultExtrinsicParams<SubstrateConfig> as ExtrinsicParams<SubstrateConfig>>::Params {
+        let mut builder = DefaultExtrinsicParamsBuilder::<SubstrateConfig>::new()
+            .tip(self.tip)
+            .nonce(self.nonce);
+        if let Some((number, hash, period)) = self.mortality {
+            builder = builder.mortal_unchecked(number, hash, period);
+        }
+        builder.build()
+    }
+}
+
+/// Resolves the signing parameters for a signer outside this service
+///
+/// Uses exactly the parameters `try_with_explicit_nonce` would (explicit
+/// nonce, mortality anchored at the latest finalized block, tip), so a
+/// transaction completed with them is identical to one signed here.
+///
+/// # Arguments
+/// * `client` - The blockchain client, used to fetch the finalized block
+/// * `nonce` - The specific nonce value to embed in the transaction
+/// * `params` - Mortality and tip for this transaction
+pub async fn signing_params_with_nonce(
+    client: &OnlineClient<SubstrateConfig>,
+    nonce: u64,
+    params: &TxParams,
+) -> Result<SigningParams, subxt::Error> {
+    let mortality = match params.mortality_period {
+        Some(period) => {
+            // at_latest() returns the latest finalized block
+            let checkpoint = client.blocks().at_latest().await?;
+            Some((checkpoint.number().into(), checkpoint.hash(), period))
+        }
+        None => None,
+    };
+
+    Ok(SigningParams {
+        nonce,
+        tip: params.tip,
+        mortality,
+    })
+}
+
+/// Creates an unsigned transaction body from resolved signing parameters
+///
+/// The caller hands `signer_payload()` to the external signer and attaches
+/// the signature with `sign_with_address_and_signature`.
+///
+/// # Arguments
+/// * `client` - The blockchain client for transaction creation
+/// * `call` - The extrinsic call to be executed on the blockchain
+/// * `params` - Parameters from `signing_params_with_nonce`
+///
+/// # Returns
+/// * `Ok(PartialExtrinsic)` - The transaction body, waiting for a signature
+/// * `Err(subxt::Error)` - If the call doesn't match the metadata
+pub fn create_partial_transaction<Call>(
+    client: &OnlineClient<SubstrateConfig>,
+    call: &Call,
+    params: &SigningParams,
+) -> Result<PartialExtrinsic<SubstrateConfig, OnlineClient<SubstrateConfig>>, subxt::Error>
+where
+    Call: subxt::tx::Payload,
+{
+    client
+        .tx()
+        .create_partial_signed_offline(call, params.build())
 }
 
 // .create_signed(call, signer, Default::default())
@@ -151,3 +411,34 @@ where
 // Packages: call data + signature + nonce + fees + mortality
 
 // Returns: A complete, ready-to-send transaction
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::{core::client::Error as ClientError, types::ErrorObjectOwned};
+    use subxt::error::RpcError;
+
+    /// An error like the one the node answers an invalid transaction with
+    fn pool_error(code: i32, data: &str) -> subxt::Error {
+        let object = ErrorObjectOwned::owned(code, "Invalid Transaction", Some(data));
+        subxt::Error::Rpc(RpcError::ClientError(Box::new(ClientError::Call(object))))
+    }
+
+    #[test]
+    fn only_nonce_errors_fall_back() {
+        let stale = pool_error(1010, "Transaction is outdated");
+        let future = pool_error(1010, "Transaction will be valid in the future");
+        let usurped = pool_error(1014, "Priority is too low");
+        let exhausted = pool_error(1010, "Inability to pay some fees");
+        let unreachable = subxt::Error::Rpc(RpcError::SubscriptionDropped);
+        let refused = subxt::Error::Other("Remote signer refused".to_string());
+
+        for nonce_error in [&stale, &future, &usurped] {
+            assert!(SigningStrategy::Fallback.falls_back_on(nonce_error));
+            assert!(!SigningStrategy::Strict.falls_back_on(nonce_error));
+        }
+        for other in [&exhausted, &unreachable, &refused] {
+            assert!(!SigningStrategy::Fallback.falls_back_on(other));
+        }
+    }
+}
@@ -0,0 +1,171 @@
+// src/users.rs
+//
+// Derived accounts for end users
+//
+// Every end user gets their own on-chain account without us storing a key
+// per user: the key is derived from one master signer along the hard path
+// `//users//{user_id}`, i.e. the key `Pair::from_string` would resolve for
+// "{master}//users//{user_id}". Deriving is deterministic, so "creating" a
+// user's account just means deriving it for the first time; nothing is
+// written anywhere.
+//
+// Requests submit as a user with `"signer": "user:{user_id}"`. The derived
+// signer goes through the usual nonce-managed path; the nonce manager only
+// ever sees the derived AccountId32. Derivation only runs one way, so every
+// account derived since startup is remembered to recognise it later (see
+// `user_of`).
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use subxt::utils::AccountId32;
+
+use crate::signer::{SignatureScheme, TxSigner};
+
+/// Prefix of signer names that refer to a derived user account
+pub const USER_SIGNER_PREFIX: &str = "user:";
+
+/// Longest accepted user ID
+const MAX_USER_ID_LEN: usize = 64;
+
+/// Request payload for the POST /users endpoint
+#[derive(Debug, Deserialize)]
+pub struct CreateUserRequest {
+    /// The end user's ID, as used in `"signer": "user:{user_id}"`
+    pub user_id: String,
+}
+
+/// A user's derived account, as returned by the /users endpoints
+#[derive(Debug, Clone, Serialize)]
+pub struct UserAccount {
+    /// The end user's ID
+    pub user_id: String,
+    /// The derived account's SS58 address
+    pub address: String,
+    /// The derived public key, as 0x-prefixed hex
+    pub public_key: String,
+    /// The derived key's signature scheme (that of the master signer)
+    pub scheme: SignatureScheme,
+    /// The derivation path below the master secret
+    pub derivation_path: String,
+}
+
+/// Derives user accounts from the master signer
+///
+/// Cheap to clone; the master signer is shared.
+#[derive(Clone)]
+pub struct UserAccounts {
+    /// The signer every user account is derived from
+    master: Arc<dyn TxSigner>,
+    /// User IDs of the accounts derived so far, keyed by account
+    derived: Arc<Mutex<HashMap<[u8; 32], String>>>,
+}
+
+impl UserAccounts {
+    /// Creates a deriver for a master signer that can derive keys
+    pub fn new(master: Arc<dyn TxSigner>) -> Self {
+        Self {
+            master,
+            derived: Arc::default(),
+        }
+    }
+
+    /// Derives the signer of a user's account
+    ///
+    /// # Returns
+    /// * `Ok(Arc<dyn TxSigner>)` - The user's signer
+    /// * `Err(String)` - If the user ID is invalid
+    pub fn signer(&self, user_id: &str) -> Result<Arc<dyn TxSigner>, String> {
+        validate_user_id(user_id)?;
+        let signer = self.master.derive(&["users", user_id])?;
+        self.derived
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(signer.account_id().0, user_id.to_string());
+        Ok(signer)
+    }
+
+    /// The user an account was derived for, if it was derived since startup
+    pub fn user_of(&self, account_id: &AccountId32) -> Option<String> {
+        self.derived
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(&account_id.0)
+            .cloned()
+    }
+
+    /// Describes a user's derived account
+    pub fn account(&self, user_id: &str) -> Result<UserAccount, String> {
+        let signer = self.signer(user_id)?;
+        Ok(UserAccount {
+            user_id: user_id.to_string(),
+            address: signer.account_id().to_string(),
+            public_key: format!("0x{}", hex::encode(signer.public_key())),
+            scheme: signer.scheme(),
+            derivation_path: format!("//users//{}", user_id),
+        })
+    }
+}
+
+/// Checks that a user ID is a single, unambiguous path segment
+fn validate_user_id(user_id: &str) -> Result<(), String> {
+    let valid = !user_id.is_empty()
+        && user_id.len() <= MAX_USER_ID_LEN
+        && user_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '@'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid user ID: use 1 to {} letters, digits, '-', '_', '.' and '@'",
+            MAX_USER_ID_LEN
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signer::LocalSigner;
+
+    fn users() -> UserAccounts {
+        UserAccounts::new(Arc::new(LocalSigner::from_suri("//Master").unwrap()))
+    }
+
+    #[test]
+    fn user_ids_are_single_path_segments() {
+        for valid in ["42", "alice@example.com", "team-a_user.1", &"x".repeat(64)] {
+            assert!(validate_user_id(valid).is_ok(), "{}", valid);
+        }
+        for invalid in ["", "a/b", "a//b", "with space", "ünïcode", &"x".repeat(65)] {
+            assert!(validate_user_id(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn accounts_are_derived_deterministically() {
+        let first = users().account("42").unwrap();
+        let again = users().account("42").unwrap();
+        assert_eq!(first.address, again.address);
+        assert_eq!(first.public_key, again.public_key);
+        assert_eq!(first.derivation_path, "//users//42");
+
+        // The same key the master's secret URI resolves to along the path
+        let expected = LocalSigner::from_suri("//Master//users//42").unwrap();
+        assert_eq!(first.address, expected.account_id().to_string());
+        assert_ne!(first.address, users().account("43").unwrap().address);
+    }
+
+    #[test]
+    fn derived_accounts_are_recognised() {
+        let users = users();
+        let signer = users.signer("42").unwrap();
+        assert_eq!(users.user_of(&signer.account_id()).as_deref(), Some("42"));
+
+        let other = LocalSigner::from_suri("//Master//users//43").unwrap();
+        assert_eq!(users.user_of(&other.account_id()), None);
+    }
+}
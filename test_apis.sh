@@ -1,6 +1,7 @@
 #!/bin/bash
 
 # Test script for the Chain A Backend API
+# Signs with //Alice, so start the server with DEV_MODE=true
 
 BASE_URL="http://127.0.0.1:3001"
 
@@ -36,4 +37,11 @@ curl -s -X POST "${BASE_URL}/do-something" \
   -d '{"value": 100}' | jq . || echo "Do something failed or jq not available"
 echo -e "\n"
 
+# Test 6: Batch of do-something transactions with consecutive nonces
+echo "6. Testing batch endpoint with values 1..5..."
+curl -s -X POST "${BASE_URL}/tx/batch" \
+  -H "Content-Type: application/json" \
+  -d '{"values": [1, 2, 3, 4, 5], "wait_for": "in_block"}' | jq . || echo "Batch failed or jq not available"
+echo -e "\n"
+
 echo "✅ All tests completed!"